@@ -0,0 +1,129 @@
+use ::arithmetic::montgomery::{MontgomeryContext, Residue};
+use num::bigint::BigInt;
+use num::Integer;
+use num::One;
+use num::Signed;
+use std::mem;
+
+/// # Montgomery Ladder
+///
+/// Calculates `base^exponent mod modulus` for a secret exponent.
+///
+/// In contrast to `BigInt::modpow`, the sequence of operations does not depend
+/// on the exponent: the ladder runs exactly `bits` iterations, each consisting of
+/// one multiplication and one squaring, regardless of the value and the actual
+/// bit length of the exponent. Therefore, `bits` must be derived from public
+/// information only, e.g. the bit length of the group order `q`.
+///
+/// Odd moduli use Montgomery multiplication on a fixed amount of limbs
+/// (see `MontgomeryContext`). Instead of selecting the operands by the exponent bit,
+/// each iteration exchanges them using `Residue::conditional_swap` before and after
+/// the step. Hence, both the control flow and the accessed memory are independent
+/// of the exponent, apart from the conversion of the exponent into bytes.
+///
+/// Even moduli do not admit Montgomery multiplication and fall back to `BigInt`
+/// arithmetic, whose timing depends on the operands. They are not side-channel protected.
+///
+/// - base: The base, which may be public
+/// - exponent: The secret, non-negative exponent having at most `bits` bits
+/// - bits: The public amount of bits to process
+/// - modulus: The positive modulus
+///
+/// # Panics
+///
+/// Panics if the exponent is negative or has more than `bits` bits,
+/// as the result would be wrong otherwise.
+pub fn ladder_pow(base: &BigInt, exponent: &BigInt, bits: usize, modulus: &BigInt) -> BigInt {
+    assert!(!exponent.is_negative(), "the exponent must not be negative");
+    assert!(exponent.bits() <= bits, "the exponent has more than {} bits", bits);
+
+    // the bytes are padded to the public length, so that reading a bit
+    // never depends on the actual length of the exponent
+    let (_, mut bytes) = exponent.to_bytes_le();
+    bytes.resize(bits.div_ceil(8), 0);
+
+    let bit = |i: usize| u64::from((bytes[i / 8] >> (i % 8)) & 1);
+
+    if let Some(context) = MontgomeryContext::cached(modulus) {
+        // invariant: r1 = r0 * base, both in Montgomery form
+        let mut r0: Residue = context.one();
+        let mut r1: Residue = context.to_montgomery(base);
+
+        for i in (0..bits).rev() {
+            // for a set bit, (r0, r1) becomes (r0 * r1, r1^2) instead of (r0^2, r0 * r1)
+            let bit = bit(i);
+
+            r0.conditional_swap(&mut r1, bit);
+            context.mul(&mut r1, &r0);
+            context.square(&mut r0);
+            r0.conditional_swap(&mut r1, bit);
+        }
+
+        return context.from_montgomery(&r0);
+    }
+
+    // invariant: r1 = r0 * base
+    let mut r0 = BigInt::one().mod_floor(modulus);
+    let mut r1 = base.mod_floor(modulus);
+
+    for i in (0..bits).rev() {
+        if bit(i) == 1 {
+            mem::swap(&mut r0, &mut r1);
+        }
+
+        r1 = (&r0 * &r1).mod_floor(modulus);
+        r0 = (&r0 * &r0).mod_floor(modulus);
+
+        if bit(i) == 1 {
+            mem::swap(&mut r0, &mut r1);
+        }
+    }
+
+    r0
+}
+
+#[cfg(test)]
+mod constant_time_test {
+
+    use ::arithmetic::constant_time::ladder_pow;
+    use ::num::bigint::{BigInt, RandBigInt};
+    use ::num::{One, Zero};
+    use ::rand;
+
+    #[test]
+    fn test_matches_modpow() {
+        let p = BigInt::from(2199023255867i64);
+        let q = BigInt::from(1099511627933i64);
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..100 {
+            let base = rng.gen_bigint_range(&BigInt::zero(), &p);
+            let exponent = rng.gen_bigint_range(&BigInt::zero(), &q);
+
+            assert_eq!(base.modpow(&exponent, &p), ladder_pow(&base, &exponent, q.bits(), &p));
+        }
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        let p = BigInt::from(23);
+
+        assert_eq!(BigInt::one(), ladder_pow(&BigInt::from(4), &BigInt::zero(), 4, &p));
+        assert_eq!(BigInt::from(4), ladder_pow(&BigInt::from(4), &BigInt::one(), 4, &p));
+        assert_eq!(BigInt::one(), ladder_pow(&BigInt::from(4), &BigInt::from(11), 4, &p));
+        assert_eq!(BigInt::zero(), ladder_pow(&BigInt::zero(), &BigInt::from(3), 4, &p));
+        assert_eq!(BigInt::zero(), ladder_pow(&BigInt::from(5), &BigInt::from(3), 4, &BigInt::one()));
+
+        // a negative base is reduced first
+        assert_eq!(BigInt::from(18), ladder_pow(&BigInt::from(-19), &BigInt::from(3), 4, &p));
+
+        // even moduli do not admit Montgomery multiplication
+        assert_eq!(BigInt::from(3), ladder_pow(&BigInt::from(3), &BigInt::from(5), 4, &BigInt::from(16)));
+    }
+
+    #[test]
+    #[should_panic(expected = "the exponent has more than 4 bits")]
+    fn test_exponent_too_large() {
+        ladder_pow(&BigInt::from(4), &BigInt::from(16), 4, &BigInt::from(23));
+    }
+}
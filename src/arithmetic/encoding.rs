@@ -0,0 +1,100 @@
+use ::arithmetic::group::Group;
+use ::arithmetic::group_element::GroupElement;
+use ::arithmetic::mod_int::ModInt;
+use ::arithmetic::scalar::Scalar;
+use ::error::Error;
+use std::sync::Arc;
+
+/// Append the amount of elements of a sequence as four big-endian bytes.
+pub(crate) fn write_length(bytes: &mut Vec<u8>, length: usize) {
+    assert!(length <= u32::MAX as usize, "too many elements to encode");
+
+    bytes.extend_from_slice(&(length as u32).to_be_bytes());
+}
+
+/// Append the fixed-width encodings of all given scalars (see `Scalar::to_bytes`).
+pub(crate) fn write_scalars(bytes: &mut Vec<u8>, values: &[Scalar]) {
+    for value in values {
+        bytes.extend_from_slice(&value.to_bytes());
+    }
+}
+
+/// Append the fixed-width encodings of all given elements (see `GroupElement::to_bytes`).
+pub(crate) fn write_elements(bytes: &mut Vec<u8>, values: &[GroupElement]) {
+    for value in values {
+        bytes.extend_from_slice(&value.to_bytes());
+    }
+}
+
+/// # Byte Reader
+///
+/// Reads fixed-width encodings from a byte slice, rejecting all values
+/// which are not reduced or not elements of the group.
+pub(crate) struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        ByteReader { bytes }
+    }
+
+    fn take(&mut self, amount: usize) -> Result<&'a [u8], Error> {
+        if self.bytes.len() < amount {
+            return Err(Error::InvalidEncoding);
+        }
+
+        let (taken, rest) = self.bytes.split_at(amount);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    /// Read the amount of elements of a sequence, each of which is encoded in `element_length` bytes.
+    /// Fails if the remaining bytes are too few to hold all elements.
+    pub fn read_length(&mut self, element_length: usize) -> Result<usize, Error> {
+        let mut length = [0u8; 4];
+        length.copy_from_slice(self.take(4)?);
+        let length = u32::from_be_bytes(length) as usize;
+
+        match length.checked_mul(element_length) {
+            Some(total) if total <= self.bytes.len() => Ok(length),
+            _ => Err(Error::InvalidEncoding)
+        }
+    }
+
+    /// Read a scalar, i.e. a value within `Z_q`.
+    pub fn read_scalar(&mut self, group: &Arc<Group>) -> Result<Scalar, Error> {
+        let bytes = self.take(ModInt::byte_length(&group.q.value))?;
+        let value = ModInt::from_bytes(bytes, group.q.value.clone())?;
+
+        Ok(Scalar::new(group, value.value))
+    }
+
+    /// Read the given amount of scalars.
+    pub fn read_scalars(&mut self, amount: usize, group: &Arc<Group>) -> Result<Vec<Scalar>, Error> {
+        (0..amount).map(|_| self.read_scalar(group)).collect()
+    }
+
+    /// Read an element of the subgroup of order `q`.
+    pub fn read_element(&mut self, group: &Arc<Group>) -> Result<GroupElement, Error> {
+        let bytes = self.take(ModInt::byte_length(&group.p.value))?;
+        let value = ModInt::from_bytes(bytes, group.p.value.clone())?;
+
+        GroupElement::new(group, value.value)
+    }
+
+    /// Read the given amount of elements of the subgroup of order `q`.
+    pub fn read_elements(&mut self, amount: usize, group: &Arc<Group>) -> Result<Vec<GroupElement>, Error> {
+        (0..amount).map(|_| self.read_element(group)).collect()
+    }
+
+    /// Ensure that all bytes have been read.
+    pub fn finish(self) -> Result<(), Error> {
+        if !self.bytes.is_empty() {
+            return Err(Error::InvalidEncoding);
+        }
+
+        Ok(())
+    }
+}
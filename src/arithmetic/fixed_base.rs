@@ -0,0 +1,202 @@
+use ::arithmetic::montgomery::{MontgomeryContext, Residue};
+use num::bigint::BigInt;
+use num::Integer;
+use num::One;
+use num::Signed;
+
+/// The amount of exponent bits processed per table lookup.
+const WINDOW_BITS: usize = 4;
+
+/// # Fixed-Base Table
+///
+/// Precomputed powers of a fixed base, which speed up repeated exponentiations
+/// of the same base, e.g. of the generator `g` or the public value `h`.
+///
+/// The exponent is split into windows of `WINDOW_BITS` bits. For every window `i`,
+/// the table stores `base^(d * 2^(WINDOW_BITS * i))` for all digits `d`, so that
+/// an exponentiation requires a single multiplication per window and no squarings.
+/// For odd moduli, the entries are kept in Montgomery form (see `MontgomeryContext`).
+///
+/// Every exponentiation performs the same amount of multiplications, independent
+/// of the exponent. For odd moduli, each lookup scans all entries of the window and
+/// selects the one of the digit using `Residue::conditional_assign`, so that neither
+/// the control flow nor the accessed memory depend on the exponent. Tables of even
+/// moduli index their entries by the digit and are not side-channel protected.
+#[derive(Clone, Debug)]
+pub struct FixedBaseTable {
+    bits: usize,
+    entries: Entries,
+}
+
+#[derive(Clone, Debug)]
+enum Entries {
+    Montgomery(MontgomeryContext, Vec<Vec<Residue>>),
+    Plain(BigInt, Vec<Vec<BigInt>>),
+}
+
+impl FixedBaseTable {
+    /// Precompute the table for exponents having at most the given amount of bits.
+    ///
+    /// - base: The fixed base
+    /// - modulus: The positive modulus
+    /// - bits: The maximum amount of bits of the exponents, e.g. the bit length of `q`
+    pub fn new(base: &BigInt, modulus: &BigInt, bits: usize) -> Self {
+        let window_count = bits.div_ceil(WINDOW_BITS);
+
+        let entries = match MontgomeryContext::new(modulus) {
+            Some(context) => {
+                let windows = precompute(context.to_montgomery(base), context.one(), window_count, |a, b| context.mul(a, b));
+                Entries::Montgomery(context, windows)
+            }
+            None => {
+                let windows = precompute(base.mod_floor(modulus), BigInt::one().mod_floor(modulus), window_count, |a, b| {
+                    *a = (&*a * b).mod_floor(modulus)
+                });
+                Entries::Plain(modulus.clone(), windows)
+            }
+        };
+
+        FixedBaseTable { bits, entries }
+    }
+
+    /// Returns `base^exponent mod modulus`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the exponent is negative or has more bits than the table supports.
+    pub fn pow(&self, exponent: &BigInt) -> BigInt {
+        assert!(!exponent.is_negative(), "the exponent must not be negative");
+        assert!(exponent.bits() <= self.bits, "the exponent has more than {} bits", self.bits);
+
+        let (_, mut bytes) = exponent.to_bytes_le();
+        bytes.resize((self.bits.div_ceil(WINDOW_BITS) * WINDOW_BITS).div_ceil(8), 0);
+
+        match self.entries {
+            Entries::Montgomery(ref context, ref windows) => {
+                // scan all entries, so that the accessed memory does not depend on the digit
+                let select = |entries: &[Residue], digit: usize| {
+                    let mut selected = context.one();
+                    for (d, entry) in entries.iter().enumerate() {
+                        selected.conditional_assign(entry, equals(d, digit));
+                    }
+                    selected
+                };
+
+                let result = evaluate(windows, &bytes, context.one(), select, |a, b| context.mul(a, b));
+                context.from_montgomery(&result)
+            }
+            Entries::Plain(ref modulus, ref windows) => {
+                // the entries are indexed by the digit, i.e. this path is not side-channel protected
+                let select = |entries: &[BigInt], digit: usize| entries[digit].clone();
+
+                evaluate(windows, &bytes, BigInt::one().mod_floor(modulus), select, |a, b| {
+                    *a = (&*a * b).mod_floor(modulus)
+                })
+            }
+        }
+    }
+}
+
+/// Compute the entries of all windows, using the given multiplication in place.
+fn precompute<T: Clone, F: Fn(&mut T, &T)>(base: T, one: T, window_count: usize, mul: F) -> Vec<Vec<T>> {
+    let mut windows: Vec<Vec<T>> = Vec::with_capacity(window_count);
+
+    // base^(2^(WINDOW_BITS * i)) of the current window i
+    let mut window_base = base;
+
+    for _ in 0..window_count {
+        let mut entries: Vec<T> = Vec::with_capacity(1 << WINDOW_BITS);
+        let mut entry = one.clone();
+
+        for _ in 0..(1 << WINDOW_BITS) {
+            entries.push(entry.clone());
+            mul(&mut entry, &window_base);
+        }
+
+        // the entry following the last digit is the base of the next window
+        window_base = entry;
+        windows.push(entries);
+    }
+
+    windows
+}
+
+/// Multiply the entries selected by the digits of the given little-endian exponent,
+/// using the given function to look up the entry of a digit within a window.
+fn evaluate<T, S, F>(windows: &[Vec<T>], bytes: &[u8], one: T, select: S, mul: F) -> T
+    where S: Fn(&[T], usize) -> T, F: Fn(&mut T, &T) {
+    let mut result = one;
+
+    for (i, entries) in windows.iter().enumerate() {
+        let mut digit: usize = 0;
+        for k in 0..WINDOW_BITS {
+            let bit = i * WINDOW_BITS + k;
+            digit |= (((bytes[bit / 8] >> (bit % 8)) & 1) as usize) << k;
+        }
+
+        mul(&mut result, &select(entries, digit));
+    }
+
+    result
+}
+
+/// Returns one if both digits are equal and zero otherwise, without branching.
+fn equals(a: usize, b: usize) -> u64 {
+    // digits are less than 2^WINDOW_BITS, hence the subtraction only wraps for equal digits
+    ((a ^ b) as u64).wrapping_sub(1) >> 63
+}
+
+#[cfg(test)]
+mod fixed_base_test {
+
+    use ::arithmetic::fixed_base::{equals, FixedBaseTable, WINDOW_BITS};
+    use ::num::bigint::{BigInt, RandBigInt};
+    use ::num::{One, Zero};
+    use ::rand;
+
+    #[test]
+    fn test_matches_modpow() {
+        let p = BigInt::from(2199023255867i64);
+        let q = BigInt::from(1099511627933i64);
+        let g = BigInt::from(4);
+
+        let table = FixedBaseTable::new(&g, &p, q.bits());
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..100 {
+            let exponent = rng.gen_bigint_range(&BigInt::zero(), &q);
+            assert_eq!(g.modpow(&exponent, &p), table.pow(&exponent));
+        }
+
+        assert_eq!(BigInt::one(), table.pow(&BigInt::zero()));
+        assert_eq!(g.modpow(&(q.clone() - BigInt::one()), &p), table.pow(&(q - BigInt::one())));
+    }
+
+    #[test]
+    fn test_even_modulus() {
+        let modulus = BigInt::from(1u64 << 40);
+        let table = FixedBaseTable::new(&BigInt::from(3), &modulus, 40);
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..20 {
+            let exponent = rng.gen_bigint_range(&BigInt::zero(), &modulus);
+            assert_eq!(BigInt::from(3).modpow(&exponent, &modulus), table.pow(&exponent));
+        }
+    }
+
+    #[test]
+    fn test_equals() {
+        for a in 0..(1 << WINDOW_BITS) {
+            for b in 0..(1 << WINDOW_BITS) {
+                assert_eq!((a == b) as u64, equals(a, b));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the exponent has more than 4 bits")]
+    fn test_exponent_too_large() {
+        let table = FixedBaseTable::new(&BigInt::from(4), &BigInt::from(23), 4);
+        table.pow(&BigInt::from(16));
+    }
+}
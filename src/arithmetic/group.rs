@@ -0,0 +1,597 @@
+use ::arithmetic::mod_int::From;
+use ::arithmetic::mod_int::ModInt;
+use ::arithmetic::montgomery;
+use num::bigint::BigInt;
+use num::bigint::RandBigInt;
+use num::Integer;
+use num::One;
+use num::Signed;
+use num::Zero;
+use rand;
+use rand::prng::ChaChaRng;
+use rand::{Rng, SeedableRng};
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// The amount of Miller-Rabin rounds applied when testing for primality.
+/// The probability of a composite passing all rounds is at most `4^-rounds`.
+pub const MILLER_RABIN_ROUNDS: usize = 40;
+
+/// Small primes used to sieve out candidates before running Miller-Rabin.
+const SMALL_PRIMES: [u32; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41,
+    43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// # Validation Error
+///
+/// Describes which check failed when validating group parameters or a public key.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash)]
+pub enum ValidationError {
+    /// The modulus `p` is not prime.
+    ModulusNotPrime,
+    /// The order `q` is not prime.
+    OrderNotPrime,
+    /// The order `q` does not divide `p - 1`.
+    OrderNotDividingModulus,
+    /// The generator `g` is not an element of the subgroup of order `q`.
+    /// This includes the case where `g` is not reduced modulo `p`.
+    GeneratorNotInSubgroup,
+    /// The generator `g` is equal to one.
+    GeneratorIsOne,
+    /// The public value `h` is not an element of the subgroup of order `q`.
+    /// This includes the case where `h` is not reduced modulo `p`.
+    PublicValueNotInSubgroup,
+    /// The public value `h` is equal to one.
+    PublicValueIsOne,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let description = match *self {
+            ValidationError::ModulusNotPrime => "the modulus p is not prime",
+            ValidationError::OrderNotPrime => "the order q is not prime",
+            ValidationError::OrderNotDividingModulus => "the order q does not divide p - 1",
+            ValidationError::GeneratorNotInSubgroup => "the generator g is not in the subgroup of order q",
+            ValidationError::GeneratorIsOne => "the generator g is equal to one",
+            ValidationError::PublicValueNotInSubgroup => "the public value h is not in the subgroup of order q",
+            ValidationError::PublicValueIsOne => "the public value h is equal to one",
+        };
+
+        write!(f, "{}", description)
+    }
+}
+
+impl Error for ValidationError {}
+
+/// # Group Parameters
+///
+/// The public parameters `(p, q, g)` of a cyclic subgroup of `Z_p^*`
+/// having prime order `q` and being generated by `g`.
+///
+/// The moduli of the contained ModInts are set as expected by the
+/// remaining parts of this library:
+/// - `p` and `q` have a zero modulus,
+/// - `g` is reduced modulo `p`.
+#[derive(Eq, PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+pub struct Group {
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    pub p: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    pub q: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    pub g: ModInt,
+}
+
+impl Group {
+    /// Create the group parameters from the given plain integers.
+    ///
+    /// - p: The modulus of the group `Z_p^*`
+    /// - q: The prime order of the subgroup generated by `g`
+    /// - g: The generator of the subgroup of order `q`
+    pub fn new(p: BigInt, q: BigInt, g: BigInt) -> Self {
+        Group {
+            p: ModInt::from_value_modulus(p.clone(), BigInt::zero()),
+            q: ModInt::from_value_modulus(q, BigInt::zero()),
+            g: ModInt::from_value_modulus(g, p),
+        }
+    }
+
+    /// Generate new group parameters, where `p = 2q + 1` is a safe prime
+    /// of exactly `bit_length` bits and `g` generates the subgroup of
+    /// quadratic residues having prime order `q`.
+    ///
+    /// - bit_length: The amount of bits of the modulus `p`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bit length is smaller than 3.
+    pub fn generate(bit_length: usize) -> Self {
+        Group::generate_with_rng(bit_length, &mut rand::thread_rng())
+    }
+
+    /// Generate new group parameters as described in `Group::generate`.
+    /// The result is deterministic, i.e. the same seed always results in
+    /// the same group parameters.
+    ///
+    /// - bit_length: The amount of bits of the modulus `p`
+    /// - seed: The seed of the random number generator used to sample candidates
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bit length is smaller than 3.
+    pub fn generate_with_seed(bit_length: usize, seed: [u8; 32]) -> Self {
+        Group::generate_with_rng(bit_length, &mut ChaChaRng::from_seed(seed))
+    }
+
+    fn generate_with_rng<R: Rng>(bit_length: usize, rng: &mut R) -> Self {
+        assert!(bit_length >= 3, "the bit length must be at least 3");
+
+        let one = BigInt::one();
+        let two = BigInt::from(2);
+
+        // q has one bit less than p, with its most significant bit set
+        let lower_bound: BigInt = one.clone() << (bit_length - 2);
+        let upper_bound: BigInt = lower_bound.clone() << 1;
+
+        let (p, q) = loop {
+            let mut q = rng.gen_bigint_range(&lower_bound, &upper_bound);
+            if q.is_even() {
+                q += one.clone();
+            }
+
+            let p = q.clone() * two.clone() + one.clone();
+
+            // sieve both candidates first, as this is much cheaper
+            // than running the full primality test
+            if has_small_factor(&q) || has_small_factor(&p) {
+                continue;
+            }
+
+            if miller_rabin(&q, MILLER_RABIN_ROUNDS, rng) && miller_rabin(&p, MILLER_RABIN_ROUNDS, rng) {
+                break (p, q);
+            }
+        };
+
+        // Squaring any element a with 1 < a < p - 1 results in a quadratic residue
+        // different from one. As the subgroup of quadratic residues has prime order q,
+        // any such element is a generator of it.
+        let a = rng.gen_bigint_range(&two, &(p.clone() - one));
+        let g = a.modpow(&two, &p);
+
+        Group::new(p, q, g)
+    }
+
+    /// Validate the group parameters, i.e. check that
+    /// - `p` and `q` are prime,
+    /// - `q` divides `p - 1`,
+    /// - `g` lies in the subgroup of order `q`, i.e. `g^q = 1 mod p`,
+    /// - `g` is not equal to one.
+    ///
+    /// Parameters from untrusted sources must be validated before use,
+    /// as otherwise the soundness of the zero-knowledge proofs is not guaranteed.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if !is_probable_prime(&self.p.value) {
+            return Err(ValidationError::ModulusNotPrime);
+        }
+
+        if !is_probable_prime(&self.q.value) {
+            return Err(ValidationError::OrderNotPrime);
+        }
+
+        if !(self.p.value.clone() - BigInt::one()).is_multiple_of(&self.q.value) {
+            return Err(ValidationError::OrderNotDividingModulus);
+        }
+
+        if !self.is_subgroup_element(&self.g) {
+            return Err(ValidationError::GeneratorNotInSubgroup);
+        }
+
+        if self.g.value.is_one() {
+            return Err(ValidationError::GeneratorIsOne);
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the given element is reduced modulo `p`
+    /// and lies in the subgroup of order `q`.
+    pub fn is_subgroup_element(&self, element: &ModInt) -> bool {
+        element.modulus == self.p.value
+            && element.value > BigInt::zero()
+            && element.value < self.p.value
+            && !self.q.value.is_negative()
+            && element.value.modpow(&self.q.value, &self.p.value).is_one()
+    }
+
+    /// Returns true if the given element is reduced modulo `p` and lies in the
+    /// subgroup of order `q`, assuming valid parameters (see `Group::validate`).
+    ///
+    /// For safe primes, the subgroup consists of the quadratic residues,
+    /// which can be checked without any exponentiation (see `Group::is_quadratic_residue`).
+    /// Otherwise, this is equivalent to `Group::is_subgroup_element`.
+    pub fn is_element(&self, element: &ModInt) -> bool {
+        if self.is_safe_prime_group() {
+            self.is_quadratic_residue(element)
+        } else {
+            self.is_subgroup_element(element)
+        }
+    }
+
+    /// Returns true if `p = 2q + 1`. For valid parameters, the subgroup of order `q`
+    /// then consists of exactly the quadratic residues modulo `p`.
+    pub fn is_safe_prime_group(&self) -> bool {
+        self.p.value == BigInt::from(2) * &self.q.value + BigInt::one()
+    }
+
+    /// Returns true if the given element is reduced modulo `p` and
+    /// a quadratic residue, i.e. if its Jacobi symbol is one.
+    ///
+    /// For valid parameters with `p = 2q + 1` (see `Group::is_safe_prime_group`),
+    /// this is equivalent to `Group::is_subgroup_element`, but considerably faster
+    /// as it requires no exponentiation.
+    pub fn is_quadratic_residue(&self, element: &ModInt) -> bool {
+        element.modulus == self.p.value
+            && element.value > BigInt::zero()
+            && element.value < self.p.value
+            && jacobi_symbol(&element.value, &self.p.value) == 1
+    }
+}
+
+/// # Primality Test
+///
+/// Tests whether the given integer is prime by applying the Miller-Rabin
+/// test with `MILLER_RABIN_ROUNDS` randomly chosen bases.
+///
+/// Returns `false` for all integers smaller than two.
+pub fn is_probable_prime(n: &BigInt) -> bool {
+    miller_rabin(n, MILLER_RABIN_ROUNDS, &mut rand::thread_rng())
+}
+
+/// # Jacobi Symbol
+///
+/// Computes the Jacobi symbol `(a / n)`, which is `1`, `-1` or `0`.
+/// For a prime `n`, this is the Legendre symbol, i.e. `1` if `a` is
+/// a non-zero quadratic residue modulo `n`.
+///
+/// The binary algorithm works on 64-bit limbs and requires only
+/// subtractions and shifts instead of divisions.
+///
+/// # Panics
+///
+/// Panics if `n` is not positive and odd.
+pub fn jacobi_symbol(a: &BigInt, n: &BigInt) -> i8 {
+    assert!(n > &BigInt::zero() && n.is_odd(), "the Jacobi symbol requires a positive odd modulus");
+
+    let mut a = to_limbs(&a.mod_floor(n));
+    let mut n = to_limbs(n);
+    let mut result: i8 = 1;
+
+    while let Some(zeros) = trailing_zeros(&a) {
+        // (2 / n) = -1 if and only if n = 3 or n = 5 modulo 8
+        shift_right(&mut a, zeros);
+        if zeros % 2 == 1 && (n[0] & 7 == 3 || n[0] & 7 == 5) {
+            result = -result;
+        }
+
+        // quadratic reciprocity for the odd a and n
+        if less_than(&a, &n) {
+            ::std::mem::swap(&mut a, &mut n);
+            if a[0] & 3 == 3 && n[0] & 3 == 3 {
+                result = -result;
+            }
+        }
+
+        // (a / n) = ((a - n) / n), where a - n is even
+        subtract(&mut a, &n);
+    }
+
+    if n.len() == 1 && n[0] == 1 {
+        result
+    } else {
+        0
+    }
+}
+
+/// Split the given non-negative integer into little-endian 64-bit limbs
+/// without leading zero limbs.
+fn to_limbs(value: &BigInt) -> Vec<u64> {
+    montgomery::to_limbs(value, value.bits().div_ceil(64).max(1))
+}
+
+fn trim(limbs: &mut Vec<u64>) {
+    while limbs.len() > 1 && limbs[limbs.len() - 1] == 0 {
+        limbs.pop();
+    }
+}
+
+/// Returns the amount of trailing zero bits, or `None` if the value is zero.
+fn trailing_zeros(limbs: &[u64]) -> Option<usize> {
+    limbs.iter()
+        .position(|limb| *limb != 0)
+        .map(|i| 64 * i + limbs[i].trailing_zeros() as usize)
+}
+
+fn shift_right(limbs: &mut Vec<u64>, bits: usize) {
+    let (words, bits) = (bits / 64, bits % 64);
+    limbs.drain(..words);
+
+    if bits > 0 {
+        for i in 0..limbs.len() {
+            let high = limbs.get(i + 1).map_or(0, |limb| limb << (64 - bits));
+            limbs[i] = (limbs[i] >> bits) | high;
+        }
+    }
+
+    trim(limbs);
+}
+
+/// Compare two trimmed little-endian integers.
+fn less_than(a: &[u64], b: &[u64]) -> bool {
+    if a.len() != b.len() {
+        return a.len() < b.len();
+    }
+
+    for (a, b) in a.iter().rev().zip(b.iter().rev()) {
+        if a != b {
+            return a < b;
+        }
+    }
+
+    false
+}
+
+/// Subtract `b` from `a`, which must not be smaller than `b`.
+fn subtract(a: &mut Vec<u64>, b: &[u64]) {
+    let mut borrow = false;
+    for (i, limb) in a.iter_mut().enumerate() {
+        let (difference, borrow_1) = limb.overflowing_sub(*b.get(i).unwrap_or(&0));
+        let (difference, borrow_2) = difference.overflowing_sub(borrow as u64);
+        *limb = difference;
+        borrow = borrow_1 || borrow_2;
+    }
+
+    trim(a);
+}
+
+/// Returns true if `n` is divisible by one of the small primes, without
+/// being equal to it.
+fn has_small_factor(n: &BigInt) -> bool {
+    SMALL_PRIMES.iter()
+        .map(|small_prime| BigInt::from(*small_prime))
+        .any(|small_prime| *n != small_prime && n.is_multiple_of(&small_prime))
+}
+
+pub(crate) fn miller_rabin<R: Rng>(n: &BigInt, rounds: usize, rng: &mut R) -> bool {
+    let one = BigInt::one();
+    let two = BigInt::from(2);
+
+    if *n < two {
+        return false;
+    }
+
+    for small_prime in SMALL_PRIMES.iter() {
+        let small_prime = BigInt::from(*small_prime);
+        if n.is_multiple_of(&small_prime) {
+            return *n == small_prime;
+        }
+    }
+
+    // write n - 1 = 2^s * d with d odd
+    let n_minus_one: BigInt = n.clone() - one.clone();
+    let mut d: BigInt = n_minus_one.clone();
+    let mut s: usize = 0;
+    while d.is_even() {
+        d >>= 1;
+        s += 1;
+    }
+
+    'witness: for _ in 0..rounds {
+        let a = rng.gen_bigint_range(&two, &n_minus_one);
+        let mut x = a.modpow(&d, n);
+
+        if x == one || x == n_minus_one {
+            continue;
+        }
+
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+
+        // a is a witness for n being composite
+        return false;
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod group_test {
+
+    use ::arithmetic::group::{Group, ValidationError, is_probable_prime, jacobi_symbol};
+    use ::arithmetic::mod_int::{From, ModInt};
+    use ::num::bigint::BigInt;
+    use ::num::traits::Pow;
+    use ::num::One;
+    use ::num::Zero;
+
+    #[test]
+    fn test_new() {
+        let group = Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(50));
+
+        assert_eq!(BigInt::from(23), group.p.value);
+        assert_eq!(BigInt::zero(), group.p.modulus);
+        assert_eq!(BigInt::from(11), group.q.value);
+        assert_eq!(BigInt::zero(), group.q.modulus);
+        assert_eq!(BigInt::from(4), group.g.value);
+        assert_eq!(BigInt::from(23), group.g.modulus);
+    }
+
+    #[test]
+    fn test_is_probable_prime() {
+        assert!(!is_probable_prime(&BigInt::from(-7)));
+        assert!(!is_probable_prime(&BigInt::zero()));
+        assert!(!is_probable_prime(&BigInt::one()));
+        assert!(is_probable_prime(&BigInt::from(2)));
+        assert!(is_probable_prime(&BigInt::from(97)));
+        assert!(is_probable_prime(&BigInt::from(2_199_023_255_867i64)));
+        // 2^127 - 1 is a Mersenne prime
+        assert!(is_probable_prime(&((BigInt::one() << 127) - BigInt::one())));
+
+        assert!(!is_probable_prime(&BigInt::from(100)));
+        // Carmichael numbers fool the Fermat test, but not Miller-Rabin
+        assert!(!is_probable_prime(&BigInt::from(561)));
+        assert!(!is_probable_prime(&BigInt::from(41_041)));
+        assert!(!is_probable_prime(&(BigInt::from(2_199_023_255_867i64) * BigInt::from(1_099_511_627_933i64))));
+    }
+
+    #[test]
+    fn test_jacobi_symbol() {
+        // Euler's criterion for the prime 23
+        let p = BigInt::from(23);
+        for a in 1..23 {
+            let a = BigInt::from(a);
+            let expected = if a.modpow(&BigInt::from(11), &p).is_one() { 1 } else { -1 };
+            assert_eq!(expected, jacobi_symbol(&a, &p));
+        }
+
+        assert_eq!(0, jacobi_symbol(&BigInt::zero(), &p));
+        assert_eq!(jacobi_symbol(&BigInt::from(5), &p), jacobi_symbol(&BigInt::from(-18), &p));
+
+        // the composite 15 = 3 * 5
+        assert_eq!(1, jacobi_symbol(&BigInt::from(2), &BigInt::from(15)));
+        assert_eq!(-1, jacobi_symbol(&BigInt::from(7), &BigInt::from(15)));
+        assert_eq!(0, jacobi_symbol(&BigInt::from(5), &BigInt::from(15)));
+    }
+
+    #[test]
+    fn test_is_quadratic_residue() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        assert!(group.is_safe_prime_group());
+        assert!(!Group::new(BigInt::from(43), BigInt::from(7), BigInt::from(4)).is_safe_prime_group());
+
+        for value in [1i64, 2, 3, 4, 5, 1680623351278, 2199023255866].iter() {
+            let element = ModInt::from_value_modulus(BigInt::from(*value), group.p.value.clone());
+            assert_eq!(group.is_subgroup_element(&element), group.is_quadratic_residue(&element));
+        }
+
+        // elements must be reduced modulo p
+        assert!(!group.is_quadratic_residue(&ModInt::from_value_modulus(BigInt::zero(), group.p.value.clone())));
+        assert!(!group.is_quadratic_residue(&ModInt::from_value(BigInt::from(4))));
+    }
+
+    #[test]
+    fn test_generate() {
+        let group = Group::generate(64);
+
+        assert_eq!(64, group.p.value.bits());
+        assert_eq!(group.q.value.clone() * BigInt::from(2) + BigInt::one(), group.p.value);
+        assert!(is_probable_prime(&group.p.value));
+        assert!(is_probable_prime(&group.q.value));
+
+        // g generates the subgroup of order q
+        assert!(!group.g.is_one());
+        assert!(group.g.clone().pow(group.q.clone()).is_one());
+    }
+
+    #[test]
+    fn test_generate_smallest() {
+        let group = Group::generate(3);
+
+        assert_eq!(BigInt::from(7), group.p.value);
+        assert_eq!(BigInt::from(3), group.q.value);
+        assert!(group.g.clone().pow(group.q.clone()).is_one());
+    }
+
+    #[test]
+    fn test_generate_with_seed() {
+        let group = Group::generate_with_seed(128, [42; 32]);
+        let same_group = Group::generate_with_seed(128, [42; 32]);
+        let other_group = Group::generate_with_seed(128, [7; 32]);
+
+        assert_eq!(group, same_group);
+        assert_ne!(group, other_group);
+
+        assert_eq!(128, group.p.value.bits());
+        assert!(is_probable_prime(&group.p.value));
+        assert!(is_probable_prime(&group.q.value));
+        assert!(group.g.clone().pow(group.q.clone()).is_one());
+    }
+
+    #[test]
+    #[should_panic(expected = "the bit length must be at least 3")]
+    fn test_generate_too_small() {
+        Group::generate(2);
+    }
+
+    #[test]
+    fn test_validate() {
+        let group = Group::new(BigInt::from(2_199_023_255_867i64), BigInt::from(1_099_511_627_933i64), BigInt::from(4));
+        assert_eq!(Ok(()), group.validate());
+
+        // the subgroup of order q = 23 of Z_47^*
+        let group = Group::new(BigInt::from(47), BigInt::from(23), BigInt::from(2));
+        assert_eq!(Ok(()), group.validate());
+
+        let generated = Group::generate(32);
+        assert_eq!(Ok(()), generated.validate());
+    }
+
+    #[test]
+    fn test_validate_failing() {
+        let group = Group::new(BigInt::from(45), BigInt::from(11), BigInt::from(4));
+        assert_eq!(Err(ValidationError::ModulusNotPrime), group.validate());
+
+        let group = Group::new(BigInt::from(23), BigInt::from(12), BigInt::from(4));
+        assert_eq!(Err(ValidationError::OrderNotPrime), group.validate());
+
+        let group = Group::new(BigInt::from(23), BigInt::from(7), BigInt::from(4));
+        assert_eq!(Err(ValidationError::OrderNotDividingModulus), group.validate());
+
+        // 5 is a generator of the whole group Z_23^* having order 22
+        let group = Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(5));
+        assert_eq!(Err(ValidationError::GeneratorNotInSubgroup), group.validate());
+
+        let group = Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(24));
+        assert_eq!(Err(ValidationError::GeneratorIsOne), group.validate());
+
+        // the former test parameters p = 5, q = 2, g = 2, where g has order 4
+        let group = Group::new(BigInt::from(5), BigInt::from(2), BigInt::from(2));
+        assert_eq!(Err(ValidationError::GeneratorNotInSubgroup), group.validate());
+    }
+
+    #[test]
+    fn test_is_subgroup_element() {
+        let group = Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4));
+
+        assert!(group.is_subgroup_element(&ModInt::from_value_modulus(BigInt::from(4), BigInt::from(23))));
+        assert!(group.is_subgroup_element(&ModInt::from_value_modulus(BigInt::from(1), BigInt::from(23))));
+        assert!(!group.is_subgroup_element(&ModInt::from_value_modulus(BigInt::from(5), BigInt::from(23))));
+        assert!(!group.is_subgroup_element(&ModInt::from_value_modulus(BigInt::from(0), BigInt::from(23))));
+        // not reduced modulo p
+        assert!(!group.is_subgroup_element(&ModInt::from_value_modulus(BigInt::from(4), BigInt::zero())));
+        assert!(!group.is_subgroup_element(&ModInt::from_value_modulus(BigInt::from(27), BigInt::zero())));
+        assert!(!group.is_subgroup_element(&ModInt::from_value_modulus(BigInt::from(4), BigInt::from(29))));
+    }
+
+    #[test]
+    fn test_is_element() {
+        // p = 2q + 1 uses the quadratic residues, p = 6q + 1 the full check
+        for &(p, q) in [(23, 11), (43, 7)].iter() {
+            let group = Group::new(BigInt::from(p), BigInt::from(q), BigInt::from(4));
+
+            for value in 0..=p {
+                let element = ModInt::from_value_modulus(BigInt::from(value), group.p.value.clone());
+                assert_eq!(group.is_subgroup_element(&element), group.is_element(&element));
+            }
+        }
+
+        // a negative order is rejected instead of panicking
+        let group = Group::new(BigInt::from(23), BigInt::from(-11), BigInt::from(4));
+        assert!(!group.is_element(&ModInt::from_value_modulus(BigInt::from(4), BigInt::from(23))));
+    }
+}
@@ -0,0 +1,380 @@
+use ::arithmetic::constant_time;
+use ::arithmetic::group::Group;
+use ::arithmetic::mod_int::ModInt;
+use ::arithmetic::mod_inverse;
+use ::arithmetic::multiexp;
+use ::arithmetic::scalar::Scalar;
+use ::error::Error;
+use num::bigint::BigInt;
+use num::traits::Pow;
+use num::One;
+use std::hash::{Hash, Hasher};
+use std::ops::{Div, Mul};
+use std::sync::Arc;
+
+/// Returns true if both groups are the same, either by reference or by their parameters.
+pub(crate) fn same_group(a: &Arc<Group>, b: &Arc<Group>) -> bool {
+    Arc::ptr_eq(a, b) || a == b
+}
+
+/// # Group Element
+///
+/// An element of the subgroup of order `q` of `Z_p^*`.
+///
+/// Every element is bound to its group and can only be created from values
+/// within the subgroup. Combining elements or scalars of different groups
+/// returns `Error::GroupMismatch` in the `checked_*` operations and panics
+/// in the corresponding operators.
+#[derive(Clone, Debug)]
+pub struct GroupElement {
+    value: BigInt,
+    group: Arc<Group>,
+}
+
+impl GroupElement {
+    /// Create the element having the given value.
+    ///
+    /// Returns `Error::ElementNotInGroup` if the value is not reduced
+    /// modulo `p` or does not lie in the subgroup of order `q` (see `Group::is_element`).
+    ///
+    /// - group: The group the element belongs to
+    /// - value: The value of the element within `[1, p)`
+    pub fn new(group: &Arc<Group>, value: BigInt) -> Result<GroupElement, Error> {
+        let element = GroupElement::new_unchecked(group, value);
+        if !group.is_element(&element.to_mod_int()) {
+            return Err(Error::ElementNotInGroup);
+        }
+
+        Ok(element)
+    }
+
+    /// Create the element having the given value without checking
+    /// its membership, e.g. for values of an already validated key.
+    pub(crate) fn new_unchecked(group: &Arc<Group>, value: BigInt) -> GroupElement {
+        GroupElement {
+            value,
+            group: group.clone(),
+        }
+    }
+
+    /// Returns the generator `g` of the given group.
+    pub fn generator(group: &Arc<Group>) -> GroupElement {
+        GroupElement::new_unchecked(group, group.g.value.clone())
+    }
+
+    /// Returns the neutral element one of the given group.
+    pub fn identity(group: &Arc<Group>) -> GroupElement {
+        GroupElement::new_unchecked(group, BigInt::one())
+    }
+
+    /// Convert the given ModInt into an element of the given group.
+    ///
+    /// Returns `Error::GroupMismatch` if the ModInt is not reduced modulo `p`
+    /// and `Error::ElementNotInGroup` if it does not lie in the subgroup.
+    pub fn from_mod_int(group: &Arc<Group>, value: &ModInt) -> Result<GroupElement, Error> {
+        if value.modulus != group.p.value {
+            return Err(Error::GroupMismatch);
+        }
+
+        GroupElement::new(group, value.value.clone())
+    }
+
+    /// Returns this element as ModInt having the modulus `p`.
+    pub fn to_mod_int(&self) -> ModInt {
+        ModInt {
+            value: self.value.clone(),
+            modulus: self.group.p.value.clone(),
+        }
+    }
+
+    /// Encode this element as the fixed-width big-endian encoding
+    /// of its value modulo `p` (see `ModInt::to_bytes`).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_mod_int().to_bytes()
+    }
+
+    /// Returns the value of this element within `[1, p)`.
+    pub fn value(&self) -> &BigInt {
+        &self.value
+    }
+
+    /// Returns the group this element belongs to.
+    pub fn group(&self) -> &Arc<Group> {
+        &self.group
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.value.is_one()
+    }
+
+    /// Compare this element with the given one.
+    ///
+    /// Returns `Error::GroupMismatch` if the elements belong to different groups.
+    pub fn checked_eq(&self, other: &GroupElement) -> Result<bool, Error> {
+        self.check_group(&other.group)?;
+
+        Ok(self.value == other.value)
+    }
+
+    /// Multiply with the given element modulo `p`.
+    ///
+    /// Returns `Error::GroupMismatch` if the elements belong to different groups.
+    pub fn checked_mul(self, rhs: GroupElement) -> Result<GroupElement, Error> {
+        self.check_group(&rhs.group)?;
+
+        let value = (self.value * rhs.value) % &self.group.p.value;
+        Ok(GroupElement::new_unchecked(&self.group, value))
+    }
+
+    /// Divide by the given element, i.e. multiply with its inverse.
+    ///
+    /// Returns `Error::GroupMismatch` if the elements belong to different groups
+    /// and `Error::NotInvertible` if the divisor has no inverse.
+    pub fn checked_div(self, rhs: GroupElement) -> Result<GroupElement, Error> {
+        self.check_group(&rhs.group)?;
+
+        self.checked_mul(rhs.invert()?)
+    }
+
+    /// Raise this element to the power of the given scalar.
+    ///
+    /// Returns `Error::GroupMismatch` if the scalar belongs to a different group.
+    pub fn checked_pow(self, rhs: &Scalar) -> Result<GroupElement, Error> {
+        self.check_group(rhs.group())?;
+
+        let value = self.value.modpow(rhs.value(), &self.group.p.value);
+        Ok(GroupElement::new_unchecked(&self.group, value))
+    }
+
+    /// Raise this element to the power of the given secret scalar using
+    /// `constant_time::ladder_pow`, processing the bit length of `q`.
+    ///
+    /// Returns `Error::GroupMismatch` if the scalar belongs to a different group.
+    pub fn checked_pow_secret(self, rhs: &Scalar) -> Result<GroupElement, Error> {
+        self.check_group(rhs.group())?;
+
+        let value = constant_time::ladder_pow(&self.value, rhs.value(), self.group.q.value.bits(), &self.group.p.value);
+        Ok(GroupElement::new_unchecked(&self.group, value))
+    }
+
+    /// Raise this element to the power of the given secret scalar
+    /// as described in `GroupElement::checked_pow_secret`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scalar belongs to a different group.
+    pub fn pow_secret(self, rhs: &Scalar) -> GroupElement {
+        match self.checked_pow_secret(rhs) {
+            Ok(result) => result,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Returns the inverse of this element modulo `p`.
+    ///
+    /// Returns `Error::NotInvertible` if the element was created without
+    /// checking its membership and has no inverse.
+    pub fn invert(self) -> Result<GroupElement, Error> {
+        match mod_inverse::mod_inverse(self.value, self.group.p.value.clone()) {
+            Some(inverse) => Ok(GroupElement::new_unchecked(&self.group, inverse)),
+            None => Err(Error::NotInvertible)
+        }
+    }
+
+    /// Returns `b_1^e_1 * b_2^e_2 * ... * b_n^e_n` using `multiexp::multiexp`.
+    ///
+    /// Returns `Error::LengthMismatch` if the amount of bases and exponents differ
+    /// and `Error::GroupMismatch` if a base or an exponent belongs to another group.
+    ///
+    /// - group: The group of the bases and exponents, which is the group of the result
+    /// - bases: The bases `b_i`
+    /// - exponents: The exponents `e_i`
+    pub fn multiexp(group: &Arc<Group>, bases: &[GroupElement], exponents: &[Scalar]) -> Result<GroupElement, Error> {
+        let foreign_base = bases.iter().any(|base| !same_group(&base.group, group));
+        let foreign_exponent = exponents.iter().any(|exponent| !same_group(exponent.group(), group));
+        if foreign_base || foreign_exponent {
+            return Err(Error::GroupMismatch);
+        }
+
+        let bases: Vec<ModInt> = bases.iter().map(GroupElement::to_mod_int).collect();
+        let exponents: Vec<ModInt> = exponents.iter().map(Scalar::to_mod_int).collect();
+
+        // the product of no factors is one, which is the identity of every group
+        let product = multiexp::multiexp(&bases, &exponents)?;
+
+        Ok(GroupElement::new_unchecked(group, product.value))
+    }
+
+    fn check_group(&self, group: &Arc<Group>) -> Result<(), Error> {
+        if same_group(&self.group, group) {
+            Ok(())
+        } else {
+            Err(Error::GroupMismatch)
+        }
+    }
+}
+
+/// Elements of different groups are never equal.
+/// Use `GroupElement::checked_eq` to detect such comparisons.
+impl PartialEq for GroupElement {
+    fn eq(&self, other: &GroupElement) -> bool {
+        self.value == other.value && same_group(&self.group, &other.group)
+    }
+}
+
+impl Eq for GroupElement {}
+
+impl Hash for GroupElement {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+        self.group.hash(state);
+    }
+}
+
+impl Mul for GroupElement {
+    type Output = GroupElement;
+
+    /// # Panics
+    ///
+    /// Panics if the elements belong to different groups.
+    /// Use `GroupElement::checked_mul` to handle this case.
+    fn mul(self, rhs: GroupElement) -> GroupElement {
+        match self.checked_mul(rhs) {
+            Ok(result) => result,
+            Err(e) => panic!("{}", e)
+        }
+    }
+}
+
+impl Div for GroupElement {
+    type Output = GroupElement;
+
+    /// # Panics
+    ///
+    /// Panics if the elements belong to different groups or the divisor has no inverse.
+    /// Use `GroupElement::checked_div` to handle these cases.
+    fn div(self, rhs: GroupElement) -> GroupElement {
+        match self.checked_div(rhs) {
+            Ok(result) => result,
+            Err(e) => panic!("{}", e)
+        }
+    }
+}
+
+impl<'a> Pow<&'a Scalar> for GroupElement {
+    type Output = GroupElement;
+
+    /// # Panics
+    ///
+    /// Panics if the scalar belongs to a different group.
+    /// Use `GroupElement::checked_pow` to handle this case.
+    fn pow(self, rhs: &'a Scalar) -> GroupElement {
+        match self.checked_pow(rhs) {
+            Ok(result) => result,
+            Err(e) => panic!("{}", e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod group_element_test {
+
+    use ::arithmetic::group::Group;
+    use ::arithmetic::group_element::GroupElement;
+    use ::arithmetic::mod_int::From;
+    use ::arithmetic::mod_int::ModInt;
+    use ::arithmetic::scalar::Scalar;
+    use ::error::Error;
+    use ::num::bigint::BigInt;
+    use ::num::traits::Pow;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_arithmetic() {
+        let group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        let g = GroupElement::generator(&group);
+
+        // 4^3 = 64 = 18 mod 23
+        let a = g.clone().pow(&Scalar::new(&group, BigInt::from(3)));
+        assert_eq!(BigInt::from(18), *a.value());
+
+        // g^3 * g^8 = g^11 = 1
+        let b = g.clone().pow(&Scalar::new(&group, BigInt::from(8)));
+        assert!((a.clone() * b.clone()).is_identity());
+        assert_eq!(b, a.clone().invert().unwrap());
+        assert_eq!(a.clone() * a.clone(), a.clone() / b);
+
+        assert_eq!(GroupElement::identity(&group), g.clone().pow(&Scalar::zero(&group)));
+        assert_eq!(a, g.pow_secret(&Scalar::new(&group, BigInt::from(3))));
+        assert_eq!(ModInt::from_value_modulus(BigInt::from(18), BigInt::from(23)), a.to_mod_int());
+    }
+
+    #[test]
+    fn test_membership() {
+        let group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+
+        assert!(GroupElement::new(&group, BigInt::from(18)).is_ok());
+
+        // 5 is not a quadratic residue modulo 23
+        for value in [0, 5, 22, 23, 41].iter() {
+            match GroupElement::new(&group, BigInt::from(*value)) {
+                Err(Error::ElementNotInGroup) => {}
+                result => panic!("expected element not in group for {}, got {:?}", value, result),
+            }
+        }
+
+        match GroupElement::from_mod_int(&group, &ModInt::from_value(BigInt::from(18))) {
+            Err(Error::GroupMismatch) => {}
+            result => panic!("expected group mismatch, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_group_mismatch() {
+        let group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        let other_group = Arc::new(Group::new(BigInt::from(47), BigInt::from(23), BigInt::from(4)));
+
+        let a = GroupElement::generator(&group);
+        let b = GroupElement::generator(&other_group);
+
+        assert_ne!(a, b);
+        assert!(a.checked_eq(&b).is_err());
+        assert!(a.clone().checked_mul(b.clone()).is_err());
+        assert!(a.clone().checked_div(b).is_err());
+        assert!(a.checked_pow(&Scalar::new(&other_group, BigInt::from(2))).is_err());
+    }
+
+    #[test]
+    fn test_multiexp() {
+        let group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        let other_group = Arc::new(Group::new(BigInt::from(47), BigInt::from(23), BigInt::from(4)));
+
+        let g = GroupElement::generator(&group);
+        let a = g.clone().pow(&Scalar::new(&group, BigInt::from(3)));
+        let exponents = [Scalar::new(&group, BigInt::from(2)), Scalar::new(&group, BigInt::from(5))];
+
+        // g^2 * g^(3 * 5) = g^17 = g^6
+        let expected = g.clone().pow(&Scalar::new(&group, BigInt::from(6)));
+        assert_eq!(expected, GroupElement::multiexp(&group, &[g.clone(), a.clone()], &exponents).unwrap());
+        assert_eq!(GroupElement::identity(&group), GroupElement::multiexp(&group, &[], &[]).unwrap());
+
+        match GroupElement::multiexp(&other_group, &[g.clone(), a.clone()], &exponents) {
+            Err(Error::GroupMismatch) => {}
+            result => panic!("expected group mismatch, got {:?}", result),
+        }
+
+        match GroupElement::multiexp(&group, &[g, a], &exponents[..1]) {
+            Err(Error::LengthMismatch { expected: 2, actual: 1 }) => {}
+            result => panic!("expected length mismatch, got {:?}", result),
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mismatched_operator_panics() {
+        let group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        let other_group = Arc::new(Group::new(BigInt::from(47), BigInt::from(23), BigInt::from(4)));
+
+        let _ = GroupElement::generator(&group) * GroupElement::generator(&other_group);
+    }
+}
@@ -0,0 +1,133 @@
+//! Deserialization of values which are bound to a group, but do not serialize
+//! its parameters, e.g. cipher texts, proofs and UCIV sets.
+//!
+//! Such values implement `DeserializeInGroup` instead of `Deserialize`, so that
+//! every decoded element is checked to lie in the subgroup of order `q`:
+//!
+//! ```
+//! extern crate crypto_rs;
+//! extern crate num;
+//! extern crate serde_json;
+//!
+//! use crypto_rs::arithmetic::group::Group;
+//! use crypto_rs::arithmetic::in_group;
+//! use crypto_rs::arithmetic::scalar::Scalar;
+//! use crypto_rs::el_gamal::ciphertext::CipherText;
+//! use crypto_rs::el_gamal::encryption::{encrypt, KeyPair};
+//! use num::bigint::BigInt;
+//!
+//! # fn main() {
+//! let key_pair = KeyPair::generate(&Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+//! let group = key_pair.public_key.group().clone();
+//!
+//! let (cipher_text, _) = encrypt(&key_pair.public_key, Scalar::new(&group, BigInt::from(3)));
+//! let json = serde_json::to_string(&cipher_text).unwrap();
+//!
+//! let decoded: CipherText = in_group::from_str(&group, &json).unwrap();
+//! assert_eq!(cipher_text, decoded);
+//! # }
+//! ```
+
+use ::arithmetic::group::Group;
+use ::arithmetic::group_element::GroupElement;
+use ::arithmetic::mod_int::ModInt;
+use ::arithmetic::scalar::Scalar;
+use serde::de::{DeserializeSeed, Error as DeError, SeqAccess, Visitor};
+use serde::Deserializer;
+use serde_json;
+use std::fmt::{self, Formatter};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// # Deserialize In Group
+///
+/// A value which can be deserialized once the group of its elements and scalars is known.
+pub trait DeserializeInGroup: Sized {
+    /// Deserialize a value whose elements and scalars belong to the given group.
+    ///
+    /// Fails if an element is not reduced modulo `p` or does not lie in the subgroup of order `q`.
+    fn deserialize_in<'de, D: Deserializer<'de>>(group: &Arc<Group>, deserializer: D) -> Result<Self, D::Error>;
+}
+
+impl<T: DeserializeInGroup> DeserializeInGroup for Vec<T> {
+    fn deserialize_in<'de, D: Deserializer<'de>>(group: &Arc<Group>, deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(VecVisitor { group, marker: PhantomData })
+    }
+}
+
+struct VecVisitor<'a, T> {
+    group: &'a Arc<Group>,
+    marker: PhantomData<T>,
+}
+
+impl<'de, 'a, T: DeserializeInGroup> Visitor<'de> for VecVisitor<'a, T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element_seed(InGroup::new(self.group))? {
+            values.push(value);
+        }
+
+        Ok(values)
+    }
+}
+
+/// # In Group
+///
+/// A `DeserializeSeed` deserializing a `DeserializeInGroup` within the given group,
+/// e.g. to decode a value nested within another message.
+pub struct InGroup<'a, T> {
+    group: &'a Arc<Group>,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T> InGroup<'a, T> {
+    pub fn new(group: &'a Arc<Group>) -> Self {
+        InGroup { group, marker: PhantomData }
+    }
+}
+
+impl<'de, 'a, T: DeserializeInGroup> DeserializeSeed<'de> for InGroup<'a, T> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        T::deserialize_in(self.group, deserializer)
+    }
+}
+
+/// Deserialize a value of the given group from its JSON representation.
+///
+/// - group: The group the elements and scalars of the value belong to
+/// - json: The JSON representation of the value
+pub fn from_str<T: DeserializeInGroup>(group: &Arc<Group>, json: &str) -> Result<T, serde_json::Error> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let value = T::deserialize_in(group, &mut deserializer)?;
+    deserializer.end()?;
+
+    Ok(value)
+}
+
+/// Convert a deserialized ModInt into an element of the given group.
+pub(crate) fn element<E: DeError>(group: &Arc<Group>, value: &ModInt) -> Result<GroupElement, E> {
+    GroupElement::from_mod_int(group, value).map_err(E::custom)
+}
+
+/// Convert deserialized ModInts into elements of the given group.
+pub(crate) fn elements<E: DeError>(group: &Arc<Group>, values: &[ModInt]) -> Result<Vec<GroupElement>, E> {
+    values.iter().map(|value| element(group, value)).collect()
+}
+
+/// Convert a deserialized ModInt into a scalar of the given group.
+pub(crate) fn scalar<E: DeError>(group: &Arc<Group>, value: &ModInt) -> Result<Scalar, E> {
+    Scalar::from_mod_int(group, value).map_err(E::custom)
+}
+
+/// Convert deserialized ModInts into scalars of the given group.
+pub(crate) fn scalars<E: DeError>(group: &Arc<Group>, values: &[ModInt]) -> Result<Vec<Scalar>, E> {
+    values.iter().map(|value| scalar(group, value)).collect()
+}
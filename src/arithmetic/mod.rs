@@ -1,5 +1,38 @@
+/// Exponentiation with secret exponents
+pub mod constant_time;
+
+/// Strict decoding of fixed-width byte encodings
+pub(crate) mod encoding;
+
+/// Precomputed powers of fixed bases
+pub mod fixed_base;
+
+/// Parameters of a cyclic group of prime order
+pub mod group;
+
+/// Elements of a cyclic group of prime order
+pub mod group_element;
+
+/// Deserialization of values bound to a group
+pub mod in_group;
+
 /// Modular arithmetic within a cyclic field
 pub mod mod_int;
 
 /// Perform the inverse operation in modular arithmetic
-pub mod mod_inverse;
\ No newline at end of file
+pub mod mod_inverse;
+
+/// Montgomery multiplication for odd moduli
+pub mod montgomery;
+
+/// Simultaneous exponentiation of many bases
+pub mod multiexp;
+
+/// Exponents of the elements of a cyclic group of prime order
+pub mod scalar;
+
+/// Hexadecimal and base64 string representations of ModInt for serde
+pub mod serde_string;
+
+/// Standardized groups of RFC 3526 and RFC 7919
+pub mod standard_groups;
\ No newline at end of file
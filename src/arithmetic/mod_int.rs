@@ -1,17 +1,24 @@
+use ::arithmetic::constant_time;
 use ::arithmetic::mod_inverse;
+use ::arithmetic::montgomery::MontgomeryContext;
+use ::error::Error;
 use num::bigint::BigInt;
 use num::bigint::RandBigInt;
+use num::bigint::Sign;
 use num::One;
 use num;
 use num::pow::Pow;
 use num::ToPrimitive;
 use num::Zero;
+use num::Integer;
 use num::Num;
+use num::Signed;
 use rand;
 use std::clone::Clone;
 use std::cmp::Ordering;
 use std::cmp::PartialEq;
 use std::cmp::PartialOrd;
+use std::hash::{Hash, Hasher};
 use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
 use std::fmt::{Formatter, Result, Display, Debug};
 use serde;
@@ -23,7 +30,6 @@ use std::result::Result as stdResult;
 
 
 /// An integer with modular operations.
-#[derive(Hash)]
 pub struct ModInt {
     /// The value.
     pub value: BigInt,
@@ -71,7 +77,16 @@ pub trait From {
     /// Create a ModInt with the given value and a zero modulus.
     fn from_value(value: BigInt) -> ModInt;
 
+    /// Create a ModInt from the given hex string and modulus.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given string is not a valid hex number.
     fn from_hex_string(hex_string: String, modulus: BigInt) -> ModInt;
+
+    /// Create a ModInt from the given hex string and modulus.
+    /// Returns `Error::InvalidHexString` if the string is not a valid hex number.
+    fn try_from_hex_string(hex_string: String, modulus: BigInt) -> stdResult<ModInt, Error>;
 }
 
 impl From for ModInt {
@@ -94,14 +109,24 @@ impl From for ModInt {
     }
 
     fn from_hex_string(hex_string: String, modulus: BigInt) -> ModInt {
-        let value = BigInt::from_str_radix(&hex_string.as_str(), 16);
+        match ModInt::try_from_hex_string(hex_string, modulus) {
+            Ok(mod_int) => mod_int,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    fn try_from_hex_string(hex_string: String, modulus: BigInt) -> stdResult<ModInt, Error> {
+        let value = match BigInt::from_str_radix(hex_string.as_str(), 16) {
+            Ok(value) => value,
+            Err(_) => return Err(Error::InvalidHexString(hex_string))
+        };
 
         let non_normalized = ModInt {
-            value: value.unwrap(),
+            value,
             modulus,
         };
 
-        non_normalized.normalize()
+        Ok(non_normalized.normalize())
     }
 }
 
@@ -125,27 +150,34 @@ impl Normalize for ModInt {
     }
 }
 
-impl Eq for ModInt {}
+impl ModInt {
+    /// Returns the value reduced to `[0, modulus)`, or the value itself
+    /// in case of a zero modulus. Congruent values share the same reduced value.
+    fn reduced_value(&self) -> BigInt {
+        if self.modulus.is_positive() {
+            self.value.mod_floor(&self.modulus)
+        } else {
+            self.value.clone()
+        }
+    }
+}
 
+/// Two ModInts are equal if they have the same modulus and their values
+/// are congruent modulo it, e.g. `21 mod 5 == 1 mod 5 == -4 mod 5`.
+/// ModInts having different moduli are never equal.
 impl PartialEq<ModInt> for ModInt {
     fn eq(&self, other: &ModInt) -> bool {
-        // we have to normalize, i.e. reduce the values
-        // whenever we have the same modulus.
-        // 21 mod 5 === 1 mod 5
-        if self.modulus > BigInt::zero() {
-            let _val: BigInt = self.value.clone();
-            let _mod: BigInt = self.modulus.clone();
-
-            let normalized_val = _val.rem(_mod);
-
-            return normalized_val.eq(&other.value);
-        } else {
-            return self.value.eq(&other.value);
-        }
+        self.modulus == other.modulus && self.reduced_value() == other.reduced_value()
     }
+}
 
-    fn ne(&self, other: &ModInt) -> bool {
-        !self.eq(other)
+impl Eq for ModInt {}
+
+/// Consistent with `PartialEq`, as only the reduced value and the modulus are hashed.
+impl Hash for ModInt {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.reduced_value().hash(state);
+        self.modulus.hash(state);
     }
 }
 
@@ -155,14 +187,11 @@ impl PartialOrd<ModInt> for ModInt {
     }
 }
 
+/// ModInts are ordered by their reduced values first and by their moduli second.
 impl Ord for ModInt {
     fn cmp(&self, other: &Self) -> Ordering {
-        let _val: BigInt = self.value.clone();
-        let _mod: BigInt = self.modulus.clone();
-
-        let normalized_val = _val.rem(_mod);
-
-        normalized_val.cmp(&other.value)
+        self.reduced_value().cmp(&other.reduced_value())
+            .then_with(|| self.modulus.cmp(&other.modulus))
     }
 }
 
@@ -256,6 +285,14 @@ impl Mul<ModInt> for ModInt {
 
     #[inline]
     fn mul(mut self, rhs: ModInt) -> ModInt {
+        if !self.value.is_negative() && !rhs.value.is_negative() {
+            if let Some(context) = MontgomeryContext::cached(&self.modulus) {
+                self.value = context.mod_mul(&self.value, &rhs.value);
+
+                return self;
+            }
+        }
+
         self.value = self.value.mul(rhs.value);
 
         self.normalize()
@@ -265,30 +302,16 @@ impl Mul<ModInt> for ModInt {
 impl Div<ModInt> for ModInt {
     type Output = ModInt;
 
+    /// # Panics
+    ///
+    /// Panics if the divisor is congruent to zero or not invertible.
+    /// Use `ModInt::checked_div` to handle these cases.
     #[inline]
-    fn div(mut self, rhs: ModInt) -> ModInt {
-        let zero = BigInt::zero();
-
-        if rhs.value.eq(&BigInt::zero()) {
-            panic!("Division by zero is not defined");
+    fn div(self, rhs: ModInt) -> ModInt {
+        match self.checked_div(rhs) {
+            Ok(result) => result,
+            Err(e) => panic!("{}", e)
         }
-
-        if self.modulus.eq(&zero) {
-            self.value = self.value.div(rhs.value)
-        } else {
-            let inv: Option<BigInt> = mod_inverse::mod_inverse(rhs.value.clone(), self.modulus.clone());
-
-            let inverse: BigInt;
-            match inv {
-                None => panic!("failed to compute inverse"),
-                Some(x) => inverse = x
-            }
-
-            self.value = self.value.mul(inverse);
-            self.value = self.value.rem(self.modulus.clone());
-        }
-
-        self.normalize()
     }
 }
 
@@ -306,33 +329,148 @@ impl Rem<ModInt> for ModInt {
 impl Pow<ModInt> for ModInt {
     type Output = ModInt;
 
+    /// # Panics
+    ///
+    /// Panics if the exponent is negative or, in case of a zero modulus,
+    /// does not fit into a `usize`. Use `ModInt::checked_pow` to handle these cases.
     #[inline]
-    fn pow(mut self, rhs: ModInt) -> ModInt {
-        let zero = BigInt::zero();
+    fn pow(self, rhs: ModInt) -> ModInt {
+        match self.checked_pow(rhs) {
+            Ok(result) => result,
+            Err(e) => panic!("{}", e)
+        }
+    }
+}
 
-        if self.modulus.eq(&zero) {
-            let usize_val: usize;
-            let result = rhs.value.to_usize();
+impl ModInt {
+    /// Divide by the given ModInt, i.e. multiply by its inverse modulo the
+    /// modulus of `self`. In case of a zero modulus, an integer division is applied.
+    ///
+    /// Returns `Error::DivisionByZero` if the divisor is congruent to zero and
+    /// `Error::NotInvertible` if the divisor has no inverse.
+    pub fn checked_div(mut self, rhs: ModInt) -> stdResult<ModInt, Error> {
+        if self.modulus.is_zero() {
+            if rhs.value.is_zero() {
+                return Err(Error::DivisionByZero);
+            }
 
-            match result {
-                Some(x) => usize_val = x,
-                None => panic!("Failed to convert BigInt to usize")
+            self.value = self.value.div(rhs.value);
+        } else {
+            let divisor: BigInt = rhs.value.mod_floor(&self.modulus);
+            if divisor.is_zero() {
+                return Err(Error::DivisionByZero);
             }
 
+            let inverse: BigInt = match mod_inverse::mod_inverse(divisor, self.modulus.clone()) {
+                Some(inverse) => inverse,
+                None => return Err(Error::NotInvertible)
+            };
+
+            self.value = self.value.mul(inverse);
+        }
+
+        Ok(self.normalize())
+    }
+
+    /// Raise this ModInt to the power of the given exponent.
+    ///
+    /// Returns `Error::ExponentOutOfRange` if the exponent is negative or,
+    /// in case of a zero modulus, does not fit into a `usize`.
+    pub fn checked_pow(mut self, rhs: ModInt) -> stdResult<ModInt, Error> {
+        if rhs.value.is_negative() {
+            return Err(Error::ExponentOutOfRange);
+        }
+
+        if self.modulus.is_zero() {
+            let usize_val: usize = match rhs.value.to_usize() {
+                Some(x) => x,
+                None => return Err(Error::ExponentOutOfRange)
+            };
+
             self.value = num::pow(self.value, usize_val)
         } else {
-            // Check whether order of the base divides the order of the exponent.
-            // Otherwise, the result is not well-defined.
-            // TODO: not sure whether this is appropriate...
-//            if ! rhs.modulus.clone().rem(self.modulus.clone()).eq(&zero) {
-//                panic!("Order of base is not compatible to the order of the exponent. Base modulus: {:?}, exponent modulus: {:?}", self.modulus.clone(), rhs.modulus.clone())
-//            }
-            let inv: BigInt = self.value.modpow(&rhs.value, &self.modulus);
+            self.value = match MontgomeryContext::cached(&self.modulus) {
+                Some(context) => context.mod_pow(&self.value, &rhs.value),
+                None => self.value.modpow(&rhs.value, &self.modulus)
+            };
+        }
 
-            self.value = inv;
+        Ok(self.normalize())
+    }
+
+    /// Raise this ModInt to the power of the given secret exponent using
+    /// `constant_time::ladder_pow`, e.g. for private keys or the randomness of an encryption.
+    ///
+    /// The exponent is reduced modulo its own modulus, e.g. the group order `q`,
+    /// and the amount of processed bits is the bit length of that modulus.
+    /// Hence, neither the value nor the bit length of the exponent is revealed.
+    ///
+    /// Returns `Error::ExponentOutOfRange` if the exponent has no positive modulus,
+    /// as its bit length would be leaked otherwise, or if this ModInt has a zero modulus.
+    pub fn checked_pow_secret(mut self, rhs: ModInt) -> stdResult<ModInt, Error> {
+        if !rhs.modulus.is_positive() || !self.modulus.is_positive() {
+            return Err(Error::ExponentOutOfRange);
         }
 
-        self.normalize()
+        let exponent = rhs.value.mod_floor(&rhs.modulus);
+        self.value = constant_time::ladder_pow(&self.value, &exponent, rhs.modulus.bits(), &self.modulus);
+
+        Ok(self.normalize())
+    }
+
+    /// Raise this ModInt to the power of the given secret exponent
+    /// as described in `ModInt::checked_pow_secret`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the exponent has no positive modulus or this ModInt has a zero modulus.
+    pub fn pow_secret(self, rhs: ModInt) -> ModInt {
+        match self.checked_pow_secret(rhs) {
+            Ok(result) => result,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Returns the amount of bytes of the fixed-width encoding of values modulo the given modulus.
+    pub fn byte_length(modulus: &BigInt) -> usize {
+        modulus.bits().div_ceil(8)
+    }
+
+    /// Encode this ModInt as its value reduced modulo the modulus, in big-endian
+    /// order and left-padded with zeros to `ModInt::byte_length(modulus)` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the modulus is not positive, as there is no fixed-width encoding
+    /// of plain integers.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        assert!(self.modulus.is_positive(), "only values with a positive modulus can be encoded");
+
+        let (_, magnitude) = self.value.mod_floor(&self.modulus).to_bytes_be();
+        let length = ModInt::byte_length(&self.modulus);
+
+        let mut bytes = vec![0u8; length - magnitude.len()];
+        bytes.extend_from_slice(&magnitude);
+
+        bytes
+    }
+
+    /// Decode a ModInt encoded by `ModInt::to_bytes`.
+    ///
+    /// Returns `Error::InvalidEncoding` if the amount of bytes does not match
+    /// `ModInt::byte_length(modulus)` or the modulus is not positive, and
+    /// `Error::ValueNotReduced` if the value is not smaller than the modulus.
+    pub fn from_bytes(bytes: &[u8], modulus: BigInt) -> stdResult<ModInt, Error> {
+        if !modulus.is_positive() || bytes.len() != ModInt::byte_length(&modulus) {
+            return Err(Error::InvalidEncoding);
+        }
+
+        let value = BigInt::from_bytes_be(Sign::Plus, bytes);
+        if value >= modulus {
+            return Err(Error::ValueNotReduced);
+        }
+
+        Ok(ModInt { value, modulus })
     }
 }
 
@@ -376,12 +514,14 @@ impl RandModInt for ModInt {
 #[cfg(test)]
 mod mod_int_tests {
     use ::arithmetic::mod_int::From;
+    use ::error::Error;
     use ::arithmetic::mod_int::ModInt;
     use ::arithmetic::mod_int::RandModInt;
     use ::num::bigint::BigInt;
     use ::num::One;
     use ::num::traits::Pow;
     use ::num::Zero;
+    use ::std::cmp::Ordering;
     use ::std::ops::Neg;
 
     #[test]
@@ -625,6 +765,39 @@ mod mod_int_tests {
         one / zero;
     }
 
+    #[test]
+    fn test_checked_div() {
+        let one: ModInt = ModInt::from_value_modulus(BigInt::from(23), BigInt::from(11));
+        let two: ModInt = ModInt::from_value_modulus(BigInt::from(2), BigInt::from(0));
+        assert_eq!(BigInt::from(6), one.checked_div(two).unwrap().value);
+
+        // 13 = 2 mod 11, i.e. the divisor must not be reduced beforehand
+        let one: ModInt = ModInt::from_value_modulus(BigInt::from(1), BigInt::from(11));
+        let thirteen: ModInt = ModInt::from_value_modulus(BigInt::from(13), BigInt::from(0));
+        assert_eq!(BigInt::from(6), one.checked_div(thirteen).unwrap().value);
+
+        let one: ModInt = ModInt::one();
+        let zero: ModInt = ModInt::zero();
+        match one.checked_div(zero) {
+            Err(Error::DivisionByZero) => (),
+            other => panic!("expected division by zero, got {:?}", other)
+        }
+
+        let one: ModInt = ModInt::from_value_modulus(BigInt::one(), BigInt::from(5));
+        let five: ModInt = ModInt::from_value(BigInt::from(5));
+        match one.checked_div(five) {
+            Err(Error::DivisionByZero) => (),
+            other => panic!("expected division by zero, got {:?}", other)
+        }
+
+        let one: ModInt = ModInt::from_value_modulus(BigInt::one(), BigInt::from(4));
+        let two: ModInt = ModInt::from_value(BigInt::from(2));
+        match one.checked_div(two) {
+            Err(Error::NotInvertible) => (),
+            other => panic!("expected a non-invertible divisor, got {:?}", other)
+        }
+    }
+
     #[test]
     fn test_rem() {
         let one: ModInt = ModInt::from_value_modulus(
@@ -710,6 +883,73 @@ mod mod_int_tests {
         assert_eq!(BigInt::from(5), result.modulus);
     }
 
+    #[test]
+    fn test_pow_negative_base() {
+        let minus_two = ModInt::from_value_modulus(BigInt::from(-2), BigInt::from(5));
+
+        assert_eq!(BigInt::from(4), minus_two.clone().pow(ModInt::from_value(BigInt::from(2))).value);
+        assert_eq!(BigInt::from(2), minus_two.pow(ModInt::from_value(BigInt::from(3))).value);
+    }
+
+    #[test]
+    fn test_checked_pow() {
+        let two: ModInt = ModInt::from_value_modulus(BigInt::from(2), BigInt::from(5));
+        let four: ModInt = ModInt::from_value(BigInt::from(4));
+        assert_eq!(BigInt::from(1), two.checked_pow(four).unwrap().value);
+
+        let two: ModInt = ModInt::from_value_modulus(BigInt::from(2), BigInt::from(5));
+        let minus_one: ModInt = ModInt::from_value(BigInt::from(-1));
+        match two.checked_pow(minus_one) {
+            Err(Error::ExponentOutOfRange) => (),
+            other => panic!("expected an invalid exponent, got {:?}", other)
+        }
+
+        let two: ModInt = ModInt::from_value(BigInt::from(2));
+        let huge: ModInt = ModInt::from_value(BigInt::one() << 128);
+        match two.checked_pow(huge) {
+            Err(Error::ExponentOutOfRange) => (),
+            other => panic!("expected an invalid exponent, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_pow_secret() {
+        let p = BigInt::from(2199023255867i64);
+        let q = BigInt::from(1099511627933i64);
+
+        let g: ModInt = ModInt::from_value_modulus(BigInt::from(4), p.clone());
+        let x: ModInt = ModInt::from_value_modulus(BigInt::from(1234567), q.clone());
+        assert_eq!(g.clone().pow(x.clone()), g.clone().pow_secret(x));
+
+        // negative exponents are reduced modulo q
+        let minus_one: ModInt = ModInt { value: BigInt::from(-1), modulus: q.clone() };
+        assert_eq!(g.clone().pow(ModInt::from_value_modulus(q.clone() - 1, q.clone())), g.clone().pow_secret(minus_one));
+
+        // exponents without modulus would leak their bit length
+        match g.checked_pow_secret(ModInt::from_value(BigInt::from(3))) {
+            Err(Error::ExponentOutOfRange) => (),
+            other => panic!("expected an invalid exponent, got {:?}", other)
+        }
+
+        let two: ModInt = ModInt::from_value(BigInt::from(2));
+        match two.checked_pow_secret(ModInt::from_value(BigInt::from(3))) {
+            Err(Error::ExponentOutOfRange) => (),
+            other => panic!("expected an invalid exponent, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_from_hex_string() {
+        let value: ModInt = ModInt::from_hex_string("ff".to_string(), BigInt::from(11));
+        assert_eq!(BigInt::from(2), value.value);
+        assert_eq!(BigInt::from(11), value.modulus);
+
+        match ModInt::try_from_hex_string("xyz".to_string(), BigInt::from(11)) {
+            Err(Error::InvalidHexString(hex_string)) => assert_eq!("xyz", hex_string),
+            other => panic!("expected an invalid hex string, got {:?}", other)
+        }
+    }
+
     #[test]
     fn test_random() {
         let rnd: ModInt = ModInt::gen_modint(ModInt::one());
@@ -723,4 +963,126 @@ mod mod_int_tests {
     fn test_random_failing() {
         ModInt::gen_modint(ModInt::zero());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_to_from_bytes() {
+        let modulus = BigInt::from(2199023255867i64);
+        assert_eq!(6, ModInt::byte_length(&modulus));
+
+        let value = ModInt::from_value_modulus(BigInt::from(258), modulus.clone());
+        let bytes = value.to_bytes();
+        assert_eq!(vec![0, 0, 0, 0, 1, 2], bytes);
+        assert_eq!(value, ModInt::from_bytes(&bytes, modulus.clone()).unwrap());
+
+        // values are reduced before encoding
+        let negative = ModInt { value: BigInt::from(-1), modulus: BigInt::from(7) };
+        assert_eq!(vec![6], negative.to_bytes());
+
+        match ModInt::from_bytes(&[0, 0, 0, 0, 1], modulus.clone()) {
+            Err(Error::InvalidEncoding) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match ModInt::from_bytes(&[7], BigInt::from(7)) {
+            Err(Error::ValueNotReduced) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert!(ModInt::from_bytes(&[], BigInt::zero()).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "only values with a positive modulus can be encoded")]
+    fn test_to_bytes_without_modulus() {
+        ModInt::from_value(BigInt::from(1)).to_bytes();
+    }
+
+    #[test]
+    fn test_equal_modulus_mismatch() {
+        let three = ModInt::from_value_modulus(BigInt::from(3), BigInt::from(5));
+        let other_three = ModInt::from_value_modulus(BigInt::from(3), BigInt::from(7));
+        let plain_three = ModInt::from_value(BigInt::from(3));
+
+        assert!(three != other_three);
+        assert!(three != plain_three);
+        assert!(plain_three != three);
+
+        // negative values are congruent to their positive representative
+        let negative = ModInt { value: BigInt::from(-2), modulus: BigInt::from(5) };
+        assert_eq!(three, negative);
+        assert_eq!(negative, three);
+    }
+
+    #[test]
+    fn test_cmp_zero_modulus() {
+        let one: ModInt = ModInt::one();
+        let two = ModInt::from_value(BigInt::from(2));
+        let six = ModInt::from_value_modulus(BigInt::from(6), BigInt::from(5));
+
+        assert!(one < two);
+        assert!(two > six);
+        assert_eq!(Ordering::Equal, six.cmp(&ModInt::from_value_modulus(BigInt::from(1), BigInt::from(5))));
+    }
+}
+
+#[cfg(test)]
+mod mod_int_properties {
+    use ::arithmetic::mod_int::ModInt;
+    use ::num::bigint::BigInt;
+    use ::proptest::prelude::*;
+    use std::cmp::Ordering;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    /// Small values and moduli, so that congruent values are generated frequently.
+    fn mod_int() -> impl Strategy<Value = ModInt> {
+        (-20i64..20, prop_oneof![Just(0i64), Just(3i64), Just(5i64)])
+            .prop_map(|(value, modulus)| ModInt { value: BigInt::from(value), modulus: BigInt::from(modulus) })
+    }
+
+    fn hash(value: &ModInt) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    proptest! {
+        #[test]
+        fn eq_is_symmetric(a in mod_int(), b in mod_int()) {
+            prop_assert_eq!(a == b, b == a);
+        }
+
+        #[test]
+        fn eq_is_transitive(a in mod_int(), b in mod_int(), c in mod_int()) {
+            if a == b && b == c {
+                prop_assert_eq!(a, c);
+            }
+        }
+
+        #[test]
+        fn eq_respects_congruence(a in mod_int(), k in -5i64..5) {
+            let congruent = ModInt { value: a.value.clone() + a.modulus.clone() * BigInt::from(k), modulus: a.modulus.clone() };
+            prop_assert_eq!(a, congruent);
+        }
+
+        #[test]
+        fn hash_is_consistent_with_eq(a in mod_int(), b in mod_int()) {
+            if a == b {
+                prop_assert_eq!(hash(&a), hash(&b));
+            }
+        }
+
+        #[test]
+        fn cmp_is_consistent_with_eq(a in mod_int(), b in mod_int()) {
+            prop_assert_eq!(a.cmp(&b) == Ordering::Equal, a == b);
+            prop_assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+        }
+
+        #[test]
+        fn cmp_is_transitive(a in mod_int(), b in mod_int(), c in mod_int()) {
+            if a <= b && b <= c {
+                prop_assert!(a <= c);
+            }
+        }
+    }
+}
@@ -1,35 +1,57 @@
 use ::num::bigint::BigInt;
+use ::num::Integer;
 use ::num::Zero;
 use ::num::One;
 
 ///
 /// # Modular Inverse
 ///
-/// Calculates the modular inverse `a^-1 mod m`
+/// Calculates the modular inverse `a^-1 mod m`.
+/// Returns `None` if the modulus is not positive or if `a` has no inverse modulo `m`.
 ///
 /// ## Credits
 /// Inspired by [simon-andrews/rust-modinverse](https://github.com/simon-andrews/rust-modinverse)
 ///
 pub fn mod_inverse(a: BigInt, m: BigInt) -> Option<BigInt> {
-    let (g, x, _) = extended_gcd(a.clone(), m.clone());
-    if g != BigInt::one() {
+    if m <= BigInt::zero() {
         return None;
-    } else {
-        // actually use the modulus instead of the remainder
-        // operator "%" which behaves differently for negative values
-        // -> https://stackoverflow.com/questions/31210357/is-there-a-modulus-not-remainder-function-operation
-        let modulus: BigInt = (x % m.clone()) + m;
+    }
 
-        return Some(modulus);
+    // actually use the modulus instead of the remainder
+    // operator "%" which behaves differently for negative values
+    // -> https://stackoverflow.com/questions/31210357/is-there-a-modulus-not-remainder-function-operation
+    let (g, x, _) = extended_gcd(a.mod_floor(&m), m.clone());
+    if g != BigInt::one() {
+        None
+    } else {
+        Some(x.mod_floor(&m))
     }
 }
 
 fn extended_gcd(a: BigInt, b: BigInt) -> (BigInt, BigInt, BigInt) {
     assert!(a < b);
     if a == BigInt::zero() {
-        return (b, BigInt::zero(), BigInt::one());
+        (b, BigInt::zero(), BigInt::one())
     } else {
         let (g, x, y) = extended_gcd(b.clone() % a.clone(), a.clone());
-        return (g, y - (b / a) * x.clone(), x.clone());
+        (g, y - (b / a) * x.clone(), x)
+    }
+}
+
+#[cfg(test)]
+mod mod_inverse_test {
+
+    use ::arithmetic::mod_inverse::mod_inverse;
+    use ::num::bigint::BigInt;
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(Some(BigInt::from(6)), mod_inverse(BigInt::from(2), BigInt::from(11)));
+        assert_eq!(Some(BigInt::from(6)), mod_inverse(BigInt::from(13), BigInt::from(11)));
+        assert_eq!(Some(BigInt::from(5)), mod_inverse(BigInt::from(-2), BigInt::from(11)));
+
+        assert_eq!(None, mod_inverse(BigInt::from(2), BigInt::from(4)));
+        assert_eq!(None, mod_inverse(BigInt::from(11), BigInt::from(11)));
+        assert_eq!(None, mod_inverse(BigInt::from(2), BigInt::from(0)));
     }
 }
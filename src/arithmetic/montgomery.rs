@@ -0,0 +1,408 @@
+use num::bigint::{BigInt, BigUint, Sign};
+use num::Integer;
+use num::One;
+use num::Signed;
+use std::cell::RefCell;
+use std::hint;
+use std::rc::Rc;
+
+/// The amount of exponent bits processed per table lookup of `MontgomeryContext::pow`.
+const WINDOW_BITS: usize = 4;
+
+/// The amount of contexts kept by `MontgomeryContext::cached` per thread.
+const CACHE_SIZE: usize = 8;
+
+thread_local! {
+    static CONTEXTS: RefCell<Vec<Rc<MontgomeryContext>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// # Residue
+///
+/// An integer `a` modulo `m` in Montgomery form, i.e. stored as `a * R mod m`
+/// in little-endian 64-bit limbs, where `R = 2^(64 * limbs)`.
+///
+/// A residue is only meaningful together with the `MontgomeryContext` it was created by.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Residue {
+    limbs: Vec<u64>,
+}
+
+impl Residue {
+    /// Swap this residue with the other one if `choice` is one, and keep both if it is zero.
+    ///
+    /// Both residues must stem from the same context. The limbs are exchanged using
+    /// a mask, so that neither the control flow nor the accessed memory depend on `choice`.
+    pub fn conditional_swap(&mut self, other: &mut Residue, choice: u64) {
+        let mask = hint::black_box(choice.wrapping_neg());
+
+        for (a, b) in self.limbs.iter_mut().zip(other.limbs.iter_mut()) {
+            let difference = mask & (*a ^ *b);
+            *a ^= difference;
+            *b ^= difference;
+        }
+    }
+
+    /// Replace this residue by the other one if `choice` is one, and keep it if it is zero.
+    ///
+    /// Both residues must stem from the same context. As for `Residue::conditional_swap`,
+    /// neither the control flow nor the accessed memory depend on `choice`.
+    pub fn conditional_assign(&mut self, other: &Residue, choice: u64) {
+        let mask = hint::black_box(choice.wrapping_neg());
+
+        for (a, b) in self.limbs.iter_mut().zip(other.limbs.iter()) {
+            *a ^= mask & (*a ^ *b);
+        }
+    }
+}
+
+/// # Montgomery Context
+///
+/// The precomputed constants for Montgomery multiplication modulo an odd modulus `m`.
+///
+/// Values are converted into Montgomery form once (see `to_montgomery`) and then
+/// multiplied without any division, using the word-wise CIOS method on fixed-size
+/// limbs. Only the final result is converted back (see `from_montgomery`).
+/// Hence, long chains of multiplications such as exponentiations avoid the long
+/// division that `BigInt` performs on every reduction.
+#[derive(Clone, Debug)]
+pub struct MontgomeryContext {
+    modulus: BigInt,
+    limbs: Vec<u64>,
+    /// `-m^(-1) mod 2^64`
+    m_prime: u64,
+    /// `R^2 mod m`, which converts into Montgomery form
+    r2: Residue,
+    /// `R mod m`, i.e. one in Montgomery form
+    one: Residue,
+}
+
+impl MontgomeryContext {
+    /// Create the context for the given modulus.
+    ///
+    /// Returns `None` if the modulus is not positive and odd,
+    /// as Montgomery multiplication requires `m` to be coprime to `R`.
+    pub fn new(modulus: &BigInt) -> Option<MontgomeryContext> {
+        if !modulus.is_positive() || modulus.is_even() {
+            return None;
+        }
+
+        let limbs = to_limbs(modulus, modulus.bits().div_ceil(64));
+
+        // Newton's iteration doubles the amount of correct low bits of the inverse
+        let mut inverse: u64 = 1;
+        for _ in 0..6 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(limbs[0].wrapping_mul(inverse)));
+        }
+
+        let r = BigInt::one() << (64 * limbs.len());
+        let r2 = (&r * &r).mod_floor(modulus);
+
+        Some(MontgomeryContext {
+            modulus: modulus.clone(),
+            m_prime: inverse.wrapping_neg(),
+            r2: Residue { limbs: to_limbs(&r2, limbs.len()) },
+            one: Residue { limbs: to_limbs(&r.mod_floor(modulus), limbs.len()) },
+            limbs,
+        })
+    }
+
+    /// Returns the context for the given modulus from a small per-thread cache,
+    /// creating it if necessary, so that repeated operations on the same modulus
+    /// share its precomputation.
+    ///
+    /// Returns `None` if the modulus is not positive and odd.
+    pub fn cached(modulus: &BigInt) -> Option<Rc<MontgomeryContext>> {
+        if !modulus.is_positive() || modulus.is_even() {
+            return None;
+        }
+
+        CONTEXTS.with(|contexts| {
+            let mut contexts = contexts.borrow_mut();
+
+            if let Some(context) = contexts.iter().find(|context| context.modulus == *modulus) {
+                return Some(context.clone());
+            }
+
+            let context = Rc::new(MontgomeryContext::new(modulus)?);
+            if contexts.len() == CACHE_SIZE {
+                contexts.remove(0);
+            }
+            contexts.push(context.clone());
+
+            Some(context)
+        })
+    }
+
+    /// Returns the modulus of this context.
+    pub fn modulus(&self) -> &BigInt {
+        &self.modulus
+    }
+
+    /// Returns one in Montgomery form.
+    pub fn one(&self) -> Residue {
+        self.one.clone()
+    }
+
+    /// Convert the given integer into Montgomery form.
+    /// Values outside of `[0, m)`, including negative ones, are reduced first.
+    pub fn to_montgomery(&self, value: &BigInt) -> Residue {
+        let mut residue = if value.is_negative() || *value >= self.modulus {
+            Residue { limbs: to_limbs(&value.mod_floor(&self.modulus), self.limbs.len()) }
+        } else {
+            Residue { limbs: to_limbs(value, self.limbs.len()) }
+        };
+
+        self.mul(&mut residue, &self.r2);
+        residue
+    }
+
+    /// Convert the given residue back into an integer within `[0, m)`.
+    pub fn from_montgomery(&self, residue: &Residue) -> BigInt {
+        let mut one = vec![0u64; self.limbs.len()];
+        one[0] = 1;
+
+        let mut result = residue.clone();
+        self.mul(&mut result, &Residue { limbs: one });
+
+        from_limbs(&result.limbs)
+    }
+
+    /// Multiply `a` by `b` in place, i.e. `a = a * b * R^(-1) mod m`,
+    /// which is the Montgomery form of the product.
+    pub fn mul(&self, a: &mut Residue, b: &Residue) {
+        let n = self.limbs.len();
+        let m = &self.limbs;
+        let mut t = vec![0u64; n + 2];
+
+        for i in 0..n {
+            // t = t + a * b_i
+            let mut carry: u64 = 0;
+            for (t_j, a_j) in t.iter_mut().zip(a.limbs.iter()) {
+                let sum = u128::from(*t_j) + u128::from(*a_j) * u128::from(b.limbs[i]) + u128::from(carry);
+                *t_j = sum as u64;
+                carry = (sum >> 64) as u64;
+            }
+            let sum = u128::from(t[n]) + u128::from(carry);
+            t[n] = sum as u64;
+            t[n + 1] = (sum >> 64) as u64;
+
+            // t = (t + k * m) / 2^64, where k makes the lowest limb vanish
+            let k = t[0].wrapping_mul(self.m_prime);
+            let sum = u128::from(t[0]) + u128::from(k) * u128::from(m[0]);
+            let mut carry = (sum >> 64) as u64;
+            for j in 1..n {
+                let sum = u128::from(t[j]) + u128::from(k) * u128::from(m[j]) + u128::from(carry);
+                t[j - 1] = sum as u64;
+                carry = (sum >> 64) as u64;
+            }
+            let sum = u128::from(t[n]) + u128::from(carry);
+            t[n - 1] = sum as u64;
+            t[n] = t[n + 1] + (sum >> 64) as u64;
+        }
+
+        // t < 2m, so a single subtraction reduces it. The difference is always
+        // computed and selected using a mask, so that no branch depends on t.
+        let mut borrow: u64 = 0;
+        for ((a_j, t_j), m_j) in a.limbs.iter_mut().zip(t.iter()).zip(m.iter()) {
+            let (difference, borrow_1) = t_j.overflowing_sub(*m_j);
+            let (difference, borrow_2) = difference.overflowing_sub(borrow);
+            *a_j = difference;
+            borrow = (borrow_1 | borrow_2) as u64;
+        }
+
+        // t < m if and only if the subtraction borrows from the top limb t[n]
+        let (_, t_less_than_m) = t[n].overflowing_sub(borrow);
+        let keep = (t_less_than_m as u64).wrapping_neg();
+        for (a_j, t_j) in a.limbs.iter_mut().zip(t.iter()) {
+            *a_j = (t_j & keep) | (*a_j & !keep);
+        }
+    }
+
+    /// Square `a` in place.
+    pub fn square(&self, a: &mut Residue) {
+        let copy = a.clone();
+        self.mul(a, &copy);
+    }
+
+    /// Returns `base^exponent` in Montgomery form for a non-negative exponent,
+    /// using a fixed window of `WINDOW_BITS` bits.
+    ///
+    /// The sequence of operations depends on the exponent.
+    /// Use `constant_time::ladder_pow` for secret exponents.
+    pub fn pow(&self, base: &Residue, exponent: &BigInt) -> Residue {
+        assert!(!exponent.is_negative(), "the exponent must not be negative");
+
+        // table[d] = base^d
+        let mut table: Vec<Residue> = Vec::with_capacity(1 << WINDOW_BITS);
+        table.push(self.one());
+        for d in 1..(1 << WINDOW_BITS) {
+            let mut power = table[d - 1].clone();
+            self.mul(&mut power, base);
+            table.push(power);
+        }
+
+        let (_, bytes) = exponent.to_bytes_le();
+        let windows = exponent.bits().div_ceil(WINDOW_BITS);
+
+        let mut result = self.one();
+        for i in (0..windows).rev() {
+            for _ in 0..WINDOW_BITS {
+                self.square(&mut result);
+            }
+
+            let mut digit: usize = 0;
+            for k in 0..WINDOW_BITS {
+                let bit = i * WINDOW_BITS + k;
+                if bit / 8 < bytes.len() {
+                    digit |= (((bytes[bit / 8] >> (bit % 8)) & 1) as usize) << k;
+                }
+            }
+
+            if digit != 0 {
+                self.mul(&mut result, &table[digit]);
+            }
+        }
+
+        result
+    }
+
+    /// Returns `a * b mod m` for integers in normal form.
+    pub fn mod_mul(&self, a: &BigInt, b: &BigInt) -> BigInt {
+        // (a * R) * b * R^(-1) = a * b, hence b needs no conversion
+        let mut product = self.to_montgomery(a);
+        let b = if b.is_negative() || *b >= self.modulus {
+            b.mod_floor(&self.modulus)
+        } else {
+            b.clone()
+        };
+        self.mul(&mut product, &Residue { limbs: to_limbs(&b, self.limbs.len()) });
+
+        from_limbs(&product.limbs)
+    }
+
+    /// Returns `base^exponent mod m` for integers in normal form
+    /// as described in `MontgomeryContext::pow`.
+    pub fn mod_pow(&self, base: &BigInt, exponent: &BigInt) -> BigInt {
+        self.from_montgomery(&self.pow(&self.to_montgomery(base), exponent))
+    }
+}
+
+/// Split the given non-negative integer into the given amount of little-endian 64-bit limbs.
+pub(crate) fn to_limbs(value: &BigInt, amount: usize) -> Vec<u64> {
+    let (_, mut bytes) = value.to_bytes_le();
+    bytes.resize(amount * 8, 0);
+
+    bytes.chunks(8)
+        .map(|chunk| chunk.iter().rev().fold(0u64, |limb, byte| (limb << 8) | u64::from(*byte)))
+        .collect()
+}
+
+fn from_limbs(limbs: &[u64]) -> BigInt {
+    let digits: Vec<u32> = limbs.iter()
+        .flat_map(|limb| vec![*limb as u32, (*limb >> 32) as u32])
+        .collect();
+
+    BigInt::from_biguint(Sign::Plus, BigUint::new(digits))
+}
+
+#[cfg(test)]
+mod montgomery_test {
+
+    use ::arithmetic::montgomery::MontgomeryContext;
+    use ::arithmetic::standard_groups::MODP_2048;
+    use ::num::bigint::{BigInt, RandBigInt};
+    use ::num::{Integer, One, Zero};
+    use ::rand;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_mul() {
+        let mut rng = rand::thread_rng();
+
+        for modulus in [BigInt::from(23), BigInt::from(2199023255867i64), MODP_2048.group().p.value].iter() {
+            let context = MontgomeryContext::new(modulus).unwrap();
+
+            for _ in 0..50 {
+                let a = rng.gen_bigint_range(&BigInt::zero(), modulus);
+                let b = rng.gen_bigint_range(&BigInt::zero(), modulus);
+
+                assert_eq!(a, context.from_montgomery(&context.to_montgomery(&a)));
+                assert_eq!((&a * &b).mod_floor(modulus), context.mod_mul(&a, &b));
+
+                let mut square = context.to_montgomery(&a);
+                context.square(&mut square);
+                assert_eq!((&a * &a).mod_floor(modulus), context.from_montgomery(&square));
+            }
+
+            // values outside of [0, m) are reduced first
+            let minus_one = modulus - BigInt::one();
+            assert_eq!(minus_one, context.mod_mul(&BigInt::from(-1), &(modulus + BigInt::one())));
+        }
+    }
+
+    #[test]
+    fn test_pow() {
+        let p = BigInt::from(2199023255867i64);
+        let q = BigInt::from(1099511627933i64);
+        let context = MontgomeryContext::new(&p).unwrap();
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..50 {
+            let base = rng.gen_bigint_range(&BigInt::zero(), &p);
+            let exponent = rng.gen_bigint_range(&BigInt::zero(), &q);
+
+            assert_eq!(base.modpow(&exponent, &p), context.mod_pow(&base, &exponent));
+        }
+
+        assert_eq!(BigInt::one(), context.mod_pow(&BigInt::from(4), &BigInt::zero()));
+        assert_eq!(BigInt::one(), context.mod_pow(&BigInt::from(4), &q));
+
+        // anything modulo one is zero
+        let one = MontgomeryContext::new(&BigInt::one()).unwrap();
+        assert_eq!(BigInt::zero(), one.mod_pow(&BigInt::from(4), &BigInt::from(3)));
+    }
+
+    #[test]
+    fn test_conditional_swap() {
+        let context = MontgomeryContext::new(&BigInt::from(2199023255867i64)).unwrap();
+        let three = context.to_montgomery(&BigInt::from(3));
+        let five = context.to_montgomery(&BigInt::from(5));
+
+        let (mut a, mut b) = (three.clone(), five.clone());
+        a.conditional_swap(&mut b, 0);
+        assert_eq!((&three, &five), (&a, &b));
+
+        a.conditional_swap(&mut b, 1);
+        assert_eq!((&five, &three), (&a, &b));
+    }
+
+    #[test]
+    fn test_conditional_assign() {
+        let context = MontgomeryContext::new(&BigInt::from(2199023255867i64)).unwrap();
+        let three = context.to_montgomery(&BigInt::from(3));
+        let five = context.to_montgomery(&BigInt::from(5));
+
+        let mut a = three.clone();
+        a.conditional_assign(&five, 0);
+        assert_eq!(three, a);
+
+        a.conditional_assign(&five, 1);
+        assert_eq!(five, a);
+    }
+
+    #[test]
+    fn test_invalid_modulus() {
+        assert!(MontgomeryContext::new(&BigInt::from(22)).is_none());
+        assert!(MontgomeryContext::new(&BigInt::zero()).is_none());
+        assert!(MontgomeryContext::new(&BigInt::from(-23)).is_none());
+    }
+
+    #[test]
+    fn test_cached() {
+        let p = BigInt::from(2199023255867i64);
+
+        let context = MontgomeryContext::cached(&p).unwrap();
+        assert!(Rc::ptr_eq(&context, &MontgomeryContext::cached(&p).unwrap()));
+        assert!(MontgomeryContext::cached(&BigInt::from(22)).is_none());
+    }
+}
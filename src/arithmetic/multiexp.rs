@@ -0,0 +1,314 @@
+use ::arithmetic::mod_int::ModInt;
+use ::arithmetic::montgomery::{MontgomeryContext, Residue};
+use ::error::Error;
+use num::bigint::BigInt;
+use num::Integer;
+use num::One;
+use num::Signed;
+
+/// The amount of exponent bits processed per step of the Straus algorithm.
+const STRAUS_WINDOW_BITS: usize = 4;
+
+/// The amount of bases from which on `multiexp` applies the Pippenger algorithm.
+const PIPPENGER_THRESHOLD: usize = 160;
+
+/// # Multi-Exponentiation
+///
+/// Calculates `b_1^e_1 * b_2^e_2 * ... * b_n^e_n` for the given bases and exponents,
+/// choosing the faster of `straus` and `pippenger` depending on the amount of bases.
+///
+/// All bases must have the same positive modulus, which is the modulus of the result.
+/// The product of no factors is `ModInt::one()`.
+///
+/// Returns `Error::LengthMismatch` if the amount of bases and exponents differ,
+/// `Error::GroupMismatch` if the bases have different moduli and
+/// `Error::ExponentOutOfRange` if an exponent is negative or the modulus is zero.
+pub fn multiexp(bases: &[ModInt], exponents: &[ModInt]) -> Result<ModInt, Error> {
+    if bases.len() < PIPPENGER_THRESHOLD {
+        straus(bases, exponents)
+    } else {
+        pippenger(bases, exponents)
+    }
+}
+
+/// # Straus Multi-Exponentiation
+///
+/// Calculates the product of powers as described in `multiexp` by interleaving
+/// the exponentiations, so that all of them share the same squarings. For two
+/// bases, this is also known as Shamir's trick.
+///
+/// Each base requires a table of `2^STRAUS_WINDOW_BITS` powers, hence this
+/// algorithm is suited for few bases, e.g. the verification of a single proof.
+pub fn straus(bases: &[ModInt], exponents: &[ModInt]) -> Result<ModInt, Error> {
+    let modulus = match check(bases, exponents)? {
+        Some(modulus) => modulus,
+        None => return Ok(ModInt::one())
+    };
+
+    let context = match MontgomeryContext::cached(&modulus) {
+        Some(context) => context,
+        None => return Ok(separately(bases, exponents, modulus))
+    };
+
+    let width = STRAUS_WINDOW_BITS;
+    let count = max_bits(exponents).div_ceil(width);
+
+    // tables[i][d] = b_i^d
+    let tables: Vec<Vec<Residue>> = bases.iter()
+        .map(|base| {
+            let base = context.to_montgomery(&base.value);
+            let mut table: Vec<Residue> = Vec::with_capacity(1 << width);
+            table.push(context.one());
+            for d in 1..(1 << width) {
+                let mut power = table[d - 1].clone();
+                context.mul(&mut power, &base);
+                table.push(power);
+            }
+
+            table
+        })
+        .collect();
+
+    let digits: Vec<Vec<usize>> = exponents.iter()
+        .map(|exponent| digits(&exponent.value, width, count))
+        .collect();
+
+    let mut result = context.one();
+    for window in (0..count).rev() {
+        for _ in 0..width {
+            context.square(&mut result);
+        }
+
+        for (table, digits) in tables.iter().zip(digits.iter()) {
+            if digits[window] != 0 {
+                context.mul(&mut result, &table[digits[window]]);
+            }
+        }
+    }
+
+    Ok(ModInt { value: context.from_montgomery(&result), modulus })
+}
+
+/// # Pippenger Multi-Exponentiation
+///
+/// Calculates the product of powers as described in `multiexp` using the bucket method:
+/// For each window of the exponents, the bases are sorted into buckets according
+/// to their digits, and the buckets are combined using a running product.
+///
+/// The amount of multiplications grows with `n / log(n)` for `n` bases instead of
+/// the `n` of `straus`. Therefore, this algorithm is suited for many bases,
+/// e.g. the verification of a large batch of ballots.
+pub fn pippenger(bases: &[ModInt], exponents: &[ModInt]) -> Result<ModInt, Error> {
+    let modulus = match check(bases, exponents)? {
+        Some(modulus) => modulus,
+        None => return Ok(ModInt::one())
+    };
+
+    let context = match MontgomeryContext::cached(&modulus) {
+        Some(context) => context,
+        None => return Ok(separately(bases, exponents, modulus))
+    };
+
+    // a window of about log2(n) - 3 bits minimizes the amount of multiplications
+    let log_n = (usize::BITS - bases.len().leading_zeros()) as usize;
+    let width = log_n.saturating_sub(3).clamp(1, 16);
+    let count = max_bits(exponents).div_ceil(width);
+
+    let bases: Vec<Residue> = bases.iter().map(|base| context.to_montgomery(&base.value)).collect();
+    let digits: Vec<Vec<usize>> = exponents.iter()
+        .map(|exponent| digits(&exponent.value, width, count))
+        .collect();
+
+    let mut result = context.one();
+    for window in (0..count).rev() {
+        for _ in 0..width {
+            context.square(&mut result);
+        }
+
+        // buckets[d] = the product of all bases having the digit d in this window
+        let mut buckets: Vec<Option<Residue>> = vec![None; 1 << width];
+        for (base, digits) in bases.iter().zip(digits.iter()) {
+            let digit = digits[window];
+            if digit != 0 {
+                match buckets[digit] {
+                    Some(ref mut bucket) => context.mul(bucket, base),
+                    None => buckets[digit] = Some(base.clone())
+                }
+            }
+        }
+
+        // Π buckets[d]^d = Π_d (Π_{d' >= d} buckets[d'])
+        let mut running: Option<Residue> = None;
+        let mut sum: Option<Residue> = None;
+        for bucket in buckets.iter().skip(1).rev() {
+            if let Some(ref bucket) = *bucket {
+                match running {
+                    Some(ref mut running) => context.mul(running, bucket),
+                    None => running = Some(bucket.clone())
+                }
+            }
+
+            if let Some(ref running) = running {
+                match sum {
+                    Some(ref mut sum) => context.mul(sum, running),
+                    None => sum = Some(running.clone())
+                }
+            }
+        }
+
+        if let Some(sum) = sum {
+            context.mul(&mut result, &sum);
+        }
+    }
+
+    Ok(ModInt { value: context.from_montgomery(&result), modulus })
+}
+
+/// Calculate the product of powers using separate exponentiations,
+/// as even moduli do not admit Montgomery multiplication.
+fn separately(bases: &[ModInt], exponents: &[ModInt], modulus: BigInt) -> ModInt {
+    let value = bases.iter().zip(exponents.iter())
+        .fold(BigInt::one().mod_floor(&modulus), |result, (base, exponent)| {
+            (result * base.value.mod_floor(&modulus).modpow(&exponent.value, &modulus)).mod_floor(&modulus)
+        });
+
+    ModInt { value, modulus }
+}
+
+/// Check the inputs of a multi-exponentiation and return their common modulus,
+/// or `None` if there are no bases at all.
+fn check(bases: &[ModInt], exponents: &[ModInt]) -> Result<Option<BigInt>, Error> {
+    if bases.len() != exponents.len() {
+        return Err(Error::LengthMismatch { expected: bases.len(), actual: exponents.len() });
+    }
+
+    let modulus = match bases.first() {
+        Some(base) => base.modulus.clone(),
+        None => return Ok(None)
+    };
+
+    if !modulus.is_positive() {
+        return Err(Error::ExponentOutOfRange);
+    }
+
+    if bases.iter().any(|base| base.modulus != modulus) {
+        return Err(Error::GroupMismatch);
+    }
+
+    if exponents.iter().any(|exponent| exponent.value.is_negative()) {
+        return Err(Error::ExponentOutOfRange);
+    }
+
+    Ok(Some(modulus))
+}
+
+fn max_bits(exponents: &[ModInt]) -> usize {
+    exponents.iter().map(|exponent| exponent.value.bits()).max().unwrap_or(0)
+}
+
+/// Split the given non-negative exponent into `count` digits of `width` bits,
+/// starting with the least significant one.
+fn digits(exponent: &BigInt, width: usize, count: usize) -> Vec<usize> {
+    let (_, bytes) = exponent.to_bytes_le();
+
+    (0..count)
+        .map(|i| {
+            let mut digit: usize = 0;
+            for k in 0..width {
+                let bit = i * width + k;
+                if bit / 8 < bytes.len() {
+                    digit |= (((bytes[bit / 8] >> (bit % 8)) & 1) as usize) << k;
+                }
+            }
+
+            digit
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod multiexp_test {
+
+    use ::arithmetic::mod_int::From;
+    use ::arithmetic::mod_int::ModInt;
+    use ::arithmetic::mod_int::RandModInt;
+    use ::arithmetic::multiexp::{multiexp, pippenger, straus};
+    use ::error::Error;
+    use ::num::bigint::BigInt;
+    use ::num::traits::Pow;
+    use ::num::One;
+
+    fn naive(bases: &[ModInt], exponents: &[ModInt]) -> ModInt {
+        bases.iter().zip(exponents.iter())
+            .fold(ModInt::from_value_modulus(BigInt::one(), bases[0].modulus.clone()), |result, (base, exponent)| {
+                result * base.clone().pow(exponent.clone())
+            })
+    }
+
+    fn random_inputs(amount: usize) -> (Vec<ModInt>, Vec<ModInt>) {
+        let p = ModInt::from_value(BigInt::from(2199023255867i64));
+        let q = ModInt::from_value(BigInt::from(1099511627933i64));
+
+        let bases = (0..amount).map(|_| ModInt::gen_modint(p.clone())).collect();
+        let exponents = (0..amount).map(|_| ModInt::gen_modint(q.clone())).collect();
+
+        (bases, exponents)
+    }
+
+    #[test]
+    fn test_straus() {
+        for amount in [1, 2, 3, 10].iter() {
+            let (bases, exponents) = random_inputs(*amount);
+            assert_eq!(naive(&bases, &exponents), straus(&bases, &exponents).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_pippenger() {
+        for amount in [1, 2, 3, 10, 300].iter() {
+            let (bases, exponents) = random_inputs(*amount);
+            assert_eq!(naive(&bases, &exponents), pippenger(&bases, &exponents).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_multiexp() {
+        let (bases, mut exponents) = random_inputs(4);
+
+        // zero exponents and exponents of different bit lengths
+        exponents[1] = ModInt::from_value(BigInt::from(0));
+        exponents[2] = ModInt::from_value(BigInt::from(3));
+
+        assert_eq!(naive(&bases, &exponents), multiexp(&bases, &exponents).unwrap());
+        assert_eq!(ModInt::one(), multiexp(&[], &[]).unwrap());
+
+        // even moduli do not admit Montgomery multiplication
+        let even_bases: Vec<ModInt> = bases.iter()
+            .map(|base| ModInt::from_value_modulus(base.value.clone(), BigInt::from(1u64 << 40)))
+            .collect();
+        assert_eq!(naive(&even_bases, &exponents), straus(&even_bases, &exponents).unwrap());
+        assert_eq!(naive(&even_bases, &exponents), pippenger(&even_bases, &exponents).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let (bases, exponents) = random_inputs(2);
+
+        match multiexp(&bases, &exponents[..1]) {
+            Err(Error::LengthMismatch { expected: 2, actual: 1 }) => {}
+            other => panic!("expected a length mismatch, got {:?}", other),
+        }
+
+        let mixed = vec![bases[0].clone(), ModInt::from_value_modulus(BigInt::from(3), BigInt::from(23))];
+        match multiexp(&mixed, &exponents) {
+            Err(Error::GroupMismatch) => {}
+            other => panic!("expected a group mismatch, got {:?}", other),
+        }
+
+        let negative = vec![exponents[0].clone(), ModInt::from_value(BigInt::from(-1))];
+        match multiexp(&bases, &negative) {
+            Err(Error::ExponentOutOfRange) => {}
+            other => panic!("expected an invalid exponent, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use ::arithmetic::group::Group;
+use ::arithmetic::group_element::same_group;
+use ::arithmetic::mod_int::ModInt;
+use ::arithmetic::mod_inverse;
+use ::error::Error;
+use num::bigint::{BigInt, RandBigInt};
+use num::Integer;
+use num::Zero;
+use rand;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Mul, Neg, Sub};
+use std::sync::Arc;
+
+/// # Scalar
+///
+/// An exponent of the elements of a group, i.e. an element of `Z_q`.
+///
+/// Every scalar is bound to the group it was created for. Combining scalars
+/// of different groups returns `Error::GroupMismatch` in the `checked_*`
+/// operations and panics in the corresponding operators.
+#[derive(Clone)]
+pub struct Scalar {
+    value: BigInt,
+    group: Arc<Group>,
+}
+
+impl Scalar {
+    /// Create the scalar `value mod q`.
+    ///
+    /// - group: The group the scalar belongs to
+    /// - value: Any integer, which is reduced modulo `q`
+    pub fn new(group: &Arc<Group>, value: BigInt) -> Scalar {
+        Scalar {
+            value: value.mod_floor(&group.q.value),
+            group: group.clone(),
+        }
+    }
+
+    /// Returns the scalar zero of the given group.
+    pub fn zero(group: &Arc<Group>) -> Scalar {
+        Scalar::new(group, BigInt::zero())
+    }
+
+    /// Sample a scalar uniformly from `Z_q`.
+    pub fn random(group: &Arc<Group>) -> Scalar {
+        let value = rand::thread_rng().gen_bigint_range(&BigInt::zero(), &group.q.value);
+
+        Scalar::new(group, value)
+    }
+
+    /// Convert the given ModInt into a scalar of the given group.
+    ///
+    /// Returns `Error::GroupMismatch` if the ModInt is neither reduced
+    /// modulo `q` nor a plain integer without modulus.
+    pub fn from_mod_int(group: &Arc<Group>, value: &ModInt) -> Result<Scalar, Error> {
+        if !value.modulus.is_zero() && value.modulus != group.q.value {
+            return Err(Error::GroupMismatch);
+        }
+
+        Ok(Scalar::new(group, value.value.clone()))
+    }
+
+    /// Returns this scalar as ModInt having the modulus `q`.
+    pub fn to_mod_int(&self) -> ModInt {
+        ModInt {
+            value: self.value.clone(),
+            modulus: self.group.q.value.clone(),
+        }
+    }
+
+    /// Encode this scalar as the fixed-width big-endian encoding
+    /// of its value modulo `q` (see `ModInt::to_bytes`).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_mod_int().to_bytes()
+    }
+
+    /// Returns the value of this scalar within `[0, q)`.
+    pub fn value(&self) -> &BigInt {
+        &self.value
+    }
+
+    /// Returns the group this scalar belongs to.
+    pub fn group(&self) -> &Arc<Group> {
+        &self.group
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+
+    /// Compare this scalar with the given one.
+    ///
+    /// Returns `Error::GroupMismatch` if the scalars belong to different groups.
+    pub fn checked_eq(&self, other: &Scalar) -> Result<bool, Error> {
+        self.check_group(other)?;
+
+        Ok(self.value == other.value)
+    }
+
+    /// Add the given scalar modulo `q`.
+    ///
+    /// Returns `Error::GroupMismatch` if the scalars belong to different groups.
+    pub fn checked_add(self, rhs: Scalar) -> Result<Scalar, Error> {
+        self.check_group(&rhs)?;
+
+        Ok(Scalar::new(&self.group, self.value + rhs.value))
+    }
+
+    /// Subtract the given scalar modulo `q`.
+    ///
+    /// Returns `Error::GroupMismatch` if the scalars belong to different groups.
+    pub fn checked_sub(self, rhs: Scalar) -> Result<Scalar, Error> {
+        self.check_group(&rhs)?;
+
+        Ok(Scalar::new(&self.group, self.value - rhs.value))
+    }
+
+    /// Multiply with the given scalar modulo `q`.
+    ///
+    /// Returns `Error::GroupMismatch` if the scalars belong to different groups.
+    pub fn checked_mul(self, rhs: Scalar) -> Result<Scalar, Error> {
+        self.check_group(&rhs)?;
+
+        Ok(Scalar::new(&self.group, self.value * rhs.value))
+    }
+
+    /// Returns the inverse of this scalar modulo `q`.
+    ///
+    /// Returns `Error::DivisionByZero` if this scalar is zero and
+    /// `Error::NotInvertible` if `q` is not prime.
+    pub fn invert(self) -> Result<Scalar, Error> {
+        if self.value.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+
+        match mod_inverse::mod_inverse(self.value, self.group.q.value.clone()) {
+            Some(inverse) => Ok(Scalar::new(&self.group, inverse)),
+            None => Err(Error::NotInvertible)
+        }
+    }
+
+    fn check_group(&self, other: &Scalar) -> Result<(), Error> {
+        if same_group(&self.group, &other.group) {
+            Ok(())
+        } else {
+            Err(Error::GroupMismatch)
+        }
+    }
+}
+
+/// Scalars of different groups are never equal.
+/// Use `Scalar::checked_eq` to detect such comparisons.
+impl PartialEq for Scalar {
+    fn eq(&self, other: &Scalar) -> bool {
+        self.value == other.value && same_group(&self.group, &other.group)
+    }
+}
+
+impl Eq for Scalar {}
+
+/// Omits the value, as scalars hold secrets such as private keys and randomness.
+impl Debug for Scalar {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Scalar(<redacted>)")
+    }
+}
+
+impl Hash for Scalar {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+        self.group.hash(state);
+    }
+}
+
+impl Add for Scalar {
+    type Output = Scalar;
+
+    /// # Panics
+    ///
+    /// Panics if the scalars belong to different groups.
+    /// Use `Scalar::checked_add` to handle this case.
+    fn add(self, rhs: Scalar) -> Scalar {
+        match self.checked_add(rhs) {
+            Ok(result) => result,
+            Err(e) => panic!("{}", e)
+        }
+    }
+}
+
+impl Sub for Scalar {
+    type Output = Scalar;
+
+    /// # Panics
+    ///
+    /// Panics if the scalars belong to different groups.
+    /// Use `Scalar::checked_sub` to handle this case.
+    fn sub(self, rhs: Scalar) -> Scalar {
+        match self.checked_sub(rhs) {
+            Ok(result) => result,
+            Err(e) => panic!("{}", e)
+        }
+    }
+}
+
+impl Mul for Scalar {
+    type Output = Scalar;
+
+    /// # Panics
+    ///
+    /// Panics if the scalars belong to different groups.
+    /// Use `Scalar::checked_mul` to handle this case.
+    fn mul(self, rhs: Scalar) -> Scalar {
+        match self.checked_mul(rhs) {
+            Ok(result) => result,
+            Err(e) => panic!("{}", e)
+        }
+    }
+}
+
+impl Neg for Scalar {
+    type Output = Scalar;
+
+    fn neg(self) -> Scalar {
+        Scalar::new(&self.group, -self.value)
+    }
+}
+
+#[cfg(test)]
+mod scalar_test {
+
+    use ::arithmetic::group::Group;
+    use ::arithmetic::mod_int::From;
+    use ::arithmetic::mod_int::ModInt;
+    use ::arithmetic::scalar::Scalar;
+    use ::error::Error;
+    use ::num::bigint::BigInt;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_arithmetic() {
+        let group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+
+        let a = Scalar::new(&group, BigInt::from(7));
+        let b = Scalar::new(&group, BigInt::from(-6));
+        assert_eq!(BigInt::from(5), *b.value());
+
+        assert_eq!(Scalar::new(&group, BigInt::from(1)), a.clone() + b.clone());
+        assert_eq!(Scalar::new(&group, BigInt::from(2)), a.clone() - b.clone());
+        assert_eq!(Scalar::new(&group, BigInt::from(2)), a.clone() * b.clone());
+        assert_eq!(Scalar::new(&group, BigInt::from(4)), -a.clone());
+
+        let inverse = a.clone().invert().unwrap();
+        assert_eq!(Scalar::new(&group, BigInt::from(1)), a * inverse);
+
+        match Scalar::zero(&group).invert() {
+            Err(Error::DivisionByZero) => {}
+            result => panic!("expected division by zero, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_debug_redacts_value() {
+        let group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+
+        assert_eq!("Scalar(<redacted>)", format!("{:?}", Scalar::new(&group, BigInt::from(7))));
+    }
+
+    #[test]
+    fn test_group_mismatch() {
+        let group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        let other_group = Arc::new(Group::new(BigInt::from(47), BigInt::from(23), BigInt::from(4)));
+
+        let a = Scalar::new(&group, BigInt::from(3));
+        let b = Scalar::new(&other_group, BigInt::from(3));
+
+        assert_ne!(a, b);
+        assert!(a.checked_eq(&b).is_err());
+        assert!(a.clone().checked_add(b.clone()).is_err());
+        assert!(a.clone().checked_mul(b).is_err());
+
+        // equal parameters form the same group
+        let same_group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        assert_eq!(a, Scalar::new(&same_group, BigInt::from(14)));
+
+        assert_eq!(a, Scalar::from_mod_int(&group, &ModInt::from_value(BigInt::from(3))).unwrap());
+        assert!(Scalar::from_mod_int(&group, &ModInt::from_value_modulus(BigInt::from(3), BigInt::from(23))).is_err());
+        assert_eq!(ModInt::from_value_modulus(BigInt::from(3), BigInt::from(11)), a.to_mod_int());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mismatched_operator_panics() {
+        let group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        let other_group = Arc::new(Group::new(BigInt::from(47), BigInt::from(23), BigInt::from(4)));
+
+        let _ = Scalar::new(&group, BigInt::from(3)) + Scalar::new(&other_group, BigInt::from(3));
+    }
+}
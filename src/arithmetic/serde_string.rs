@@ -0,0 +1,238 @@
+//! Alternative serde representations of `ModInt`, which encode the value and
+//! the modulus as strings instead of the digit vectors of `BigInt`.
+//!
+//! The representations are selected per field using `#[serde(with = ...)]`:
+//!
+//! ```
+//! extern crate crypto_rs;
+//! #[macro_use]
+//! extern crate serde_derive;
+//!
+//! use crypto_rs::arithmetic::mod_int::ModInt;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Ballot {
+//!     #[serde(with = "crypto_rs::arithmetic::serde_string::hex")]
+//!     vote: ModInt,
+//!     #[serde(with = "crypto_rs::arithmetic::serde_string::base64::vec")]
+//!     responses: Vec<ModInt>,
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! With the `serde-hex` feature enabled, all serializable types of this crate, i.e. the
+//! group parameters, keys, cipher texts, proofs, threshold shares and UCIV sets, use the
+//! hexadecimal representation for all of their values.
+
+use ::arithmetic::mod_int::ModInt;
+use num::bigint::{BigInt, Sign};
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+type Encode = fn(&BigInt) -> String;
+type Decode = fn(&str) -> Option<BigInt>;
+
+fn serialize_with<S: Serializer>(value: &ModInt, serializer: S, encode: Encode) -> Result<S::Ok, S::Error> {
+    (encode(&value.value), encode(&value.modulus)).serialize(serializer)
+}
+
+fn deserialize_with<'de, D: Deserializer<'de>>(deserializer: D, decode: Decode) -> Result<ModInt, D::Error> {
+    let (value, modulus): (String, String) = Deserialize::deserialize(deserializer)?;
+
+    decode_mod_int::<D::Error>(&value, &modulus, decode)
+}
+
+fn serialize_vec_with<S: Serializer>(values: &[ModInt], serializer: S, encode: Encode) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(values.iter().map(|value| (encode(&value.value), encode(&value.modulus))))
+}
+
+fn deserialize_vec_with<'de, D: Deserializer<'de>>(deserializer: D, decode: Decode) -> Result<Vec<ModInt>, D::Error> {
+    let values: Vec<(String, String)> = Deserialize::deserialize(deserializer)?;
+
+    values.iter()
+        .map(|(value, modulus)| decode_mod_int::<D::Error>(value, modulus, decode))
+        .collect()
+}
+
+fn decode_mod_int<E: DeError>(value: &str, modulus: &str, decode: Decode) -> Result<ModInt, E> {
+    match (decode(value), decode(modulus)) {
+        (Some(value), Some(modulus)) => Ok(ModInt { value, modulus }),
+        _ => Err(E::custom(format!("invalid ModInt ({:?}, {:?})", value, modulus)))
+    }
+}
+
+/// Encode the sign of the given integer as an optional leading minus,
+/// followed by its magnitude encoded using the given function.
+fn encode_signed(value: &BigInt, encode_magnitude: fn(&[u8]) -> String) -> String {
+    let (sign, magnitude) = value.to_bytes_be();
+
+    match sign {
+        Sign::Minus => format!("-{}", encode_magnitude(&magnitude)),
+        _ => encode_magnitude(&magnitude),
+    }
+}
+
+/// Decode an integer encoded by `encode_signed`.
+fn decode_signed(string: &str, decode_magnitude: fn(&str) -> Option<Vec<u8>>) -> Option<BigInt> {
+    let (sign, magnitude) = match string.strip_prefix('-') {
+        Some(magnitude) => (Sign::Minus, magnitude),
+        None => (Sign::Plus, string),
+    };
+
+    decode_magnitude(magnitude).map(|bytes| BigInt::from_bytes_be(sign, &bytes))
+}
+
+/// # Hexadecimal Representation
+///
+/// Represents a `ModInt` as the tuple `(value, modulus)` of lowercase hexadecimal
+/// strings without prefix, e.g. `["1f", "2b"]`. Negative values are prefixed with a minus.
+pub mod hex {
+    use ::arithmetic::mod_int::ModInt;
+    use num::bigint::BigInt;
+    use num::Num;
+    use serde::{Deserializer, Serializer};
+
+    pub(super) fn encode(value: &BigInt) -> String {
+        value.to_str_radix(16)
+    }
+
+    pub(super) fn decode(string: &str) -> Option<BigInt> {
+        let value = BigInt::from_str_radix(string, 16).ok()?;
+
+        // reject non-canonical encodings, e.g. leading zeros, uppercase digits or "-0"
+        if encode(&value) != string {
+            return None;
+        }
+
+        Some(value)
+    }
+
+    pub fn serialize<S: Serializer>(value: &ModInt, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_with(value, serializer, encode)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ModInt, D::Error> {
+        super::deserialize_with(deserializer, decode)
+    }
+
+    /// The hexadecimal representation of a `Vec<ModInt>`.
+    pub mod vec {
+        use ::arithmetic::mod_int::ModInt;
+        use serde::{Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(values: &[ModInt], serializer: S) -> Result<S::Ok, S::Error> {
+            super::super::serialize_vec_with(values, serializer, super::encode)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<ModInt>, D::Error> {
+            super::super::deserialize_vec_with(deserializer, super::decode)
+        }
+    }
+}
+
+/// # Base64 Representation
+///
+/// Represents a `ModInt` as the tuple `(value, modulus)` of the standard, padded
+/// base64 encodings of their big-endian bytes, e.g. `["Hw==", "Kw=="]`.
+/// Negative values are prefixed with a minus.
+pub mod base64 {
+    use ::arithmetic::mod_int::ModInt;
+    use num::bigint::BigInt;
+    use serde::{Deserializer, Serializer};
+
+    pub(super) fn encode(value: &BigInt) -> String {
+        super::encode_signed(value, |bytes| ::base64::encode(bytes))
+    }
+
+    pub(super) fn decode(string: &str) -> Option<BigInt> {
+        let value = super::decode_signed(string, |magnitude| ::base64::decode(magnitude).ok())?;
+
+        // reject non-canonical encodings, e.g. missing padding, leading zero bytes or "-AA=="
+        if encode(&value) != string {
+            return None;
+        }
+
+        Some(value)
+    }
+
+    pub fn serialize<S: Serializer>(value: &ModInt, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_with(value, serializer, encode)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ModInt, D::Error> {
+        super::deserialize_with(deserializer, decode)
+    }
+
+    /// The base64 representation of a `Vec<ModInt>`.
+    pub mod vec {
+        use ::arithmetic::mod_int::ModInt;
+        use serde::{Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(values: &[ModInt], serializer: S) -> Result<S::Ok, S::Error> {
+            super::super::serialize_vec_with(values, serializer, super::encode)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<ModInt>, D::Error> {
+            super::super::deserialize_vec_with(deserializer, super::decode)
+        }
+    }
+}
+
+#[cfg(test)]
+mod serde_string_test {
+
+    use ::arithmetic::mod_int::From;
+    use ::arithmetic::mod_int::ModInt;
+    use ::num::bigint::BigInt;
+    use ::serde_json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Hex {
+        #[serde(with = "::arithmetic::serde_string::hex")]
+        value: ModInt,
+        #[serde(with = "::arithmetic::serde_string::hex::vec")]
+        values: Vec<ModInt>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Base64 {
+        #[serde(with = "::arithmetic::serde_string::base64")]
+        value: ModInt,
+        #[serde(with = "::arithmetic::serde_string::base64::vec")]
+        values: Vec<ModInt>,
+    }
+
+    #[test]
+    fn test_hex() {
+        let hex = Hex {
+            value: ModInt::from_value_modulus(BigInt::from(31), BigInt::from(43)),
+            values: vec![ModInt::from_value(BigInt::from(-255)), ModInt::from_value(BigInt::from(0))],
+        };
+
+        let json = serde_json::to_string(&hex).unwrap();
+        assert_eq!(r#"{"value":["1f","2b"],"values":[["-ff","0"],["0","0"]]}"#, json);
+        assert_eq!(hex, serde_json::from_str(&json).unwrap());
+
+        for invalid in [r#"["0x1f","2b"]"#, r#"["","2b"]"#, r#"["1g","2b"]"#, r#"["+1f","2b"]"#, r#"["-0","2b"]"#, r#"["01f","2b"]"#, r#"["1F","2b"]"#].iter() {
+            let json = format!(r#"{{"value":{},"values":[]}}"#, invalid);
+            assert!(serde_json::from_str::<Hex>(&json).is_err(), "accepted {}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_base64() {
+        let base64 = Base64 {
+            value: ModInt::from_value_modulus(BigInt::from(31), BigInt::from(43)),
+            values: vec![ModInt::from_value(BigInt::from(-65535)), ModInt::from_value(BigInt::from(0))],
+        };
+
+        let json = serde_json::to_string(&base64).unwrap();
+        assert_eq!(r#"{"value":["Hw==","Kw=="],"values":[["-//8=","AA=="],["AA==","AA=="]]}"#, json);
+        assert_eq!(base64, serde_json::from_str(&json).unwrap());
+
+        for invalid in [r#"["Hw","Kw=="]"#, r#"["","Kw=="]"#, r#"["H*==","Kw=="]"#, r#"["-AA==","Kw=="]"#, r#"["AB8=","Kw=="]"#].iter() {
+            let json = format!(r#"{{"value":{},"values":[]}}"#, invalid);
+            assert!(serde_json::from_str::<Base64>(&json).is_err(), "accepted {}", invalid);
+        }
+    }
+}
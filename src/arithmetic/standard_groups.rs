@@ -0,0 +1,307 @@
+use ::arithmetic::group::Group;
+use num::bigint::BigInt;
+
+/// # Standardized Group
+///
+/// The hex encoded parameters `(p, q, g)` of a standardized group,
+/// where `p = 2q + 1` is a safe prime and `g` generates the subgroup
+/// of prime order `q`.
+///
+/// Use `StandardGroup::group()` to obtain the parameters for creating
+/// a `PublicKey` or generating a `KeyPair`.
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash)]
+pub struct StandardGroup {
+    /// The name of the group as used in its specification.
+    pub name: &'static str,
+    pub p: &'static str,
+    pub q: &'static str,
+    pub g: &'static str,
+}
+
+impl StandardGroup {
+    /// Returns the parameters of this standardized group.
+    pub fn group(&self) -> Group {
+        Group::new(
+            StandardGroup::parse(self.p),
+            StandardGroup::parse(self.q),
+            StandardGroup::parse(self.g),
+        )
+    }
+
+    fn parse(hex_string: &str) -> BigInt {
+        // all constants are validated by the tests below
+        BigInt::parse_bytes(hex_string.as_bytes(), 16).expect("standardized group parameters must be valid hex")
+    }
+}
+
+/// All standardized groups shipped with this library.
+pub const STANDARD_GROUPS: [StandardGroup; 6] = [
+    MODP_2048,
+    MODP_3072,
+    MODP_4096,
+    FFDHE_2048,
+    FFDHE_3072,
+    FFDHE_4096,
+];
+
+/// The 2048-bit MODP Group as specified in RFC 3526, section 3.
+pub const MODP_2048: StandardGroup = StandardGroup {
+    name: "modp2048",
+    p: concat!(
+        "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74",
+        "020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437",
+        "4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED",
+        "EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05",
+        "98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB",
+        "9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B",
+        "E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718",
+        "3995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF",
+    ),
+    q: concat!(
+        "7FFFFFFFFFFFFFFFE487ED5110B4611A62633145C06E0E68948127044533E63A",
+        "0105DF531D89CD9128A5043CC71A026EF7CA8CD9E69D218D98158536F92F8A1B",
+        "A7F09AB6B6A8E122F242DABB312F3F637A262174D31BF6B585FFAE5B7A035BF6",
+        "F71C35FDAD44CFD2D74F9208BE258FF324943328F6722D9EE1003E5C50B1DF82",
+        "CC6D241B0E2AE9CD348B1FD47E9267AFC1B2AE91EE51D6CB0E3179AB1042A95D",
+        "CF6A9483B84B4B36B3861AA7255E4C0278BA3604650C10BE19482F23171B671D",
+        "F1CF3B960C074301CD93C1D17603D147DAE2AEF837A62964EF15E5FB4AAC0B8C",
+        "1CCAA4BE754AB5728AE9130C4C7D02880AB9472D455655347FFFFFFFFFFFFFFF",
+    ),
+    g: "2",
+};
+
+/// The 3072-bit MODP Group as specified in RFC 3526, section 4.
+pub const MODP_3072: StandardGroup = StandardGroup {
+    name: "modp3072",
+    p: concat!(
+        "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74",
+        "020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437",
+        "4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED",
+        "EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05",
+        "98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB",
+        "9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B",
+        "E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718",
+        "3995497CEA956AE515D2261898FA051015728E5A8AAAC42DAD33170D04507A33",
+        "A85521ABDF1CBA64ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7",
+        "ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6BF12FFA06D98A0864",
+        "D87602733EC86A64521F2B18177B200CBBE117577A615D6C770988C0BAD946E2",
+        "08E24FA074E5AB3143DB5BFCE0FD108E4B82D120A93AD2CAFFFFFFFFFFFFFFFF",
+    ),
+    q: concat!(
+        "7FFFFFFFFFFFFFFFE487ED5110B4611A62633145C06E0E68948127044533E63A",
+        "0105DF531D89CD9128A5043CC71A026EF7CA8CD9E69D218D98158536F92F8A1B",
+        "A7F09AB6B6A8E122F242DABB312F3F637A262174D31BF6B585FFAE5B7A035BF6",
+        "F71C35FDAD44CFD2D74F9208BE258FF324943328F6722D9EE1003E5C50B1DF82",
+        "CC6D241B0E2AE9CD348B1FD47E9267AFC1B2AE91EE51D6CB0E3179AB1042A95D",
+        "CF6A9483B84B4B36B3861AA7255E4C0278BA3604650C10BE19482F23171B671D",
+        "F1CF3B960C074301CD93C1D17603D147DAE2AEF837A62964EF15E5FB4AAC0B8C",
+        "1CCAA4BE754AB5728AE9130C4C7D02880AB9472D45556216D6998B8682283D19",
+        "D42A90D5EF8E5D32767DC2822C6DF785457538ABAE83063ED9CB87C2D370F263",
+        "D5FAD7466D8499EB8F464A702512B0CEE771E9130D697735F897FD036CC50432",
+        "6C3B01399F643532290F958C0BBD90065DF08BABBD30AEB63B84C4605D6CA371",
+        "047127D03A72D598A1EDADFE707E884725C16890549D69657FFFFFFFFFFFFFFF",
+    ),
+    g: "2",
+};
+
+/// The 4096-bit MODP Group as specified in RFC 3526, section 5.
+pub const MODP_4096: StandardGroup = StandardGroup {
+    name: "modp4096",
+    p: concat!(
+        "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74",
+        "020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437",
+        "4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED",
+        "EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05",
+        "98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB",
+        "9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B",
+        "E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718",
+        "3995497CEA956AE515D2261898FA051015728E5A8AAAC42DAD33170D04507A33",
+        "A85521ABDF1CBA64ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7",
+        "ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6BF12FFA06D98A0864",
+        "D87602733EC86A64521F2B18177B200CBBE117577A615D6C770988C0BAD946E2",
+        "08E24FA074E5AB3143DB5BFCE0FD108E4B82D120A92108011A723C12A787E6D7",
+        "88719A10BDBA5B2699C327186AF4E23C1A946834B6150BDA2583E9CA2AD44CE8",
+        "DBBBC2DB04DE8EF92E8EFC141FBECAA6287C59474E6BC05D99B2964FA090C3A2",
+        "233BA186515BE7ED1F612970CEE2D7AFB81BDD762170481CD0069127D5B05AA9",
+        "93B4EA988D8FDDC186FFB7DC90A6C08F4DF435C934063199FFFFFFFFFFFFFFFF",
+    ),
+    q: concat!(
+        "7FFFFFFFFFFFFFFFE487ED5110B4611A62633145C06E0E68948127044533E63A",
+        "0105DF531D89CD9128A5043CC71A026EF7CA8CD9E69D218D98158536F92F8A1B",
+        "A7F09AB6B6A8E122F242DABB312F3F637A262174D31BF6B585FFAE5B7A035BF6",
+        "F71C35FDAD44CFD2D74F9208BE258FF324943328F6722D9EE1003E5C50B1DF82",
+        "CC6D241B0E2AE9CD348B1FD47E9267AFC1B2AE91EE51D6CB0E3179AB1042A95D",
+        "CF6A9483B84B4B36B3861AA7255E4C0278BA3604650C10BE19482F23171B671D",
+        "F1CF3B960C074301CD93C1D17603D147DAE2AEF837A62964EF15E5FB4AAC0B8C",
+        "1CCAA4BE754AB5728AE9130C4C7D02880AB9472D45556216D6998B8682283D19",
+        "D42A90D5EF8E5D32767DC2822C6DF785457538ABAE83063ED9CB87C2D370F263",
+        "D5FAD7466D8499EB8F464A702512B0CEE771E9130D697735F897FD036CC50432",
+        "6C3B01399F643532290F958C0BBD90065DF08BABBD30AEB63B84C4605D6CA371",
+        "047127D03A72D598A1EDADFE707E884725C16890549084008D391E0953C3F36B",
+        "C438CD085EDD2D934CE1938C357A711E0D4A341A5B0A85ED12C1F4E5156A2674",
+        "6DDDE16D826F477C97477E0A0FDF6553143E2CA3A735E02ECCD94B27D04861D1",
+        "119DD0C328ADF3F68FB094B867716BD7DC0DEEBB10B8240E68034893EAD82D54",
+        "C9DA754C46C7EEE0C37FDBEE48536047A6FA1AE49A0318CCFFFFFFFFFFFFFFFF",
+    ),
+    g: "2",
+};
+
+/// The ffdhe2048 as specified in RFC 7919, appendix A.1.
+pub const FFDHE_2048: StandardGroup = StandardGroup {
+    name: "ffdhe2048",
+    p: concat!(
+        "FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695",
+        "A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617A",
+        "D3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935",
+        "984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797A",
+        "BC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4",
+        "AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F61",
+        "9172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005",
+        "C58EF1837D1683B2C6F34A26C1B2EFFA886B423861285C97FFFFFFFFFFFFFFFF",
+    ),
+    q: concat!(
+        "7FFFFFFFFFFFFFFFD6FC2A2C515DA54D57EE2B10139E9E78EC5CE2C1E7169B4A",
+        "D4F09B208A3219FDE649CEE7124D9F7CBE97F1B1B1863AEC7B40D901576230BD",
+        "69EF8F6AEAFEB2B09219FA8FAF83376842B1B2AA9EF68D79DAAB89AF3FABE49A",
+        "CC278638707345BBF15344ED79F7F4390EF8AC509B56F39A98566527A41D3CBD",
+        "5E0558C159927DB0E88454A5D96471FDDCB56D5BB06BFA340EA7A151EF1CA6FA",
+        "572B76F3B1B95D8C8583D3E4770536B84F017E70E6FBF176601A0266941A17B0",
+        "C8B97F4E74C2C1FFC7278919777940C1E1FF1D8DA637D6B99DDAFE5E17611002",
+        "E2C778C1BE8B41D96379A51360D977FD4435A11C30942E4BFFFFFFFFFFFFFFFF",
+    ),
+    g: "2",
+};
+
+/// The ffdhe3072 as specified in RFC 7919, appendix A.2.
+pub const FFDHE_3072: StandardGroup = StandardGroup {
+    name: "ffdhe3072",
+    p: concat!(
+        "FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695",
+        "A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617A",
+        "D3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935",
+        "984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797A",
+        "BC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4",
+        "AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F61",
+        "9172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005",
+        "C58EF1837D1683B2C6F34A26C1B2EFFA886B4238611FCFDCDE355B3B6519035B",
+        "BC34F4DEF99C023861B46FC9D6E6C9077AD91D2691F7F7EE598CB0FAC186D91C",
+        "AEFE130985139270B4130C93BC437944F4FD4452E2D74DD364F2E21E71F54BFF",
+        "5CAE82AB9C9DF69EE86D2BC522363A0DABC521979B0DEADA1DBF9A42D5C4484E",
+        "0ABCD06BFA53DDEF3C1B20EE3FD59D7C25E41D2B66C62E37FFFFFFFFFFFFFFFF",
+    ),
+    q: concat!(
+        "7FFFFFFFFFFFFFFFD6FC2A2C515DA54D57EE2B10139E9E78EC5CE2C1E7169B4A",
+        "D4F09B208A3219FDE649CEE7124D9F7CBE97F1B1B1863AEC7B40D901576230BD",
+        "69EF8F6AEAFEB2B09219FA8FAF83376842B1B2AA9EF68D79DAAB89AF3FABE49A",
+        "CC278638707345BBF15344ED79F7F4390EF8AC509B56F39A98566527A41D3CBD",
+        "5E0558C159927DB0E88454A5D96471FDDCB56D5BB06BFA340EA7A151EF1CA6FA",
+        "572B76F3B1B95D8C8583D3E4770536B84F017E70E6FBF176601A0266941A17B0",
+        "C8B97F4E74C2C1FFC7278919777940C1E1FF1D8DA637D6B99DDAFE5E17611002",
+        "E2C778C1BE8B41D96379A51360D977FD4435A11C308FE7EE6F1AAD9DB28C81AD",
+        "DE1A7A6F7CCE011C30DA37E4EB736483BD6C8E9348FBFBF72CC6587D60C36C8E",
+        "577F0984C289C9385A098649DE21BCA27A7EA229716BA6E9B279710F38FAA5FF",
+        "AE574155CE4EFB4F743695E2911B1D06D5E290CBCD86F56D0EDFCD216AE22427",
+        "055E6835FD29EEF79E0D90771FEACEBE12F20E95B363171BFFFFFFFFFFFFFFFF",
+    ),
+    g: "2",
+};
+
+/// The ffdhe4096 as specified in RFC 7919, appendix A.3.
+pub const FFDHE_4096: StandardGroup = StandardGroup {
+    name: "ffdhe4096",
+    p: concat!(
+        "FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695",
+        "A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617A",
+        "D3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935",
+        "984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797A",
+        "BC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4",
+        "AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F61",
+        "9172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005",
+        "C58EF1837D1683B2C6F34A26C1B2EFFA886B4238611FCFDCDE355B3B6519035B",
+        "BC34F4DEF99C023861B46FC9D6E6C9077AD91D2691F7F7EE598CB0FAC186D91C",
+        "AEFE130985139270B4130C93BC437944F4FD4452E2D74DD364F2E21E71F54BFF",
+        "5CAE82AB9C9DF69EE86D2BC522363A0DABC521979B0DEADA1DBF9A42D5C4484E",
+        "0ABCD06BFA53DDEF3C1B20EE3FD59D7C25E41D2B669E1EF16E6F52C3164DF4FB",
+        "7930E9E4E58857B6AC7D5F42D69F6D187763CF1D5503400487F55BA57E31CC7A",
+        "7135C886EFB4318AED6A1E012D9E6832A907600A918130C46DC778F971AD0038",
+        "092999A333CB8B7A1A1DB93D7140003C2A4ECEA9F98D0ACC0A8291CDCEC97DCF",
+        "8EC9B55A7F88A46B4DB5A851F44182E1C68A007E5E655F6AFFFFFFFFFFFFFFFF",
+    ),
+    q: concat!(
+        "7FFFFFFFFFFFFFFFD6FC2A2C515DA54D57EE2B10139E9E78EC5CE2C1E7169B4A",
+        "D4F09B208A3219FDE649CEE7124D9F7CBE97F1B1B1863AEC7B40D901576230BD",
+        "69EF8F6AEAFEB2B09219FA8FAF83376842B1B2AA9EF68D79DAAB89AF3FABE49A",
+        "CC278638707345BBF15344ED79F7F4390EF8AC509B56F39A98566527A41D3CBD",
+        "5E0558C159927DB0E88454A5D96471FDDCB56D5BB06BFA340EA7A151EF1CA6FA",
+        "572B76F3B1B95D8C8583D3E4770536B84F017E70E6FBF176601A0266941A17B0",
+        "C8B97F4E74C2C1FFC7278919777940C1E1FF1D8DA637D6B99DDAFE5E17611002",
+        "E2C778C1BE8B41D96379A51360D977FD4435A11C308FE7EE6F1AAD9DB28C81AD",
+        "DE1A7A6F7CCE011C30DA37E4EB736483BD6C8E9348FBFBF72CC6587D60C36C8E",
+        "577F0984C289C9385A098649DE21BCA27A7EA229716BA6E9B279710F38FAA5FF",
+        "AE574155CE4EFB4F743695E2911B1D06D5E290CBCD86F56D0EDFCD216AE22427",
+        "055E6835FD29EEF79E0D90771FEACEBE12F20E95B34F0F78B737A9618B26FA7D",
+        "BC9874F272C42BDB563EAFA16B4FB68C3BB1E78EAA81A00243FAADD2BF18E63D",
+        "389AE44377DA18C576B50F0096CF34195483B00548C0986236E3BC7CB8D6801C",
+        "0494CCD199E5C5BD0D0EDC9EB8A0001E15276754FCC68566054148E6E764BEE7",
+        "C764DAAD3FC45235A6DAD428FA20C170E345003F2F32AFB57FFFFFFFFFFFFFFF",
+    ),
+    g: "2",
+};
+
+#[cfg(test)]
+mod standard_groups_test {
+
+    use ::arithmetic::group::miller_rabin;
+    use ::arithmetic::standard_groups::{STANDARD_GROUPS, MODP_2048, FFDHE_4096};
+    use ::num::bigint::BigInt;
+    use ::num::traits::Pow;
+    use ::num::One;
+    use ::num::Zero;
+    use ::rand;
+
+    #[test]
+    fn test_bit_lengths() {
+        let bit_lengths = [2048, 3072, 4096, 2048, 3072, 4096];
+
+        for (standard_group, bit_length) in STANDARD_GROUPS.iter().zip(bit_lengths.iter()) {
+            let group = standard_group.group();
+
+            assert_eq!(*bit_length, group.p.value.bits(), "{}", standard_group.name);
+        }
+    }
+
+    #[test]
+    fn test_safe_primes() {
+        for standard_group in STANDARD_GROUPS.iter() {
+            let group = standard_group.group();
+
+            assert_eq!(group.q.value.clone() * BigInt::from(2) + BigInt::one(), group.p.value, "{}", standard_group.name);
+            // a few rounds suffice, as the parameters have been chosen deterministically
+            assert!(miller_rabin(&group.p.value, 2, &mut rand::thread_rng()), "{}", standard_group.name);
+            assert!(miller_rabin(&group.q.value, 2, &mut rand::thread_rng()), "{}", standard_group.name);
+        }
+    }
+
+    #[test]
+    fn test_generator_order() {
+        for standard_group in STANDARD_GROUPS.iter() {
+            let group = standard_group.group();
+
+            // as q is prime, g has order q iff g != 1 and g^q = 1
+            assert!(!group.g.is_one(), "{}", standard_group.name);
+            assert!(group.g.clone().pow(group.q.clone()).is_one(), "{}", standard_group.name);
+        }
+    }
+
+    #[test]
+    fn test_group() {
+        let group = MODP_2048.group();
+
+        assert_eq!(BigInt::from(2), group.g.value);
+        assert_eq!(group.p.value, group.g.modulus);
+        assert_eq!(BigInt::zero(), group.p.modulus);
+        assert_eq!(BigInt::zero(), group.q.modulus);
+
+        assert_ne!(MODP_2048.group(), FFDHE_4096.group());
+    }
+}
@@ -1,30 +1,88 @@
 use std::vec::Vec;
-use num::pow::Pow;
-use num::Zero;
+use std::sync::Arc;
 
-use std::ops::{Mul, Div, Sub, Add, Neg};
+use std::ops::{Mul, Sub, Add, Neg};
 
-use ::arithmetic::mod_int::From;
-use ::arithmetic::mod_int::RandModInt;
+use ::arithmetic::encoding::{self, ByteReader};
+use ::arithmetic::group::Group;
+use ::arithmetic::group_element::{same_group, GroupElement};
+use ::arithmetic::in_group::{self, DeserializeInGroup};
 use ::arithmetic::mod_int::ModInt;
+use ::arithmetic::scalar::Scalar;
 use ::el_gamal::encryption::PublicKey;
-use ::el_gamal::ciphertext::CipherText;
-use ::el_gamal::serializer::Serializer;
+use ::el_gamal::precomputed::FixedBasePow;
+use ::el_gamal::ciphertext::{CipherText, Randomness};
+use ::el_gamal::transcript::Transcript;
+use ::error::Error;
+use ::parallel;
+use serde::{Deserialize, Deserializer};
 
 /// Secret UCIV Information `(x1, x2, ..., xn)`.
 /// This information is specific to a particular voter.
 /// Each `xn` is further tight to the n-th voting option.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize)]
+#[serde(into = "PreImageSetRepr")]
 pub struct PreImageSet {
-    pub pre_images: Vec<ModInt>
+    pub pre_images: Vec<Scalar>
+}
+
+/// The serialized form of a `PreImageSet`.
+#[derive(Clone, Serialize, Deserialize)]
+struct PreImageSetRepr {
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex::vec"))]
+    pre_images: Vec<ModInt>
+}
+
+impl ::std::convert::From<PreImageSet> for PreImageSetRepr {
+    fn from(pre_image_set: PreImageSet) -> Self {
+        PreImageSetRepr {
+            pre_images: pre_image_set.pre_images.iter().map(Scalar::to_mod_int).collect()
+        }
+    }
+}
+
+impl DeserializeInGroup for PreImageSet {
+    fn deserialize_in<'de, D: Deserializer<'de>>(group: &Arc<Group>, deserializer: D) -> Result<Self, D::Error> {
+        let repr = PreImageSetRepr::deserialize(deserializer)?;
+
+        Ok(PreImageSet {
+            pre_images: in_group::scalars(group, &repr.pre_images)?
+        })
+    }
 }
 
 /// Public UCIV Information `(y1, y2, ..., yn)`.
 /// This information is specific to a particular voter.
 /// Each `yn` is further tight to the n-th voting option.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize)]
+#[serde(into = "ImageSetRepr")]
 pub struct ImageSet {
-    pub images: Vec<ModInt>
+    pub images: Vec<GroupElement>
+}
+
+/// The serialized form of an `ImageSet`.
+#[derive(Clone, Serialize, Deserialize)]
+struct ImageSetRepr {
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex::vec"))]
+    images: Vec<ModInt>
+}
+
+impl ::std::convert::From<ImageSet> for ImageSetRepr {
+    fn from(image_set: ImageSet) -> Self {
+        ImageSetRepr {
+            images: image_set.images.iter().map(GroupElement::to_mod_int).collect()
+        }
+    }
+}
+
+impl DeserializeInGroup for ImageSet {
+    fn deserialize_in<'de, D: Deserializer<'de>>(group: &Arc<Group>, deserializer: D) -> Result<Self, D::Error> {
+        let repr = ImageSetRepr::deserialize(deserializer)?;
+
+        Ok(ImageSet {
+            images: in_group::elements(group, &repr.images)?
+        })
+    }
 }
 
 impl ImageSet {
@@ -41,131 +99,222 @@ impl ImageSet {
     /// - pre_image_set: A number of pre-images equal to the number of voting options available
     ///                  which are specific to a particular voter.
     ///
-    pub fn new(generator: ModInt, pre_image_set: PreImageSet) -> Self {
-        let mut vec = vec![];
-
+    /// # Panics
+    ///
+    /// Panics if a pre-image belongs to another group than the generator.
+    pub fn new(generator: GroupElement, pre_image_set: PreImageSet) -> Self {
         // apply g^x as one-way function
-        for pre_image in pre_image_set.pre_images.iter() {
-            // If modulus is not equal, then we will end up with a different one after the exponentiation
-            // TODO: check this
-            //assert_eq!(pre_image.modulus.clone(), generator.modulus.clone(), "Modulus of pre-image and generator must be equal");
-
-            vec.push(generator.clone().pow(pre_image.clone()));
+        ImageSet {
+            images: pre_image_set.pre_images.iter().map(|pre_image| generator.clone().pow_secret(pre_image)).collect()
         }
+    }
 
+    /// Creates an ImageSet as described in `ImageSet::new`, using the generator `g`
+    /// of the given public key, or a `PrecomputedPublicKey` of it.
+    ///
+    /// - key: The public key used during encryption of the vote
+    /// - pre_image_set: The voter specific pre-images, one for each voting option
+    ///
+    /// # Panics
+    ///
+    /// Panics if a pre-image belongs to another group than the public key.
+    pub fn from_public_key<K: FixedBasePow>(key: &K, pre_image_set: PreImageSet) -> Self {
         ImageSet {
-            images: vec
+            images: pre_image_set.pre_images.iter().map(|pre_image| key.g_pow(pre_image)).collect()
         }
     }
 }
 
+/// Returns true if the cipher text, all given elements and all given scalars belong to the given group.
+fn in_same_group(group: &Arc<Group>, cipher_text: &CipherText, elements: &[GroupElement], scalars: &[Scalar]) -> bool {
+    same_group(group, cipher_text.big_g.group())
+        && same_group(group, cipher_text.big_h.group())
+        && elements.iter().all(|element| same_group(group, element.group()))
+        && scalars.iter().all(|scalar| same_group(group, scalar.group()))
+}
+
 /// Cast-as-Intended proof
-#[derive(Eq, PartialEq, Debug, Clone, Hash, Serialize, Deserialize)]
+#[derive(Eq, PartialEq, Debug, Clone, Hash, Serialize)]
+#[serde(into = "CaiProofRepr")]
 pub struct CaiProof {
+    s1_options: Vec<Scalar>,
+    s2_options: Vec<Scalar>,
+    h1_options: Vec<Scalar>,
+    h2_options: Vec<Scalar>,
+
+    h: Scalar
+}
+
+/// The serialized form of a `CaiProof`.
+#[derive(Clone, Serialize, Deserialize)]
+struct CaiProofRepr {
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex::vec"))]
     s1_options: Vec<ModInt>,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex::vec"))]
     s2_options: Vec<ModInt>,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex::vec"))]
     h1_options: Vec<ModInt>,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex::vec"))]
     h2_options: Vec<ModInt>,
 
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
     h: ModInt
 }
 
+impl ::std::convert::From<CaiProof> for CaiProofRepr {
+    fn from(proof: CaiProof) -> Self {
+        CaiProofRepr {
+            s1_options: proof.s1_options.iter().map(Scalar::to_mod_int).collect(),
+            s2_options: proof.s2_options.iter().map(Scalar::to_mod_int).collect(),
+            h1_options: proof.h1_options.iter().map(Scalar::to_mod_int).collect(),
+            h2_options: proof.h2_options.iter().map(Scalar::to_mod_int).collect(),
+            h: proof.h.to_mod_int()
+        }
+    }
+}
+
+impl DeserializeInGroup for CaiProof {
+    fn deserialize_in<'de, D: Deserializer<'de>>(group: &Arc<Group>, deserializer: D) -> Result<Self, D::Error> {
+        let repr = CaiProofRepr::deserialize(deserializer)?;
+
+        Ok(CaiProof {
+            s1_options: in_group::scalars(group, &repr.s1_options)?,
+            s2_options: in_group::scalars(group, &repr.s2_options)?,
+            h1_options: in_group::scalars(group, &repr.h1_options)?,
+            h2_options: in_group::scalars(group, &repr.h2_options)?,
+            h: in_group::scalar(group, &repr.h)?
+        })
+    }
+}
+
 impl CaiProof {
 
     /// Create a new Cast-as-Intended Proof.
     ///
-    /// - public_key: The public key of the election system
+    /// - key: The public key of the election system, or a `PrecomputedPublicKey` of it
     /// - cipher_text: The cipher text for which to create the proof
+    /// - randomness: The randomness used to encrypt the chosen vote
     /// - pre_image_set: The voter and voting option dependent secret set of pre-images
     /// - image_set: The voter and voting option dependent public set of images
     /// - chosen_vote_idx: The index of the chosen vote within the set of available voting options
     /// - voting_options: The set of available voting options
+    /// - context: The context the proof is bound to, e.g. the IDs of the election, the voter and the question
     ///
     /// # Panics
     ///
     /// Panics if the amount of pre-images, images and available voting options are not equal.
     /// In addition, panics if the chosen vote index is out-of-bound of the available set of
-    /// voting options.
-    pub fn new(public_key: PublicKey, cipher_text: CipherText, pre_image_set: PreImageSet, image_set: ImageSet, chosen_vote_idx: usize, voting_options: Vec<ModInt>) -> Self {
-        assert_eq!(pre_image_set.pre_images.len(), image_set.images.len(), "The amount of pre-images and images must be equal");
-        assert_eq!(pre_image_set.pre_images.len(), voting_options.len(), "The amount of pre-images must be equal to the amount of voting options");
-        assert!(chosen_vote_idx < pre_image_set.pre_images.len(), "The chosen vote index must refer to a voting option for which a pre-image exists");
+    /// voting options, or if any of the values belongs to another group than the public key.
+    /// Use `CaiProof::try_new` to handle these cases.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<K: FixedBasePow>(key: K, cipher_text: CipherText, randomness: Randomness, pre_image_set: PreImageSet, image_set: ImageSet, chosen_vote_idx: usize, voting_options: Vec<Scalar>, context: &[u8]) -> Self {
+        match CaiProof::try_new(key, cipher_text, randomness, pre_image_set, image_set, chosen_vote_idx, voting_options, context) {
+            Ok(proof) => proof,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Create a new Cast-as-Intended Proof as described in `CaiProof::new`.
+    ///
+    /// Returns `Error::LengthMismatch` if the amount of pre-images, images and available
+    /// voting options are not equal, `Error::IndexOutOfBounds` if the chosen vote index
+    /// is out-of-bound of the available set of voting options, and `Error::GroupMismatch`
+    /// if any of the values belongs to another group than the public key.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_new<K: FixedBasePow>(key: K, cipher_text: CipherText, randomness: Randomness, pre_image_set: PreImageSet, image_set: ImageSet, chosen_vote_idx: usize, voting_options: Vec<Scalar>, context: &[u8]) -> Result<Self, Error> {
+        let public_key = key.public_key();
+        let group = public_key.group();
+        let amount = pre_image_set.pre_images.len();
+
+        if image_set.images.len() != amount {
+            return Err(Error::LengthMismatch { expected: amount, actual: image_set.images.len() });
+        }
+
+        if voting_options.len() != amount {
+            return Err(Error::LengthMismatch { expected: amount, actual: voting_options.len() });
+        }
+
+        if chosen_vote_idx >= amount {
+            return Err(Error::IndexOutOfBounds { index: chosen_vote_idx, length: amount });
+        }
+
+        let secrets = [randomness.random.clone()];
+        if !in_same_group(group, &cipher_text, &image_set.images, &voting_options)
+            || !in_same_group(group, &cipher_text, &[], &pre_image_set.pre_images)
+            || !in_same_group(group, &cipher_text, &[], &secrets) {
+            return Err(Error::GroupMismatch);
+        }
 
         // initialize vector with the amount of pre_images which are
         // equal to the number of voting options
-        let mut s1_options: Vec<ModInt> = vec![ModInt::zero(); pre_image_set.pre_images.len()];
-        let mut s2_options: Vec<ModInt> = vec![ModInt::zero(); pre_image_set.pre_images.len()];
-        let mut h1_options: Vec<ModInt> = vec![ModInt::zero(); pre_image_set.pre_images.len()];
-        let mut h2_options: Vec<ModInt> = vec![ModInt::zero(); pre_image_set.pre_images.len()];
+        let mut s1_options: Vec<Scalar> = vec![Scalar::zero(group); amount];
+        let mut s2_options: Vec<Scalar> = vec![Scalar::zero(group); amount];
+        let mut h1_options: Vec<Scalar> = vec![Scalar::zero(group); amount];
+        let mut h2_options: Vec<Scalar> = vec![Scalar::zero(group); amount];
 
-        let mut a_options: Vec<ModInt> = vec![ModInt::zero(); pre_image_set.pre_images.len()];
-        let mut b_options: Vec<ModInt> = vec![ModInt::zero(); pre_image_set.pre_images.len()];
+        let mut a_options: Vec<Scalar> = vec![Scalar::zero(group); amount];
+        let mut b_options: Vec<Scalar> = vec![Scalar::zero(group); amount];
 
         let j = chosen_vote_idx;
 
+        let mut transcript = CaiProof::transcript(public_key, &cipher_text, &image_set, &voting_options, context);
+
         let c1 = cipher_text.big_g;
         let c2 = cipher_text.big_h;
 
-        let mut string_to_hash = String::new();
-        string_to_hash += &c1.to_string();
-        string_to_hash += &c2.to_string();
-
-        // TODO: add c3, c4
-
-        for i in 0..pre_image_set.pre_images.len() {
+        for i in 0..amount {
             if i != j {
                 // case 1: all not-chosen options
 
                 // generate random values
-                let s1_i = ModInt::gen_modint(public_key.q.clone());
-                let h1_i = ModInt::gen_modint(public_key.q.clone());
+                let s1_i = Scalar::random(group);
+                let h1_i = Scalar::random(group);
                 s1_options[i] = s1_i.clone();
                 h1_options[i] = h1_i.clone();
 
                 // the specific values for each voting options
-                let c1_i = public_key.g.clone().pow(s1_i.clone()).mul(c1.clone().pow(h1_i.clone().neg()));
-                let c2_i = public_key.h.clone().pow(s1_i.clone()).mul((c2.clone().div(public_key.g.clone().pow(voting_options[i].clone()))).pow(h1_i.clone().neg()));
+                let c1_i = key.g_pow(&s1_i).mul(c1.clone().checked_pow(&h1_i.clone().neg())?);
+                let c2_i = key.h_pow(&s1_i).mul(c2.clone().checked_div(key.g_pow(&voting_options[i]))?.checked_pow(&h1_i.neg())?);
 
-                let a_i = ModInt::gen_modint(public_key.q.clone());
+                let a_i = Scalar::random(group);
                 a_options[i] = a_i.clone();
-                let r_i = public_key.g.clone().pow(a_i);
+                let r_i = key.g_pow(&a_i);
 
-                string_to_hash += &c1_i.to_string();
-                string_to_hash += &c2_i.to_string();
-                string_to_hash += &r_i.to_string();
+                transcript.append_element(b"c1", &c1_i);
+                transcript.append_element(b"c2", &c2_i);
+                transcript.append_element(b"r", &r_i);
             } else {
                 // case 2: the chosen option
 
-                let s2_j = ModInt::gen_modint(public_key.q.clone());
-                let h2_j = ModInt::gen_modint(public_key.q.clone());
+                let s2_j = Scalar::random(group);
+                let h2_j = Scalar::random(group);
                 s2_options[j] = s2_j.clone();
                 h2_options[j] = h2_j.clone();
 
-                let b_j = ModInt::gen_modint(public_key.q.clone());
+                let b_j = Scalar::random(group);
                 b_options[j] = b_j.clone();
 
-                let c1_j = public_key.g.clone().pow(b_j.clone());
-                let c2_j = public_key.h.clone().pow(b_j.clone());
+                let c1_j = key.g_pow(&b_j);
+                let c2_j = key.h_pow(&b_j);
 
-                let r_j = public_key.g.clone().pow(s2_j).mul(image_set.images[j].clone().pow(h2_j.clone().neg()));
+                let r_j = key.g_pow(&s2_j).mul(image_set.images[j].clone().checked_pow(&h2_j.neg())?);
 
-                string_to_hash += &c1_j.to_string();
-                string_to_hash += &c2_j.to_string();
-                string_to_hash += &r_j.to_string();
+                transcript.append_element(b"c1", &c1_j);
+                transcript.append_element(b"c2", &c2_j);
+                transcript.append_element(b"r", &r_j);
             }
         }
 
-        let h_hash = Serializer::string_to_sha512(string_to_hash);
-        let h = ModInt::from_hex_string(h_hash, public_key.q.value.clone());
+        let h = transcript.challenge(b"h", group);
 
-        for i in 0..pre_image_set.pre_images.len() {
+        for i in 0..amount {
             if i != j {
                 // case 1: all not-chosen options
 
                 let h2_i = h.clone().sub(h1_options[i].clone());
                 h2_options[i] = h2_i.clone();
 
-                let s2_i = a_options[i].clone().add(pre_image_set.pre_images[i].clone().mul(h2_i.clone()));
+                let s2_i = a_options[i].clone().add(pre_image_set.pre_images[i].clone().mul(h2_i));
                 s2_options[i] = s2_i;
 
             } else {
@@ -174,18 +323,18 @@ impl CaiProof {
                 let h1_j = h.clone().sub(h2_options[j].clone());
                 h1_options[j] = h1_j.clone();
 
-                let s1_j = b_options[j].clone().add(cipher_text.random.clone().mul(h1_j.clone()));
-                s1_options[j] = s1_j.clone();
+                let s1_j = b_options[j].clone().add(randomness.random.clone().mul(h1_j));
+                s1_options[j] = s1_j;
             }
         }
 
-        CaiProof {
+        Ok(CaiProof {
             s1_options,
             s2_options,
             h1_options,
             h2_options,
             h
-        }
+        })
     }
 
     /// Verify this proof for validity.
@@ -194,32 +343,151 @@ impl CaiProof {
     /// - cipher_text: The cipher text for which to create the proof
     /// - image_set: The voter and voting option dependent public set of images
     /// - voting_options: The set of available voting options
-    pub fn verify(&self, public_key: PublicKey, cipher_text: CipherText, image_set: ImageSet, voting_options: Vec<ModInt>) -> bool {
+    /// - context: The context the proof was created for
+    ///
+    /// Note, that the public key is trusted. Keys from untrusted sources
+    /// must be checked using `PublicKey::validate` beforehand.
+    ///
+    /// Returns false for malformed proofs, e.g. if the amount of options
+    /// of the proof does not match the amount of voting options.
+    pub fn verify(&self, public_key: PublicKey, cipher_text: CipherText, image_set: ImageSet, voting_options: Vec<Scalar>, context: &[u8]) -> bool {
+        self.try_verify(public_key, cipher_text, image_set, voting_options, context).unwrap_or(false)
+    }
+
+    /// Verify the proofs of many ballots as described in `CaiProof::verify`.
+    ///
+    /// If the `parallel` feature is enabled, the ballots are distributed over all
+    /// available cores. Otherwise, they are verified one after another.
+    ///
+    /// - public_key: The public key of the election system
+    /// - ballots: The cipher texts along with their proofs, the image sets of the
+    ///   respective voters and the contexts the proofs were created for
+    /// - voting_options: The set of available voting options
+    ///
+    /// Returns whether each proof is valid, in the order of the ballots.
+    pub fn par_verify(public_key: &PublicKey, ballots: &[(CipherText, CaiProof, ImageSet, &[u8])], voting_options: &[Scalar]) -> Vec<bool> {
+        parallel::map(ballots, parallel::threads(), |&(ref cipher_text, ref proof, ref image_set, context)| {
+            proof.verify(public_key.clone(), cipher_text.clone(), image_set.clone(), voting_options.to_vec(), context)
+        })
+    }
+
+    /// Verify this proof as described in `CaiProof::verify`, returning
+    /// an error if the proof or its inputs are malformed.
+    ///
+    /// Returns `Error::LengthMismatch` if the amount of options of the proof,
+    /// the amount of images and the amount of voting options are not equal, and
+    /// `Error::GroupMismatch` if the proof, the cipher text, the images or the
+    /// voting options belong to another group than the public key.
+    pub fn try_verify(&self, public_key: PublicKey, cipher_text: CipherText, image_set: ImageSet, voting_options: Vec<Scalar>, context: &[u8]) -> Result<bool, Error> {
+        let amount = voting_options.len();
+
+        for options in [self.s1_options.len(), self.s2_options.len(), self.h1_options.len(), self.h2_options.len(), image_set.images.len()].iter() {
+            if *options != amount {
+                return Err(Error::LengthMismatch { expected: amount, actual: *options });
+            }
+        }
+
+        let group = public_key.group();
+        let responses: Vec<Scalar> = [&self.s1_options, &self.s2_options, &self.h1_options, &self.h2_options].iter()
+            .flat_map(|options| options.iter().cloned())
+            .chain(Some(self.h.clone()))
+            .collect();
+        if !in_same_group(group, &cipher_text, &image_set.images, &voting_options) || !in_same_group(group, &cipher_text, &[], &responses) {
+            return Err(Error::GroupMismatch);
+        }
+
+        let mut transcript = CaiProof::transcript(&public_key, &cipher_text, &image_set, &voting_options, context);
+
+        let g = public_key.g();
+        let h = public_key.h.clone();
+
         let c1 = cipher_text.big_g;
         let c2 = cipher_text.big_h;
 
-        let mut string_to_hash = String::new();
-        string_to_hash += &c1.to_string();
-        string_to_hash += &c2.to_string();
-        // TODO: c3, c4
-
         // reconstruct h
-        for i in 0..self.s1_options.len() {
-            let c1_i = public_key.g.clone().pow(self.s1_options[i].clone()).mul(c1.clone().pow(self.h1_options[i].clone().neg()));
-            let c2_i = public_key.h.clone().pow(self.s1_options[i].clone()).mul((c2.clone().div(public_key.g.clone().pow(voting_options[i].clone()))).pow(self.h1_options[i].clone().neg()));
-            let r_i = public_key.g.clone().pow(self.s2_options[i].clone()).mul(image_set.images[i].clone().pow(self.h2_options[i].clone().neg()));
-
-            string_to_hash += &c1_i.to_string();
-            string_to_hash += &c2_i.to_string();
-            string_to_hash += &r_i.to_string();
+        for (i, voting_option) in voting_options.iter().enumerate() {
+            let s1_i = self.s1_options[i].clone();
+            let neg_h1_i = self.h1_options[i].clone().neg();
+            let c2_div_g_pow = c2.clone().checked_div(g.clone().checked_pow(voting_option)?)?;
+
+            let c1_i = GroupElement::multiexp(group, &[g.clone(), c1.clone()], &[s1_i.clone(), neg_h1_i.clone()])?;
+            let c2_i = GroupElement::multiexp(group, &[h.clone(), c2_div_g_pow], &[s1_i, neg_h1_i])?;
+            let r_i = GroupElement::multiexp(group, &[g.clone(), image_set.images[i].clone()], &[self.s2_options[i].clone(), self.h2_options[i].clone().neg()])?;
+
+            transcript.append_element(b"c1", &c1_i);
+            transcript.append_element(b"c2", &c2_i);
+            transcript.append_element(b"r", &r_i);
+        }
+
+        let h = transcript.challenge(b"h", group);
+
+        Ok(self.h == h && self.challenges_match(&h))
+    }
+
+    /// Returns true if the challenges `h1_i + h2_i` of each option add up to the
+    /// challenge `h` of the transcript.
+    ///
+    /// Otherwise, the prover could simulate the proofs of all options using arbitrary
+    /// challenges, and thus prove a cipher text which encrypts none of the voting options.
+    fn challenges_match(&self, h: &Scalar) -> bool {
+        self.h1_options.iter().zip(self.h2_options.iter())
+            .all(|(h1, h2)| h1.clone().checked_add(h2.clone()).map(|sum| sum == *h).unwrap_or(false))
+    }
+
+    /// Encode this proof as the amount of options, followed by the fixed-width big-endian
+    /// encodings of `s1`, `s2`, `h1` and `h2` of all options and of `h` (see `Scalar::to_bytes`).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        encoding::write_length(&mut bytes, self.s1_options.len());
+
+        for options in [&self.s1_options, &self.s2_options, &self.h1_options, &self.h2_options].iter() {
+            encoding::write_scalars(&mut bytes, options);
         }
+        bytes.extend_from_slice(&self.h.to_bytes());
 
-        let h_hash = Serializer::string_to_sha512(string_to_hash);
-        let h = ModInt::from_hex_string(h_hash, public_key.q.value.clone());
+        bytes
+    }
 
+    /// Decode a proof encoded by `CaiProof::to_bytes`.
+    ///
+    /// Returns `Error::InvalidEncoding` if the encoding is malformed
+    /// and `Error::ValueNotReduced` if a value is not reduced modulo `q`.
+    ///
+    /// - bytes: The encoded proof
+    /// - public_key: The public key of the election system
+    pub fn from_bytes(bytes: &[u8], public_key: &PublicKey) -> Result<Self, Error> {
+        let group = public_key.group();
 
+        let mut reader = ByteReader::new(bytes);
+        let amount = reader.read_length(4 * ModInt::byte_length(&group.q.value))?;
 
-        self.h == h
+        let s1_options = reader.read_scalars(amount, group)?;
+        let s2_options = reader.read_scalars(amount, group)?;
+        let h1_options = reader.read_scalars(amount, group)?;
+        let h2_options = reader.read_scalars(amount, group)?;
+        let h = reader.read_scalar(group)?;
+        reader.finish()?;
+
+        Ok(CaiProof {
+            s1_options,
+            s2_options,
+            h1_options,
+            h2_options,
+            h
+        })
+    }
+
+    /// Create the transcript of the statement, i.e. of the context, the public key,
+    /// the cipher text, the images and the available voting options.
+    fn transcript(public_key: &PublicKey, cipher_text: &CipherText, image_set: &ImageSet, voting_options: &[Scalar], context: &[u8]) -> Transcript {
+        let mut transcript = Transcript::new(b"cast-as-intended-proof");
+        transcript.append_message(b"context", context);
+        transcript.append_public_key(public_key);
+        transcript.append_cipher_text(b"cipher-text", cipher_text);
+        transcript.append_elements(b"images", &image_set.images);
+        transcript.append_scalars(b"voting-options", voting_options);
+
+        transcript
     }
 }
 
@@ -228,121 +496,400 @@ impl CaiProof {
 #[cfg(test)]
 mod uciv_proof_test {
 
+    use ::arithmetic::group::Group;
+    use ::arithmetic::group_element::GroupElement;
+    use ::arithmetic::in_group;
+    use ::arithmetic::scalar::Scalar;
     use ::el_gamal::encryption::PublicKey;
     use ::el_gamal::encryption::{encrypt};
+    use ::el_gamal::precomputed::PrecomputedPublicKey;
     use ::el_gamal::ciphertext::CipherText;
-    use ::arithmetic::mod_int::ModInt;
-    use arithmetic::mod_int::From;
     use ::num::bigint::BigInt;
-    use ::num::Zero;
-    use ::num::One;
     use std::vec::Vec;
     use std::clone::Clone;
+    use std::sync::Arc;
     use ::cai::uciv::{CaiProof, ImageSet, PreImageSet};
+    use ::error::Error;
+    use ::num::traits::Pow;
+    use std::ops::Neg;
+
+    const CONTEXT: &[u8] = b"election-1/voter-1/question-1";
+
+    //h := (g^x) mod p
+    //1680623351278 := 4^1234567 mod 2199023255867
+    fn public_key() -> PublicKey {
+        let group = Arc::new(Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4)));
+
+        PublicKey::from_group(&group, BigInt::from(1680623351278i64)).unwrap()
+    }
+
+    fn scalar(pub_key: &PublicKey, value: i64) -> Scalar {
+        Scalar::new(pub_key.group(), BigInt::from(value))
+    }
+
+    fn pre_image_set(pub_key: &PublicKey) -> PreImageSet {
+        PreImageSet {
+            pre_images: vec![scalar(pub_key, 1), scalar(pub_key, 0)]
+        }
+    }
 
     #[test]
     pub fn test_valid_proof() {
-        //h := (g^x) mod p
-        //2 := 2^5 mod 5
-        let pub_key: PublicKey = PublicKey {
-            p: ModInt::from_value_modulus(BigInt::from(5), BigInt::zero()),
-            q: ModInt::from_value_modulus(BigInt::from(2), BigInt::zero()),
-            h: ModInt::from_value_modulus(BigInt::from(32), BigInt::from(5)),
-            g: ModInt::from_value_modulus(BigInt::from(2), BigInt::from(5))
-        };
+        let pub_key = public_key();
 
         let mut voting_options = Vec::new();
-        voting_options.push(ModInt::zero());
-        voting_options.push(ModInt::one());
+        voting_options.push(scalar(&pub_key, 0));
+        voting_options.push(scalar(&pub_key, 1));
 
-        let message: ModInt = ModInt {
-            value: BigInt::one(),
-            modulus: BigInt::from(5) // must be equal to the value p of the public key
-        };
-        let cipher_text = encrypt(&pub_key, message.clone());
+        let message = scalar(&pub_key, 1);
+        let (cipher_text, randomness) = encrypt(&pub_key, message);
         let chosen_vote_idx = 1;
 
-        let pre_image_set = PreImageSet {
-            pre_images: vec![
-                ModInt::from_value_modulus(BigInt::from(1), BigInt::from(5)),
-                ModInt::from_value_modulus(BigInt::from(0), BigInt::from(5))
-            ]
-        };
+        let pre_image_set = pre_image_set(&pub_key);
 
-        let image_set = ImageSet::new(pub_key.g.clone(), pre_image_set.clone());
+        let image_set = ImageSet::new(pub_key.g(), pre_image_set.clone());
 
         let proof = CaiProof::new(
             pub_key.clone(),
             cipher_text.clone(),
+            randomness.clone(),
             pre_image_set.clone(),
             image_set.clone(),
             chosen_vote_idx,
-            voting_options.clone()
+            voting_options.clone(),
+            CONTEXT
         );
 
         let is_proven = proof.verify(
             pub_key.clone(),
             cipher_text.clone(),
             image_set.clone(),
-            voting_options.clone()
+            voting_options.clone(),
+            CONTEXT
         );
 
         assert!(is_proven);
     }
 
     #[test]
-    pub fn test_invalid_proof() {
-        //h := (g^x) mod p
-        //2 := 2^5 mod 5
-        let pub_key: PublicKey = PublicKey {
-            p: ModInt::from_value_modulus(BigInt::from(5), BigInt::zero()),
-            q: ModInt::from_value_modulus(BigInt::from(2), BigInt::zero()),
-            h: ModInt::from_value_modulus(BigInt::from(32), BigInt::from(5)),
-            g: ModInt::from_value_modulus(BigInt::from(2), BigInt::from(5))
+    pub fn test_wrong_context_rejected() {
+        let pub_key = public_key();
+
+        let voting_options = vec![scalar(&pub_key, 0), scalar(&pub_key, 1)];
+        let (cipher_text, randomness) = encrypt(&pub_key, scalar(&pub_key, 1));
+
+        let pre_image_set = pre_image_set(&pub_key);
+        let image_set = ImageSet::new(pub_key.g(), pre_image_set.clone());
+
+        let proof = CaiProof::new(pub_key.clone(), cipher_text.clone(), randomness, pre_image_set, image_set.clone(), 1, voting_options.clone(), CONTEXT);
+
+        assert!(proof.verify(pub_key.clone(), cipher_text.clone(), image_set.clone(), voting_options.clone(), CONTEXT));
+
+        // the proof cannot be replayed onto another ballot or another election
+        assert!(!proof.verify(pub_key.clone(), cipher_text.clone(), image_set.clone(), voting_options.clone(), b"election-1/voter-2/question-1"));
+        assert!(!proof.verify(pub_key, cipher_text, image_set, voting_options, b"election-2/voter-1/question-1"));
+    }
+
+    #[test]
+    pub fn test_par_verify() {
+        let pub_key = public_key();
+
+        let voting_options = vec![scalar(&pub_key, 0), scalar(&pub_key, 1)];
+        let contexts: Vec<Vec<u8>> = (0..6)
+            .map(|voter| format!("election-1/voter-{}/question-1", voter).into_bytes())
+            .collect();
+
+        let mut ballots: Vec<(CipherText, CaiProof, ImageSet, &[u8])> = contexts.iter().enumerate()
+            .map(|(voter, context)| {
+                let chosen_vote_idx = voter % voting_options.len();
+                let (cipher_text, randomness) = encrypt(&pub_key, voting_options[chosen_vote_idx].clone());
+
+                let pre_image_set = PreImageSet {
+                    pre_images: vec![
+                        Scalar::random(pub_key.group()),
+                        Scalar::random(pub_key.group())
+                    ]
+                };
+                let image_set = ImageSet::new(pub_key.g(), pre_image_set.clone());
+
+                let proof = CaiProof::new(
+                    pub_key.clone(),
+                    cipher_text.clone(),
+                    randomness,
+                    pre_image_set,
+                    image_set.clone(),
+                    chosen_vote_idx,
+                    voting_options.clone(),
+                    context
+                );
+
+                (cipher_text, proof, image_set, context.as_slice())
+            })
+            .collect();
+
+        // the image set belongs to another voter
+        ballots[4].2 = ballots[5].2.clone();
+
+        let expected: Vec<bool> = ballots.iter()
+            .map(|&(ref cipher_text, ref proof, ref image_set, context)| {
+                proof.verify(pub_key.clone(), cipher_text.clone(), image_set.clone(), voting_options.clone(), context)
+            })
+            .collect();
+
+        assert_eq!(vec![true, true, true, true, false, true], expected);
+        assert_eq!(expected, CaiProof::par_verify(&pub_key, &ballots, &voting_options));
+    }
+
+    #[test]
+    pub fn test_precomputed_public_key() {
+        let pub_key = public_key();
+        let precomputed = PrecomputedPublicKey::new(pub_key.clone());
+
+        let voting_options = vec![scalar(&pub_key, 0), scalar(&pub_key, 1)];
+        let (cipher_text, randomness) = encrypt(&precomputed, scalar(&pub_key, 0));
+
+        let pre_image_set = PreImageSet {
+            pre_images: vec![scalar(&pub_key, 5), scalar(&pub_key, 7)]
         };
 
+        let image_set = ImageSet::from_public_key(&precomputed, pre_image_set.clone());
+        assert_eq!(ImageSet::new(pub_key.g(), pre_image_set.clone()).images, image_set.images);
+
+        let proof = CaiProof::new(
+            &precomputed,
+            cipher_text.clone(),
+            randomness,
+            pre_image_set,
+            image_set.clone(),
+            0,
+            voting_options.clone(),
+            CONTEXT
+        );
+
+        assert!(proof.verify(pub_key, cipher_text, image_set, voting_options, CONTEXT));
+    }
+
+    #[test]
+    pub fn test_invalid_proof() {
+        let pub_key = public_key();
+
         let mut voting_options = Vec::new();
-        voting_options.push(ModInt::zero());
-        voting_options.push(ModInt::one());
+        voting_options.push(scalar(&pub_key, 0));
+        voting_options.push(scalar(&pub_key, 1));
 
-        let message: ModInt = ModInt {
-            value: BigInt::one(),
-            modulus: BigInt::from(5) // must be equal to the value p of the public key
-        };
-        let cipher_text = encrypt(&pub_key, message.clone());
+        let message = scalar(&pub_key, 1);
+        let (cipher_text, randomness) = encrypt(&pub_key, message);
         let chosen_vote_idx = 1;
 
-        let pre_image_set = PreImageSet {
-            pre_images: vec![
-                ModInt::from_value_modulus(BigInt::from(1), BigInt::from(5)),
-                ModInt::from_value_modulus(BigInt::from(0), BigInt::from(5))
-            ]
-        };
+        let pre_image_set = pre_image_set(&pub_key);
 
-        let image_set = ImageSet::new(pub_key.g.clone(), pre_image_set.clone());
+        let image_set = ImageSet::new(pub_key.g(), pre_image_set.clone());
 
         let proof = CaiProof::new(
             pub_key.clone(),
             cipher_text.clone(),
+            randomness.clone(),
             pre_image_set.clone(),
             image_set.clone(),
             chosen_vote_idx,
-            voting_options.clone()
+            voting_options.clone(),
+            CONTEXT
         );
 
         let fake_cipher_text = CipherText {
-            big_g: ModInt::from_value_modulus(BigInt::from(1), BigInt::from(0)),
-            big_h: ModInt::from_value_modulus(BigInt::from(2), BigInt::from(0)),
-            random: ModInt::from_value_modulus(BigInt::from(3), BigInt::from(0))
+            big_g: GroupElement::identity(pub_key.group()),
+            big_h: pub_key.g()
         };
 
         let is_proven = proof.verify(
             pub_key.clone(),
             fake_cipher_text.clone(),
             image_set.clone(),
-            voting_options.clone()
+            voting_options.clone(),
+            CONTEXT
         );
 
         assert!(!is_proven);
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn test_malformed_input() {
+        let pub_key = public_key();
+
+        let voting_options = vec![scalar(&pub_key, 0), scalar(&pub_key, 1)];
+
+        let message = scalar(&pub_key, 1);
+        let (cipher_text, randomness) = encrypt(&pub_key, message);
+
+        let pre_image_set = pre_image_set(&pub_key);
+
+        let image_set = ImageSet::new(pub_key.g(), pre_image_set.clone());
+
+        match CaiProof::try_new(pub_key.clone(), cipher_text.clone(), randomness.clone(), pre_image_set.clone(), image_set.clone(), 2, voting_options.clone(), CONTEXT) {
+            Err(Error::IndexOutOfBounds { index: 2, length: 2 }) => (),
+            other => panic!("expected an index out of bounds, got {:?}", other)
+        }
+
+        match CaiProof::try_new(pub_key.clone(), cipher_text.clone(), randomness.clone(), pre_image_set.clone(), image_set.clone(), 1, vec![scalar(&pub_key, 0)], CONTEXT) {
+            Err(Error::LengthMismatch { expected: 2, actual: 1 }) => (),
+            other => panic!("expected a length mismatch, got {:?}", other)
+        }
+
+        let proof = CaiProof::new(pub_key.clone(), cipher_text.clone(), randomness.clone(), pre_image_set.clone(), image_set.clone(), 1, voting_options.clone(), CONTEXT);
+
+        let more_voting_options = vec![scalar(&pub_key, 0), scalar(&pub_key, 1), scalar(&pub_key, 2)];
+        match proof.try_verify(pub_key.clone(), cipher_text.clone(), image_set.clone(), more_voting_options.clone(), CONTEXT) {
+            Err(Error::LengthMismatch { expected: 3, actual: 2 }) => (),
+            other => panic!("expected a length mismatch, got {:?}", other)
+        }
+        assert!(!proof.verify(pub_key.clone(), cipher_text.clone(), image_set.clone(), more_voting_options, CONTEXT));
+    }
+
+    #[test]
+    pub fn test_other_group() {
+        let pub_key = public_key();
+        let other_group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+
+        let voting_options = vec![scalar(&pub_key, 0), scalar(&pub_key, 1)];
+        let (cipher_text, randomness) = encrypt(&pub_key, scalar(&pub_key, 1));
+
+        let pre_image_set = pre_image_set(&pub_key);
+        let image_set = ImageSet::new(pub_key.g(), pre_image_set.clone());
+
+        // the images were created for another group
+        let other_pre_image_set = PreImageSet {
+            pre_images: vec![Scalar::new(&other_group, BigInt::from(1)), Scalar::zero(&other_group)]
+        };
+        let other_image_set = ImageSet::new(GroupElement::generator(&other_group), other_pre_image_set.clone());
+
+        match CaiProof::try_new(pub_key.clone(), cipher_text.clone(), randomness.clone(), pre_image_set.clone(), other_image_set.clone(), 1, voting_options.clone(), CONTEXT) {
+            Err(Error::GroupMismatch) => (),
+            other => panic!("expected a group mismatch, got {:?}", other)
+        }
+
+        match CaiProof::try_new(pub_key.clone(), cipher_text.clone(), randomness.clone(), other_pre_image_set, image_set.clone(), 1, voting_options.clone(), CONTEXT) {
+            Err(Error::GroupMismatch) => (),
+            other => panic!("expected a group mismatch, got {:?}", other)
+        }
+
+        let proof = CaiProof::new(pub_key.clone(), cipher_text.clone(), randomness, pre_image_set, image_set.clone(), 1, voting_options.clone(), CONTEXT);
+
+        match proof.try_verify(pub_key.clone(), cipher_text.clone(), other_image_set, voting_options, CONTEXT) {
+            Err(Error::GroupMismatch) => (),
+            other => panic!("expected a group mismatch, got {:?}", other)
+        }
+
+        let other_voting_options = vec![Scalar::zero(&other_group), Scalar::new(&other_group, BigInt::from(1))];
+        match proof.try_verify(pub_key.clone(), cipher_text.clone(), image_set.clone(), other_voting_options.clone(), CONTEXT) {
+            Err(Error::GroupMismatch) => (),
+            other => panic!("expected a group mismatch, got {:?}", other)
+        }
+        assert!(!proof.verify(pub_key, cipher_text, image_set, other_voting_options, CONTEXT));
+    }
+
+    #[test]
+    pub fn test_deserialize_in_group() {
+        let pub_key = public_key();
+
+        let voting_options = vec![scalar(&pub_key, 0), scalar(&pub_key, 1)];
+        let (cipher_text, randomness) = encrypt(&pub_key, scalar(&pub_key, 1));
+
+        let pre_image_set = pre_image_set(&pub_key);
+        let image_set = ImageSet::new(pub_key.g(), pre_image_set.clone());
+
+        let proof = CaiProof::new(pub_key.clone(), cipher_text.clone(), randomness, pre_image_set.clone(), image_set.clone(), 1, voting_options.clone(), CONTEXT);
+
+        let proof_json = ::serde_json::to_string(&proof).unwrap();
+        let image_set_json = ::serde_json::to_string(&image_set).unwrap();
+        let pre_image_set_json = ::serde_json::to_string(&pre_image_set).unwrap();
+
+        let decoded_proof: CaiProof = in_group::from_str(pub_key.group(), &proof_json).unwrap();
+        let decoded_image_set: ImageSet = in_group::from_str(pub_key.group(), &image_set_json).unwrap();
+        let decoded_pre_image_set: PreImageSet = in_group::from_str(pub_key.group(), &pre_image_set_json).unwrap();
+
+        assert_eq!(proof, decoded_proof);
+        assert_eq!(image_set.images, decoded_image_set.images);
+        assert_eq!(pre_image_set.pre_images, decoded_pre_image_set.pre_images);
+        assert!(decoded_proof.verify(pub_key, cipher_text, decoded_image_set, voting_options, CONTEXT));
+
+        // the images are not elements of the subgroup of order 11
+        let other_group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        assert!(in_group::from_str::<ImageSet>(&other_group, &image_set_json).is_err());
+    }
+
+    #[test]
+    pub fn test_challenges_not_adding_up() {
+        let pub_key = public_key();
+
+        let voting_options = vec![scalar(&pub_key, 0), scalar(&pub_key, 1)];
+
+        // encrypt a value which is not a voting option
+        let (cipher_text, _) = encrypt(&pub_key, scalar(&pub_key, 5));
+        let image_set = ImageSet::new(pub_key.g(), pre_image_set(&pub_key));
+
+        // simulate all options with arbitrary challenges,
+        // which do not add up to the challenge of the transcript
+        let random = || Scalar::random(pub_key.group());
+        let s1_options: Vec<Scalar> = vec![random(), random()];
+        let s2_options: Vec<Scalar> = vec![random(), random()];
+        let h1_options: Vec<Scalar> = vec![random(), random()];
+        let h2_options: Vec<Scalar> = vec![random(), random()];
+
+        let mut transcript = CaiProof::transcript(&pub_key, &cipher_text, &image_set, &voting_options, CONTEXT);
+        for i in 0..voting_options.len() {
+            let c1_i = pub_key.g().pow(&s1_options[i]) * cipher_text.big_g.clone().pow(&h1_options[i].clone().neg());
+            let c2_i = pub_key.h.clone().pow(&s1_options[i]) * (cipher_text.big_h.clone() / pub_key.g().pow(&voting_options[i])).pow(&h1_options[i].clone().neg());
+            let r_i = pub_key.g().pow(&s2_options[i]) * image_set.images[i].clone().pow(&h2_options[i].clone().neg());
+
+            transcript.append_element(b"c1", &c1_i);
+            transcript.append_element(b"c2", &c2_i);
+            transcript.append_element(b"r", &r_i);
+        }
+
+        let forged = CaiProof {
+            s1_options,
+            s2_options,
+            h1_options,
+            h2_options,
+            h: transcript.challenge(b"h", pub_key.group())
+        };
+
+        // the challenge h matches the transcript, so only the sum of the challenges is wrong
+        assert!(!forged.challenges_match(&forged.h));
+        assert!(!forged.verify(pub_key, cipher_text, image_set, voting_options, CONTEXT));
+    }
+
+    #[test]
+    pub fn test_to_from_bytes() {
+        let pub_key = public_key();
+
+        let voting_options = vec![scalar(&pub_key, 0), scalar(&pub_key, 1)];
+        let (cipher_text, randomness) = encrypt(&pub_key, scalar(&pub_key, 1));
+
+        let pre_image_set = pre_image_set(&pub_key);
+        let image_set = ImageSet::new(pub_key.g(), pre_image_set.clone());
+
+        let proof = CaiProof::new(pub_key.clone(), cipher_text.clone(), randomness, pre_image_set, image_set.clone(), 1, voting_options.clone(), CONTEXT);
+
+        // the amount of options followed by 4 * 2 + 1 scalars of 6 bytes each
+        let bytes = proof.to_bytes();
+        assert_eq!(4 + 9 * 6, bytes.len());
+
+        let decoded = CaiProof::from_bytes(&bytes, &pub_key).unwrap();
+        assert_eq!(proof, decoded);
+        assert!(decoded.verify(pub_key.clone(), cipher_text, image_set, voting_options, CONTEXT));
+
+        match CaiProof::from_bytes(&bytes[..bytes.len() - 1], &pub_key) {
+            Err(Error::InvalidEncoding) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // h > q is not reduced
+        let mut not_reduced = bytes[..bytes.len() - 6].to_vec();
+        not_reduced.extend_from_slice(&[0xff, 0, 0, 0, 0, 0]);
+        match CaiProof::from_bytes(&not_reduced, &pub_key) {
+            Err(Error::ValueNotReduced) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -1,10 +1,59 @@
-use ::el_gamal::ciphertext::CipherText;
+use ::arithmetic::scalar::Scalar;
+use ::el_gamal::ciphertext::{CipherText, Randomness};
+use ::error::Error;
+use ::parallel;
+use num::traits::Pow;
+use std::iter::Sum;
+use std::ops::Neg;
 
 /// # Homomorphic Operation
 ///
 /// Operate in a homomorphic way on the given cipher text.
-pub trait Operate {
-    fn operate(self, cipher_text: CipherText) -> CipherText;
+///
+/// The operation is implemented for cipher texts, for the randomness used
+/// to create them, and for pairs of both. The latter combines the cipher
+/// texts along with their randomness, so that proofs can be created for the
+/// resulting cipher text.
+///
+/// Combining values of different groups panics.
+pub trait Operate: Sized {
+    /// Combine with the other cipher text, i.e. add the encrypted plain texts.
+    fn operate(self, other: Self) -> Self;
+
+    /// Multiply the encrypted plain text with the scalar `k`.
+    fn scale(self, k: &Scalar) -> Self;
+
+    /// Negate the encrypted plain text.
+    ///
+    /// Returns `Error::NotInvertible` if an element of the cipher text has
+    /// no inverse modulo `p`, which only happens for malformed cipher texts.
+    fn negate(self) -> Result<Self, Error>;
+
+    /// Subtract the plain text encrypted by the other cipher text from this one.
+    ///
+    /// Returns an error if the other cipher text cannot be negated (see `Operate::negate`).
+    fn sub(self, other: Self) -> Result<Self, Error> {
+        Ok(self.operate(other.negate()?))
+    }
+
+    /// Combine all given cipher texts, i.e. add all encrypted plain texts.
+    /// Returns `None` if no cipher texts are given.
+    fn sum<I: IntoIterator<Item = Self>>(elements: I) -> Option<Self> {
+        let mut iter = elements.into_iter();
+        let first = iter.next()?;
+
+        Some(iter.fold(first, |sum, element| sum.operate(element)))
+    }
+
+    /// Combine all given cipher texts as described in `Operate::sum`.
+    ///
+    /// If the `parallel` feature is enabled, the cipher texts are split into
+    /// contiguous chunks which are combined on all available cores. As the
+    /// operation is associative, the result does not depend on the amount of cores.
+    /// Returns `None` if no cipher texts are given.
+    fn par_sum(elements: Vec<Self>) -> Option<Self> where Self: Send {
+        parallel::reduce(elements, parallel::threads(), Self::operate)
+    }
 }
 
 impl Operate for CipherText {
@@ -12,40 +61,272 @@ impl Operate for CipherText {
     fn operate(self, cipher_text: CipherText) -> CipherText {
         CipherText {
             big_g: self.big_g * cipher_text.big_g,
-            big_h: self.big_h * cipher_text.big_h,
-            random: self.random + cipher_text.random
+            big_h: self.big_h * cipher_text.big_h
+        }
+    }
+
+    fn scale(self, k: &Scalar) -> CipherText {
+        CipherText {
+            big_g: self.big_g.pow(k),
+            big_h: self.big_h.pow(k)
+        }
+    }
+
+    fn negate(self) -> Result<CipherText, Error> {
+        Ok(CipherText {
+            big_g: self.big_g.invert()?,
+            big_h: self.big_h.invert()?
+        })
+    }
+}
+
+/// Combine cipher texts using `Iterator::sum`, e.g. `cipher_texts.into_iter().sum::<Option<CipherText>>()`.
+/// As the identity depends on the group, the sum of no cipher texts is `None` (see `Operate::sum`).
+impl Sum<CipherText> for Option<CipherText> {
+    fn sum<I: Iterator<Item = CipherText>>(iter: I) -> Option<CipherText> {
+        Operate::sum(iter)
+    }
+}
+
+impl Operate for Randomness {
+
+    fn operate(self, randomness: Randomness) -> Randomness {
+        Randomness {
+            random: self.random + randomness.random
+        }
+    }
+
+    fn scale(self, k: &Scalar) -> Randomness {
+        Randomness {
+            random: self.random * k.clone()
         }
     }
+
+    fn negate(self) -> Result<Randomness, Error> {
+        Ok(Randomness {
+            random: self.random.neg()
+        })
+    }
+}
+
+impl Operate for (CipherText, Randomness) {
+
+    fn operate(self, other: (CipherText, Randomness)) -> (CipherText, Randomness) {
+        (self.0.operate(other.0), self.1.operate(other.1))
+    }
+
+    fn scale(self, k: &Scalar) -> (CipherText, Randomness) {
+        (self.0.scale(k), self.1.scale(k))
+    }
+
+    fn negate(self) -> Result<(CipherText, Randomness), Error> {
+        Ok((self.0.negate()?, self.1.negate()?))
+    }
 }
 
 #[cfg(test)]
 mod additive_tests {
 
-    use ::arithmetic::mod_int::From;
-    use ::arithmetic::mod_int::ModInt;
+    use ::arithmetic::group_element::GroupElement;
+    use ::arithmetic::scalar::Scalar;
     use ::el_gamal::additive::Operate;
-    use ::el_gamal::ciphertext::CipherText;
+    use ::el_gamal::ciphertext::{CipherText, Randomness};
+    use ::el_gamal::encryption::{encrypt, decrypt, KeyPair};
+    use ::arithmetic::group::Group;
+    use ::el_gamal::encryption::{Decryptor, PublicKey};
+    use ::error::Error;
     use ::num::BigInt;
+    use ::num::traits::Pow;
+    use std::sync::Arc;
+
+    fn small_group() -> Arc<Group> {
+        Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)))
+    }
+
+    fn message(pub_key: &PublicKey, value: i64) -> Scalar {
+        Scalar::new(pub_key.group(), BigInt::from(value))
+    }
 
     #[test]
     fn test_additive_elgamal() {
+        let group = small_group();
+        let element = |value: i64| GroupElement::new(&group, BigInt::from(value)).unwrap();
+
         let c1: CipherText = CipherText {
-            big_g: ModInt::from_value(BigInt::from(2)),
-            big_h: ModInt::from_value(BigInt::from(4)),
-            random: ModInt::from_value(BigInt::from(1)),
+            big_g: element(2),
+            big_h: element(4),
         };
 
         let c2: CipherText = CipherText {
-            big_g: ModInt::from_value(BigInt::from(2)),
-            big_h: ModInt::from_value(BigInt::from(1)),
-            random: ModInt::from_value(BigInt::from(1)),
+            big_g: element(2),
+            big_h: element(1),
         };
 
 
         let res: CipherText = c1.operate(c2);
 
-        assert_eq!(BigInt::from(4), res.big_g.value);
-        assert_eq!(BigInt::from(4), res.big_h.value);
-        assert_eq!(BigInt::from(2), res.random.value);
+        assert_eq!(BigInt::from(4), *res.big_g.value());
+        assert_eq!(BigInt::from(4), *res.big_h.value());
+    }
+
+    #[test]
+    fn test_additive_randomness() {
+        let group = small_group();
+
+        let r1: Randomness = Randomness {
+            random: Scalar::new(&group, BigInt::from(7)),
+        };
+
+        let r2: Randomness = Randomness {
+            random: Scalar::new(&group, BigInt::from(5)),
+        };
+
+        let res: Randomness = r1.operate(r2);
+
+        assert_eq!(BigInt::from(1), *res.random.value());
+        assert_eq!(group, *res.random.group());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_additive_with_randomness() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let pub_key = key_pair.public_key;
+
+        let e1 = encrypt(&pub_key, message(&pub_key, 2));
+        let e2 = encrypt(&pub_key, message(&pub_key, 3));
+
+        let (cipher_text, randomness) = e1.operate(e2);
+
+        // the combined randomness re-creates the combined cipher text
+        let g_to_r = pub_key.g().pow(&randomness.random);
+        assert_eq!(g_to_r, cipher_text.big_g);
+
+        let h_to_r = pub_key.h.clone().pow(&randomness.random);
+        let g_to_m = pub_key.g().pow(&message(&pub_key, 5));
+        assert_eq!(h_to_r * g_to_m, cipher_text.big_h);
+
+        assert_eq!(BigInt::from(5), *decrypt(key_pair.private_key, cipher_text, 1000).unwrap().value());
+    }
+
+    /// Asserts that the randomness re-creates the given cipher text of the plain text.
+    fn assert_randomness(pub_key: &PublicKey, cipher_text: &CipherText, randomness: &Randomness, plain_text: i64) {
+        assert_eq!(pub_key.g().pow(&randomness.random), cipher_text.big_g);
+
+        let h_to_r = pub_key.h.clone().pow(&randomness.random);
+        let g_to_m = pub_key.g().pow(&message(pub_key, plain_text));
+        assert_eq!(h_to_r * g_to_m, cipher_text.big_h);
+    }
+
+    #[test]
+    fn test_scale() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let pub_key = key_pair.public_key;
+
+        let e = encrypt(&pub_key, message(&pub_key, 3));
+        let (cipher_text, randomness) = e.scale(&message(&pub_key, 4));
+
+        assert_randomness(&pub_key, &cipher_text, &randomness, 12);
+        assert_eq!(BigInt::from(12), *decrypt(key_pair.private_key, cipher_text, 1000).unwrap().value());
+    }
+
+    #[test]
+    fn test_negate_and_sub() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let pub_key = key_pair.public_key;
+
+        let e1 = encrypt(&pub_key, message(&pub_key, 5));
+        let e2 = encrypt(&pub_key, message(&pub_key, 3));
+
+        let (difference, randomness) = e1.clone().sub(e2.clone()).unwrap();
+        assert_randomness(&pub_key, &difference, &randomness, 2);
+        assert_eq!(BigInt::from(2), *decrypt(key_pair.private_key.clone(), difference, 1000).unwrap().value());
+
+        // -3 + 5 = 2
+        let (negated, negated_randomness) = e2.negate().unwrap();
+        assert_randomness(&pub_key, &negated, &negated_randomness, -3);
+
+        let (sum, _) = (negated, negated_randomness).operate(e1);
+        assert_eq!(BigInt::from(2), *decrypt(key_pair.private_key, sum, 1000).unwrap().value());
+    }
+
+    #[test]
+    fn test_negate_malformed() {
+        let group = Arc::new(Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4)));
+
+        // a component congruent to zero has no inverse
+        let zero = CipherText {
+            big_g: GroupElement::generator(&group),
+            big_h: GroupElement::new_unchecked(&group, BigInt::from(0)),
+        };
+        match zero.clone().negate() {
+            Err(Error::NotInvertible) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert!(zero.clone().sub(zero).is_err());
+    }
+
+    #[test]
+    fn test_sum() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let pub_key = key_pair.public_key;
+
+        let encryptions: Vec<(CipherText, Randomness)> = (0..1000)
+            .map(|i| encrypt(&pub_key, message(&pub_key, i % 3)))
+            .collect();
+
+        let cipher_texts: Vec<CipherText> = encryptions.iter().map(|e| e.0.clone()).collect();
+        let tally = CipherText::sum(cipher_texts).unwrap();
+
+        let decryptor = Decryptor::new(key_pair.private_key, 2000);
+        assert_eq!(BigInt::from(999), *decryptor.decrypt(&tally).unwrap().value());
+
+        let (tally_with_randomness, randomness) = Operate::sum(encryptions).unwrap();
+        assert_eq!(tally, tally_with_randomness);
+        assert_randomness(&pub_key, &tally, &randomness, 999);
+
+        assert_eq!(None, CipherText::sum(vec![]));
+    }
+
+    #[test]
+    fn test_iterator_sum() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let pub_key = key_pair.public_key;
+
+        let cipher_texts: Vec<CipherText> = (0..100)
+            .map(|i| encrypt(&pub_key, message(&pub_key, i % 3)).0)
+            .collect();
+
+        let tally: Option<CipherText> = cipher_texts.iter().cloned().sum();
+        assert_eq!(CipherText::sum(cipher_texts), tally);
+        assert_eq!(BigInt::from(99), *decrypt(key_pair.private_key, tally.unwrap(), 1000).unwrap().value());
+
+        assert_eq!(None, Vec::<CipherText>::new().into_iter().sum::<Option<CipherText>>());
+    }
+
+    #[test]
+    fn test_par_sum() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let pub_key = key_pair.public_key;
+
+        let encryptions: Vec<(CipherText, Randomness)> = (0..1000)
+            .map(|i| encrypt(&pub_key, message(&pub_key, i % 3)))
+            .collect();
+
+        let cipher_texts: Vec<CipherText> = encryptions.iter().map(|e| e.0.clone()).collect();
+        let tally = CipherText::par_sum(cipher_texts.clone()).unwrap();
+        assert_eq!(CipherText::sum(cipher_texts).unwrap(), tally);
+
+        let (tally_with_randomness, randomness) = Operate::par_sum(encryptions).unwrap();
+        assert_eq!(tally, tally_with_randomness);
+        assert_randomness(&pub_key, &tally, &randomness, 999);
+
+        assert_eq!(None, CipherText::par_sum(vec![]));
+    }
+}
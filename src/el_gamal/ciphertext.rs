@@ -1,9 +1,271 @@
+use ::arithmetic::encoding::ByteReader;
+use ::arithmetic::group::Group;
+use ::arithmetic::group_element::GroupElement;
+use ::arithmetic::in_group::{self, DeserializeInGroup};
 use ::arithmetic::mod_int::ModInt;
+use ::arithmetic::scalar::Scalar;
+use ::el_gamal::additive::Operate;
+use ::el_gamal::encryption::PublicKey;
+use ::error::Error;
+use serde::{Deserialize, Deserializer};
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
 
 /// # ElGamal CipherText.
-#[derive(Eq, PartialEq, Clone, Debug, Hash, Serialize, Deserialize)]
+///
+/// The public part `(G, H) = (g^r, h^r * g^m)` of an encryption.
+/// The randomness `r` is kept separately in a `Randomness`.
+///
+/// Cipher texts are serialized without their group. Therefore,
+/// they are deserialized using `DeserializeInGroup`.
+#[derive(Eq, PartialEq, Clone, Debug, Hash, Serialize)]
+#[serde(into = "CipherTextRepr")]
 pub struct CipherText {
-    pub big_g: ModInt,
-    pub big_h: ModInt,
-    pub random: ModInt
-}
\ No newline at end of file
+    pub big_g: GroupElement,
+    pub big_h: GroupElement
+}
+
+/// The serialized form of a `CipherText`.
+#[derive(Clone, Serialize, Deserialize)]
+struct CipherTextRepr {
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    big_g: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    big_h: ModInt
+}
+
+impl ::std::convert::From<CipherText> for CipherTextRepr {
+    fn from(cipher_text: CipherText) -> Self {
+        CipherTextRepr {
+            big_g: cipher_text.big_g.to_mod_int(),
+            big_h: cipher_text.big_h.to_mod_int()
+        }
+    }
+}
+
+impl DeserializeInGroup for CipherText {
+    fn deserialize_in<'de, D: Deserializer<'de>>(group: &Arc<Group>, deserializer: D) -> Result<Self, D::Error> {
+        let repr = CipherTextRepr::deserialize(deserializer)?;
+
+        Ok(CipherText {
+            big_g: in_group::element(group, &repr.big_g)?,
+            big_h: in_group::element(group, &repr.big_h)?
+        })
+    }
+}
+
+impl CipherText {
+    /// Re-randomize this cipher text by multiplying it with a fresh encryption of zero.
+    /// The resulting cipher text encrypts the same plain text, but cannot be linked to this one.
+    ///
+    /// Returns the re-randomized cipher text along with the fresh randomness `r'`.
+    /// If this cipher text was created using the randomness `r`, the resulting one
+    /// has the randomness `r + r'`.
+    ///
+    /// - public_key: The public key used to encrypt this cipher text
+    pub fn rerandomize(&self, public_key: &PublicKey) -> (CipherText, Randomness) {
+        let randomness = Randomness {
+            random: Scalar::random(public_key.group())
+        };
+
+        (self.rerandomize_with(public_key, &randomness), randomness)
+    }
+
+    /// Re-randomize this cipher text as described in `CipherText::rerandomize`,
+    /// using the given randomness `r'` for the encryption of zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cipher text or the randomness belong to another group than the public key.
+    ///
+    /// - public_key: The public key used to encrypt this cipher text
+    /// - randomness: The randomness `r'` to use
+    pub fn rerandomize_with(&self, public_key: &PublicKey, randomness: &Randomness) -> CipherText {
+        let zero_cipher_text = CipherText {
+            big_g: public_key.g().pow_secret(&randomness.random),
+            big_h: public_key.h.clone().pow_secret(&randomness.random)
+        };
+
+        self.clone().operate(zero_cipher_text)
+    }
+
+    /// Encode this cipher text as the fixed-width big-endian encodings
+    /// of `G` and `H` (see `GroupElement::to_bytes`).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.big_g.to_bytes();
+        bytes.extend_from_slice(&self.big_h.to_bytes());
+
+        bytes
+    }
+
+    /// Decode a cipher text encoded by `CipherText::to_bytes`.
+    ///
+    /// Returns `Error::InvalidEncoding` if the amount of bytes is wrong,
+    /// `Error::ValueNotReduced` if an element is not reduced modulo `p` and
+    /// `Error::ElementNotInGroup` if an element is not in the subgroup of order `q`.
+    ///
+    /// - bytes: The encoded cipher text
+    /// - public_key: The public key used to encrypt the cipher text
+    pub fn from_bytes(bytes: &[u8], public_key: &PublicKey) -> Result<CipherText, Error> {
+        let group = public_key.group();
+        if bytes.len() != 2 * ModInt::byte_length(&group.p.value) {
+            return Err(Error::InvalidEncoding);
+        }
+
+        let mut reader = ByteReader::new(bytes);
+
+        let big_g = reader.read_element(group)?;
+        let big_h = reader.read_element(group)?;
+        reader.finish()?;
+
+        Ok(CipherText { big_g, big_h })
+    }
+}
+
+/// # Encryption Randomness
+///
+/// The randomness `r` used to create a particular cipher text.
+/// It is the secret witness required to prove statements about the
+/// encrypted plain text and must never be published along with the cipher text.
+#[derive(Eq, PartialEq, Clone, Hash)]
+pub struct Randomness {
+    pub random: Scalar
+}
+
+/// Omits the randomness, so that it does not end up in logs or panic messages.
+impl Debug for Randomness {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Randomness(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod ciphertext_test {
+
+    use ::arithmetic::group::Group;
+    use ::arithmetic::group_element::GroupElement;
+    use ::arithmetic::in_group;
+    use ::arithmetic::mod_int::From;
+    use ::arithmetic::mod_int::ModInt;
+    use ::arithmetic::scalar::Scalar;
+    use ::el_gamal::additive::Operate;
+    use ::el_gamal::ciphertext::{CipherText, Randomness};
+    use ::error::Error;
+    use ::el_gamal::encryption::{encrypt, decrypt, KeyPair};
+    use ::num::bigint::BigInt;
+    use ::num::traits::Pow;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_rerandomize() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let message = |value: i64| Scalar::new(key_pair.public_key.group(), BigInt::from(value));
+
+        let (cipher_text, randomness) = encrypt(&key_pair.public_key, message(3));
+
+        let (rerandomized, fresh_randomness) = cipher_text.rerandomize(&key_pair.public_key);
+
+        assert_ne!(cipher_text, rerandomized);
+        assert_eq!(BigInt::from(3), *decrypt(key_pair.private_key.clone(), rerandomized.clone(), 1000).unwrap().value());
+
+        // the combined randomness r + r' results in the same re-randomized cipher text
+        let combined_randomness = randomness.operate(fresh_randomness);
+        let pub_key = &key_pair.public_key;
+
+        assert_eq!(pub_key.g().pow(&combined_randomness.random), rerandomized.big_g);
+        assert_eq!(
+            pub_key.h.clone().pow(&combined_randomness.random) * pub_key.g().pow(&message(3)),
+            rerandomized.big_h
+        );
+    }
+
+    #[test]
+    fn test_debug_redacts_randomness() {
+        let group = Arc::new(Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4)));
+        let randomness = Randomness {
+            random: Scalar::new(&group, BigInt::from(1234567))
+        };
+
+        assert_eq!("Randomness(<redacted>)", format!("{:?}", randomness));
+    }
+
+    #[test]
+    fn test_rerandomize_with() {
+        let group = Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let group = key_pair.public_key.group();
+
+        let (cipher_text, _) = encrypt(&key_pair.public_key, Scalar::new(group, BigInt::from(2)));
+
+        let randomness = Randomness {
+            random: Scalar::new(group, BigInt::from(5))
+        };
+
+        let rerandomized = cipher_text.rerandomize_with(&key_pair.public_key, &randomness);
+        let same_rerandomized = cipher_text.rerandomize_with(&key_pair.public_key, &randomness);
+
+        assert_eq!(rerandomized, same_rerandomized);
+        // G' = G * g^5
+        assert_eq!(cipher_text.big_g.clone() * GroupElement::new(group, BigInt::from(12)).unwrap(), rerandomized.big_g);
+        assert_eq!(BigInt::from(2), *decrypt(key_pair.private_key.clone(), rerandomized, 1000).unwrap().value());
+    }
+
+    #[test]
+    fn test_deserialize_in_group() {
+        let group = Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let group = key_pair.public_key.group();
+
+        let (cipher_text, _) = encrypt(&key_pair.public_key, Scalar::new(group, BigInt::from(2)));
+        let json = ::serde_json::to_string(&cipher_text).unwrap();
+
+        assert_eq!(cipher_text, in_group::from_str(group, &json).unwrap());
+
+        // the elements are reduced modulo another prime
+        let other_group = Arc::new(Group::new(BigInt::from(47), BigInt::from(23), BigInt::from(4)));
+        assert!(in_group::from_str::<CipherText>(&other_group, &json).is_err());
+
+        // 5 is not a quadratic residue modulo 23
+        let not_in_group = CipherText {
+            big_g: GroupElement::new_unchecked(group, BigInt::from(5)),
+            big_h: cipher_text.big_h.clone()
+        };
+        let json = ::serde_json::to_string(&not_in_group).unwrap();
+        assert!(in_group::from_str::<CipherText>(group, &json).is_err());
+    }
+
+    #[test]
+    fn test_to_from_bytes() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+
+        let (cipher_text, _) = encrypt(&key_pair.public_key, Scalar::new(key_pair.public_key.group(), BigInt::from(3)));
+
+        let bytes = cipher_text.to_bytes();
+        assert_eq!(12, bytes.len());
+        assert_eq!(cipher_text, CipherText::from_bytes(&bytes, &key_pair.public_key).unwrap());
+
+        match CipherText::from_bytes(&bytes[1..], &key_pair.public_key) {
+            Err(Error::InvalidEncoding) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // p itself is not reduced
+        let mut not_reduced = ModInt::from_value_modulus(BigInt::from(2199023255866i64), group.p.value.clone()).to_bytes();
+        *not_reduced.last_mut().unwrap() += 1;
+        not_reduced.extend_from_slice(&bytes[6..]);
+        match CipherText::from_bytes(&not_reduced, &key_pair.public_key) {
+            Err(Error::ValueNotReduced) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // p - 1 has order two, i.e. it is not in the subgroup of order q
+        let mut not_in_group = bytes[..6].to_vec();
+        not_in_group.extend_from_slice(&ModInt::from_value_modulus(BigInt::from(2199023255866i64), group.p.value.clone()).to_bytes());
+        match CipherText::from_bytes(&not_in_group, &key_pair.public_key) {
+            Err(Error::ElementNotInGroup) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use ::arithmetic::group::Group;
+use ::arithmetic::group_element::{same_group, GroupElement};
+use ::arithmetic::in_group::{self, DeserializeInGroup};
+use ::arithmetic::mod_int::ModInt;
+use ::arithmetic::scalar::Scalar;
+use ::el_gamal::ciphertext::CipherText;
+use ::el_gamal::encryption::{PrivateKey, PublicKey};
+use ::el_gamal::transcript::Transcript;
+use ::error::Error;
+use serde::{Deserialize, Deserializer};
+use std::ops::{Add, Mul, Neg};
+use std::sync::Arc;
+
+/// # Decryption Proof
+///
+/// A non-interactive Chaum-Pedersen proof that a decryption share `d = G^x`
+/// of a cipher text `(G, H)` was computed using the same secret `x` as the
+/// public value `h = g^x`, i.e. that `log_g(h) = log_G(d)`.
+///
+/// Since `g^m = H / d`, the proof allows anyone to check the decryption of
+/// a cipher text without knowing the private key.
+#[derive(Eq, PartialEq, Serialize, Hash, Clone, Debug)]
+#[serde(into = "DecryptionProofRepr")]
+pub struct DecryptionProof {
+    challenge: Scalar,
+    response: Scalar,
+}
+
+/// The serialized form of a `DecryptionProof`.
+#[derive(Clone, Serialize, Deserialize)]
+struct DecryptionProofRepr {
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    challenge: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    response: ModInt,
+}
+
+impl ::std::convert::From<DecryptionProof> for DecryptionProofRepr {
+    fn from(proof: DecryptionProof) -> Self {
+        DecryptionProofRepr {
+            challenge: proof.challenge.to_mod_int(),
+            response: proof.response.to_mod_int(),
+        }
+    }
+}
+
+impl DeserializeInGroup for DecryptionProof {
+    fn deserialize_in<'de, D: Deserializer<'de>>(group: &Arc<Group>, deserializer: D) -> Result<Self, D::Error> {
+        let repr = DecryptionProofRepr::deserialize(deserializer)?;
+
+        Ok(DecryptionProof {
+            challenge: in_group::scalar(group, &repr.challenge)?,
+            response: in_group::scalar(group, &repr.response)?,
+        })
+    }
+}
+
+impl DecryptionProof {
+    /// Create a new proof that the decryption share `d = G^x` of the given
+    /// cipher text was computed using the given private key.
+    ///
+    /// - private_key: The private key `x` used to compute the decryption share
+    /// - cipher_text: The cipher text `(G, H)` which is decrypted
+    /// - context: The context the proof is bound to, e.g. the IDs of the election and the question
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cipher text belongs to another group than the private key.
+    /// Use `DecryptionProof::try_new` to handle this case.
+    pub fn new(private_key: PrivateKey, cipher_text: CipherText, context: &[u8]) -> DecryptionProof {
+        match DecryptionProof::try_new(private_key, cipher_text, context) {
+            Ok(proof) => proof,
+            Err(e) => panic!("{}", e)
+        }
+    }
+
+    /// Create a new proof as described in `DecryptionProof::new`.
+    ///
+    /// Returns `Error::GroupMismatch` if the cipher text belongs to another group than the private key.
+    pub fn try_new(private_key: PrivateKey, cipher_text: CipherText, context: &[u8]) -> Result<DecryptionProof, Error> {
+        let same_groups = same_group(private_key.group(), cipher_text.big_g.group())
+            && same_group(private_key.group(), cipher_text.big_h.group());
+        if !same_groups {
+            return Err(Error::GroupMismatch);
+        }
+
+        let public_key = private_key.public_key();
+        let d = cipher_text.big_g.clone().pow_secret(&private_key.x);
+
+        let t = Scalar::random(private_key.group());
+
+        let a = public_key.g().pow_secret(&t);
+        let b = cipher_text.big_g.clone().pow_secret(&t);
+
+        let c = DecryptionProof::challenge(&public_key, &cipher_text, &d, &a, &b, context);
+        let s = c.clone().mul(private_key.x).add(t);
+
+        Ok(DecryptionProof {
+            challenge: c,
+            response: s,
+        })
+    }
+
+    /// Verify that the given decryption share `d` of the cipher text was
+    /// computed using the private key belonging to the given public key.
+    ///
+    /// - public_key: The public key `h = g^x`
+    /// - cipher_text: The cipher text `(G, H)` which was decrypted
+    /// - decryption_share: The decryption share `d = G^x`
+    /// - context: The context the proof was created for
+    pub fn verify(&self, public_key: PublicKey, cipher_text: CipherText, decryption_share: GroupElement, context: &[u8]) -> bool {
+        self.try_verify(public_key, cipher_text, decryption_share, context).unwrap_or(false)
+    }
+
+    /// Verify this proof as described in `DecryptionProof::verify`, returning
+    /// an error if the proof or its inputs are malformed.
+    ///
+    /// Returns `Error::GroupMismatch` if the proof, the cipher text or the
+    /// decryption share belong to another group than the public key.
+    pub fn try_verify(&self, public_key: PublicKey, cipher_text: CipherText, decryption_share: GroupElement, context: &[u8]) -> Result<bool, Error> {
+        let group = public_key.group();
+
+        let s: Scalar = self.response.clone();
+        let neg_c: Scalar = self.challenge.clone().neg();
+
+        // a = g^s * h^-c and b = G^s * d^-c
+        let a = GroupElement::multiexp(group, &[public_key.g(), public_key.h.clone()], &[s.clone(), neg_c.clone()])?;
+        let b = GroupElement::multiexp(group, &[cipher_text.big_g.clone(), decryption_share.clone()], &[s, neg_c])?;
+
+        if !same_group(group, cipher_text.big_h.group()) {
+            return Err(Error::GroupMismatch);
+        }
+
+        let new_c = DecryptionProof::challenge(&public_key, &cipher_text, &decryption_share, &a, &b, context);
+
+        self.challenge.checked_eq(&new_c)
+    }
+
+    /// Verify that the given cipher text decrypts to the given plain text,
+    /// i.e. that `d = H / g^m` is the decryption share proven by this proof.
+    ///
+    /// - public_key: The public key used to encrypt the plain text
+    /// - cipher_text: The cipher text `(G, H)` which was decrypted
+    /// - plain_text: The plain text `m` the cipher text decrypts to
+    /// - context: The context the proof was created for
+    pub fn verify_plain_text(&self, public_key: PublicKey, cipher_text: CipherText, plain_text: Scalar, context: &[u8]) -> bool {
+        let g_to_m = match public_key.g().checked_pow(&plain_text) {
+            Ok(g_to_m) => g_to_m,
+            Err(_) => return false
+        };
+
+        let decryption_share = match cipher_text.big_h.clone().checked_div(g_to_m) {
+            Ok(decryption_share) => decryption_share,
+            Err(_) => return false
+        };
+
+        self.verify(public_key, cipher_text, decryption_share, context)
+    }
+
+    fn challenge(public_key: &PublicKey, cipher_text: &CipherText, d: &GroupElement, a: &GroupElement, b: &GroupElement, context: &[u8]) -> Scalar {
+        let mut transcript = Transcript::new(b"decryption-proof");
+        transcript.append_message(b"context", context);
+        transcript.append_public_key(public_key);
+        transcript.append_cipher_text(b"cipher-text", cipher_text);
+        transcript.append_element(b"decryption-share", d);
+        transcript.append_element(b"a", a);
+        transcript.append_element(b"b", b);
+
+        transcript.challenge(b"c", public_key.group())
+    }
+}
+
+#[cfg(test)]
+mod decryption_proof_test {
+
+    use ::arithmetic::group::Group;
+    use ::arithmetic::group_element::GroupElement;
+    use ::arithmetic::in_group;
+    use ::arithmetic::scalar::Scalar;
+    use ::el_gamal::decryption_proof::DecryptionProof;
+    use ::el_gamal::encryption::{encrypt, KeyPair, PrivateKey, PublicKey};
+    use ::error::Error;
+    use ::num::bigint::BigInt;
+    use ::num::traits::Pow;
+    use std::sync::Arc;
+
+    const CONTEXT: &[u8] = b"election-1/question-1";
+
+    fn keys() -> (PublicKey, PrivateKey) {
+        let group = Arc::new(Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4)));
+
+        let public_key = PublicKey::from_group(&group, BigInt::from(1680623351278i64)).unwrap();
+
+        let private_key = PrivateKey {
+            x: Scalar::new(&group, BigInt::from(1234567)),
+        };
+
+        (public_key, private_key)
+    }
+
+    fn message(public_key: &PublicKey, value: i64) -> Scalar {
+        Scalar::new(public_key.group(), BigInt::from(value))
+    }
+
+    #[test]
+    fn test_decryption_proof() {
+        let (public_key, private_key) = keys();
+
+        let (cipher_text, _) = encrypt(&public_key, message(&public_key, 3));
+
+        let decryption_share = cipher_text.big_g.clone().pow(&private_key.x);
+        let proof = DecryptionProof::new(private_key.clone(), cipher_text.clone(), CONTEXT);
+
+        assert!(proof.verify(public_key.clone(), cipher_text.clone(), decryption_share.clone(), CONTEXT));
+        assert!(proof.verify_plain_text(public_key.clone(), cipher_text.clone(), message(&public_key, 3), CONTEXT));
+
+        assert!(!proof.verify_plain_text(public_key.clone(), cipher_text.clone(), message(&public_key, 4), CONTEXT));
+
+        let wrong_share = decryption_share * public_key.g();
+        assert!(!proof.verify(public_key.clone(), cipher_text.clone(), wrong_share, CONTEXT));
+
+        // the proof is bound to the cipher text
+        let (other_cipher_text, _) = encrypt(&public_key, message(&public_key, 3));
+        let other_share = other_cipher_text.big_g.clone().pow(&private_key.x);
+        assert!(!proof.verify(public_key.clone(), other_cipher_text, other_share, CONTEXT));
+
+        // the proof can be published along with the decryption share
+        let json = ::serde_json::to_string(&proof).unwrap();
+        assert_eq!(proof, in_group::from_str(public_key.group(), &json).unwrap());
+    }
+
+    #[test]
+    fn test_wrong_context_rejected() {
+        let (public_key, private_key) = keys();
+
+        let (cipher_text, _) = encrypt(&public_key, message(&public_key, 3));
+
+        let decryption_share = cipher_text.big_g.clone().pow(&private_key.x);
+        let proof = DecryptionProof::new(private_key, cipher_text.clone(), CONTEXT);
+
+        assert!(proof.verify(public_key.clone(), cipher_text.clone(), decryption_share.clone(), CONTEXT));
+        assert!(!proof.verify(public_key.clone(), cipher_text.clone(), decryption_share, b"election-2/question-1"));
+        assert!(!proof.verify_plain_text(public_key.clone(), cipher_text, message(&public_key, 3), b"election-2/question-1"));
+    }
+
+    #[test]
+    fn test_decryption_proof_other_key() {
+        let (public_key, private_key) = keys();
+
+        let (cipher_text, _) = encrypt(&public_key, message(&public_key, 1));
+
+        // prove a decryption share computed with a different secret
+        let other_private_key = PrivateKey {
+            x: Scalar::new(private_key.group(), BigInt::from(7654321)),
+        };
+
+        let decryption_share = cipher_text.big_g.clone().pow(&other_private_key.x);
+        let proof = DecryptionProof::new(other_private_key, cipher_text.clone(), CONTEXT);
+
+        assert!(!proof.verify(public_key, cipher_text, decryption_share, CONTEXT));
+    }
+
+    #[test]
+    fn test_elements_of_other_group() {
+        let (public_key, private_key) = keys();
+        let group = public_key.group().clone();
+
+        let (cipher_text, _) = encrypt(&public_key, message(&public_key, 2));
+        let decryption_share = cipher_text.big_g.clone().pow(&private_key.x);
+        let proof = DecryptionProof::new(private_key.clone(), cipher_text.clone(), CONTEXT);
+
+        // -1 has order 2, hence multiplying with it leaves the subgroup of order q
+        let minus_share = group.p.value.clone() - decryption_share.value().clone();
+        match GroupElement::new(&group, minus_share) {
+            Err(Error::ElementNotInGroup) => {}
+            other => panic!("expected an element outside of the subgroup, got {:?}", other),
+        }
+
+        let other_key_pair = KeyPair::generate(&Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        let (other_cipher_text, _) = encrypt(&other_key_pair.public_key, message(&other_key_pair.public_key, 2));
+        let other_share = other_cipher_text.big_g.clone().pow(&other_key_pair.private_key.x);
+
+        match proof.try_verify(public_key.clone(), cipher_text.clone(), other_share.clone(), CONTEXT) {
+            Err(Error::GroupMismatch) => {}
+            other => panic!("expected a group mismatch, got {:?}", other),
+        }
+        assert!(!proof.verify(public_key.clone(), cipher_text, other_share.clone(), CONTEXT));
+
+        match proof.try_verify(public_key, other_cipher_text.clone(), other_share, CONTEXT) {
+            Err(Error::GroupMismatch) => {}
+            other => panic!("expected a group mismatch, got {:?}", other),
+        }
+
+        match DecryptionProof::try_new(private_key, other_cipher_text, CONTEXT) {
+            Err(Error::GroupMismatch) => {}
+            other => panic!("expected a group mismatch, got {:?}", other),
+        }
+    }
+}
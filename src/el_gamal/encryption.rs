@@ -1,143 +1,560 @@
-use ::arithmetic::mod_int::From;
+use ::arithmetic::group::{Group, ValidationError};
+use ::arithmetic::group_element::{same_group, GroupElement};
 use ::arithmetic::mod_int::ModInt;
-use ::arithmetic::mod_int::RandModInt;
-use ::el_gamal::ciphertext::CipherText;
-use num::traits::Pow;
+use ::arithmetic::mod_inverse;
+use ::arithmetic::scalar::Scalar;
+use ::el_gamal::ciphertext::{CipherText, Randomness};
+use ::el_gamal::precomputed::FixedBasePow;
+use ::error::Error;
+use num::bigint::BigInt;
+use num::integer::Roots;
 use num::Zero;
 use num::One;
-use std::fs::File;
-use std::io::{Read, Write};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json;
-
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs::{File, OpenOptions};
+#[cfg(unix)]
+use std::fs::Permissions;
+use std::io::{self, Read, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+use std::sync::Arc;
+
+/// # Public Key
+///
+/// The public value `h = g^x`, which is bound to its group.
+///
+/// Keys are serialized along with the parameters `(p, q, g)` of their group.
+/// Deserialization fails if `h` does not lie in the subgroup of order `q`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "PublicKeyRepr", try_from = "PublicKeyRepr")]
 pub struct PublicKey {
-    pub p: ModInt,
-    pub q: ModInt,
-    pub h: ModInt,
-    pub g: ModInt,
+    pub h: GroupElement,
+}
+
+/// The serialized form of a `PublicKey`.
+#[derive(Clone, Serialize, Deserialize)]
+struct PublicKeyRepr {
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    p: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    q: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    h: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    g: ModInt,
+}
+
+impl ::std::convert::From<PublicKey> for PublicKeyRepr {
+    fn from(public_key: PublicKey) -> Self {
+        let group = public_key.group();
+
+        PublicKeyRepr {
+            p: group.p.clone(),
+            q: group.q.clone(),
+            h: public_key.h.to_mod_int(),
+            g: group.g.clone(),
+        }
+    }
+}
+
+impl TryFrom<PublicKeyRepr> for PublicKey {
+    type Error = Error;
+
+    fn try_from(repr: PublicKeyRepr) -> Result<Self, Error> {
+        let group = Arc::new(Group::new(repr.p.value, repr.q.value, repr.g.value));
+
+        Ok(PublicKey {
+            h: GroupElement::from_mod_int(&group, &repr.h)?,
+        })
+    }
 }
 
 impl PublicKey {
+    /// Load a PublicKey from its JSON representation at the given path.
+    ///
+    /// - path: The path of the public key file
+    ///
+    /// Returns `KeyError::Io` if the file cannot be read and `KeyError::Parse`
+    /// if its contents are not a valid public key.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, KeyError> {
+        read_json(path.as_ref())
+    }
+
+    /// Save this public key as JSON to the given path.
+    /// An existing file is overwritten.
+    ///
+    /// - path: The path of the public key file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), KeyError> {
+        let file = File::create(path)?;
+
+        write_json(self, file)
+    }
+
     /// Create a PublicKey based its string representation at a specific path.
     ///
-    /// - `public_key_file_name`: The file name of the public key.
-    ///                           Must reside in the same directory as the binary is launched.
+    /// - `public_key_file_name`: The file name of the public key relative
+    ///   to the directory the binary is launched in.
+    ///
+    /// # Panics
     ///
+    /// Panics if the file cannot be read or does not contain a valid public key.
+    #[deprecated(note = "use `PublicKey::load` instead")]
     pub fn new(public_key_file_name: &str) -> Self {
-        // Read the input file to string.
-        let mut file = File::open("./".to_owned() + public_key_file_name).unwrap();
-        let mut contents = String::new();
-        file.read_to_string(&mut contents).unwrap();
-
-        let public_key: PublicKey = match serde_json::from_str(&contents) {
-            Ok(public_key_data) => {
-                public_key_data
-            },
-            Err(e) => {
-                panic!("Failed to transform file {:?} into PublicKey: {:?}", file, e);
-            }
-        };
-
-        public_key
+        match PublicKey::load(Path::new(".").join(public_key_file_name)) {
+            Ok(public_key) => public_key,
+            Err(e) => panic!("Failed to read PublicKey from file {:?}: {}", public_key_file_name, e)
+        }
     }
 
     /// Write this public key to a file with the given path.
     ///
-    /// - `public_key_file_name`: The file name of the public key.
-    ///                           Must reside in the same directory as the binary is launched.
+    /// - `public_key_file_name`: The file name of the public key relative
+    ///   to the directory the binary is launched in.
     ///
+    /// # Panics
+    ///
+    /// Panics if the file cannot be written.
+    #[deprecated(note = "use `PublicKey::save` instead")]
     pub fn to_file(&self, public_key_file_name: &str) {
-        // Read the input file to string.
-        let mut file = File::create("./".to_owned() + public_key_file_name).unwrap();
+        if let Err(e) = self.save(Path::new(".").join(public_key_file_name)) {
+            panic!("Failed to write PublicKey to file {:?}: {}", public_key_file_name, e);
+        }
+    }
 
-        let public_key_str = serde_json::to_string_pretty(&self).unwrap();
-        file.write(public_key_str.as_bytes()).unwrap();
+    /// Create a PublicKey within the given group, e.g. one of the
+    /// standardized groups in `arithmetic::standard_groups`.
+    ///
+    /// Returns `Error::ElementNotInGroup` if `h` is not reduced modulo `p`
+    /// or does not lie in the subgroup of order `q`.
+    ///
+    /// - group: The parameters `(p, q, g)` of the group
+    /// - h: The public value `h = g^x mod p`
+    pub fn from_group(group: &Arc<Group>, h: BigInt) -> Result<Self, Error> {
+        Ok(PublicKey {
+            h: GroupElement::new(group, h)?,
+        })
+    }
+
+    /// Returns the parameters `(p, q, g)` of the group this key belongs to.
+    pub fn group(&self) -> &Arc<Group> {
+        self.h.group()
+    }
+
+    /// Returns the generator `g` of the group this key belongs to.
+    pub fn g(&self) -> GroupElement {
+        GroupElement::generator(self.group())
+    }
+
+    /// Validate this public key, i.e. check that its group parameters are valid
+    /// (see `Group::validate`), that `h` lies in the subgroup of order `q`
+    /// and that `h` is not equal to one.
+    ///
+    /// Encryption and the verification of proofs trust the public key.
+    /// Therefore, keys from untrusted sources must be validated before use.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let group = self.group();
+
+        group.validate()?;
+
+        if !group.is_subgroup_element(&self.h.to_mod_int()) {
+            return Err(ValidationError::PublicValueNotInSubgroup);
+        }
+
+        if self.h.is_identity() {
+            return Err(ValidationError::PublicValueIsOne);
+        }
+
+        Ok(())
     }
 }
 
+/// # Private Key
+///
+/// The secret `x` within `Z_q`, which is bound to its group.
+///
+/// Keys are serialized along with the parameters `(p, q, g)` of their group.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "PrivateKeyRepr", try_from = "PrivateKeyRepr")]
 pub struct PrivateKey {
-    pub p: ModInt,
-    pub q: ModInt,
-    pub g: ModInt,
-    pub x: ModInt,
+    pub x: Scalar,
+}
+
+/// The serialized form of a `PrivateKey`.
+#[derive(Clone, Serialize, Deserialize)]
+struct PrivateKeyRepr {
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    p: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    q: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    g: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    x: ModInt,
+}
+
+impl ::std::convert::From<PrivateKey> for PrivateKeyRepr {
+    fn from(private_key: PrivateKey) -> Self {
+        let group = private_key.group();
+
+        PrivateKeyRepr {
+            p: group.p.clone(),
+            q: group.q.clone(),
+            g: group.g.clone(),
+            x: private_key.x.to_mod_int(),
+        }
+    }
+}
+
+impl TryFrom<PrivateKeyRepr> for PrivateKey {
+    type Error = Error;
+
+    fn try_from(repr: PrivateKeyRepr) -> Result<Self, Error> {
+        let group = Arc::new(Group::new(repr.p.value, repr.q.value, repr.g.value));
+
+        Ok(PrivateKey {
+            x: Scalar::from_mod_int(&group, &repr.x)?,
+        })
+    }
 }
 
 impl PrivateKey {
+    /// Returns the parameters `(p, q, g)` of the group this key belongs to.
+    pub fn group(&self) -> &Arc<Group> {
+        self.x.group()
+    }
+
+    /// Returns the public key `h = g^x` belonging to this private key.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey {
+            h: GroupElement::generator(self.group()).pow_secret(&self.x),
+        }
+    }
+
+    /// Load a PrivateKey from its JSON representation at the given path.
+    ///
+    /// - path: The path of the private key file
+    ///
+    /// Returns `KeyError::Io` if the file cannot be read and `KeyError::Parse`
+    /// if its contents are not a valid private key.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, KeyError> {
+        read_json(path.as_ref())
+    }
+
+    /// Save this private key as JSON to the given path.
+    /// An existing file is overwritten.
+    ///
+    /// On Unix, the file is only readable and writable by its owner,
+    /// i.e. it has the permissions `0600`.
+    ///
+    /// - path: The path of the private key file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), KeyError> {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let file = options.open(path)?;
+
+        // the mode above is only applied to newly created files
+        #[cfg(unix)]
+        file.set_permissions(Permissions::from_mode(0o600))?;
+
+        write_json(self, file)
+    }
+
     /// Create a PrivateKey based its string representation at a specific path.
     ///
-    /// - `private_key_file_name`: The file name of the private key.
-    ///                            Must reside in the same directory as the binary is launched.
+    /// - `private_key_file_name`: The file name of the private key relative
+    ///   to the directory the binary is launched in.
+    ///
+    /// # Panics
     ///
+    /// Panics if the file cannot be read or does not contain a valid private key.
+    #[deprecated(note = "use `PrivateKey::load` instead")]
     pub fn new(private_key_file_name: &str) -> Self {
-        // Read the input file to string.
-        let mut file = File::open("./".to_owned() + private_key_file_name).unwrap();
-        let mut contents = String::new();
-        file.read_to_string(&mut contents).unwrap();
-
-        let private_key: PrivateKey = match serde_json::from_str(&contents) {
-            Ok(private_key_data) => {
-                private_key_data
-            },
-            Err(e) => {
-                panic!("Failed to transform file {:?} into PrivatKey: {:?}", file, e);
-            }
-        };
-
-        private_key
+        match PrivateKey::load(Path::new(".").join(private_key_file_name)) {
+            Ok(private_key) => private_key,
+            Err(e) => panic!("Failed to read PrivateKey from file {:?}: {}", private_key_file_name, e)
+        }
     }
 
     /// Write this private key to a file with the given path.
     ///
-    /// - `private_key_file_name`: The file name of the private key.
-    ///                           Must reside in the same directory as the binary is launched.
+    /// - `private_key_file_name`: The file name of the private key relative
+    ///   to the directory the binary is launched in.
+    ///
+    /// # Panics
     ///
+    /// Panics if the file cannot be written.
+    #[deprecated(note = "use `PrivateKey::save` instead")]
     pub fn to_file(&self, private_key_file_name: &str) {
-        // Read the input file to string.
-        let mut file = File::create("./".to_owned() + private_key_file_name).unwrap();
+        if let Err(e) = self.save(Path::new(".").join(private_key_file_name)) {
+            panic!("Failed to write PrivateKey to file {:?}: {}", private_key_file_name, e);
+        }
+    }
+}
 
-        let private_key_str = serde_json::to_string_pretty(&self).unwrap();
-        file.write(private_key_str.as_bytes()).unwrap();
+/// # Key Error
+///
+/// An error occurring while loading or saving a key.
+#[derive(Debug)]
+pub enum KeyError {
+    /// The key file could not be read or written.
+    Io(io::Error),
+    /// The key could not be parsed from or serialized to JSON.
+    Parse(serde_json::Error),
+}
+
+impl Display for KeyError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            KeyError::Io(ref e) => write!(f, "failed to access key file: {}", e),
+            KeyError::Parse(ref e) => write!(f, "failed to parse key: {}", e),
+        }
     }
 }
 
-pub fn encrypt(public_key: &PublicKey, message: ModInt) -> CipherText {
-    let random: ModInt = ModInt::gen_modint(public_key.q.clone());
+impl StdError for KeyError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            KeyError::Io(ref e) => Some(e),
+            KeyError::Parse(ref e) => Some(e),
+        }
+    }
+}
+
+impl ::std::convert::From<io::Error> for KeyError {
+    fn from(error: io::Error) -> Self {
+        KeyError::Io(error)
+    }
+}
+
+impl ::std::convert::From<serde_json::Error> for KeyError {
+    fn from(error: serde_json::Error) -> Self {
+        KeyError::Parse(error)
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, KeyError> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+
+    Ok(serde_json::from_str(&contents)?)
+}
+
+fn write_json<T: Serialize>(value: &T, mut file: File) -> Result<(), KeyError> {
+    let json = serde_json::to_string_pretty(value)?;
+    file.write_all(json.as_bytes())?;
+
+    Ok(())
+}
 
-    let g = public_key.g.clone();
-    let h = public_key.h.clone();
+/// A matching pair of public and private key.
+#[derive(Debug, Clone)]
+pub struct KeyPair {
+    pub public_key: PublicKey,
+    pub private_key: PrivateKey,
+}
 
-    let big_g = g.clone().pow(random.clone());
-    let big_h1= h.clone().pow(random.clone());
-    let big_h2 = g.clone().pow(message.clone());
+impl KeyPair {
+    /// Generate a new key pair for the given group parameters.
+    ///
+    /// The secret `x` is sampled uniformly from `Z_q \ {0}`, and the
+    /// public value is derived as `h = g^x mod p`.
+    ///
+    /// - group: The parameters `(p, q, g)` of the group to use
+    pub fn generate(group: &Group) -> Self {
+        let group = Arc::new(group.clone());
+
+        let mut x = Scalar::random(&group);
+        while x.is_zero() {
+            // x = 0 would result in h = 1, i.e. not encrypting at all
+            x = Scalar::random(&group);
+        }
 
-    let big_h = big_h1 * big_h2;
+        let private_key = PrivateKey { x };
 
-    CipherText {
-        big_g,
-        big_h,
-        random,
+        KeyPair {
+            public_key: private_key.public_key(),
+            private_key,
+        }
     }
 }
 
-pub fn decrypt(private_key: PrivateKey, cipher_text: CipherText) -> ModInt {
+/// Encrypt the given message using the given public key.
+///
+/// Returns the public cipher text along with the randomness used for encryption.
+/// The randomness is required for creating proofs about the cipher text and must be kept secret.
+///
+/// - public_key: The public key, or a `PrecomputedPublicKey` when encrypting many messages
+/// - message: The plain text `m`
+///
+/// Note, that the public key is trusted. Keys from untrusted sources
+/// must be checked using `PublicKey::validate` beforehand.
+///
+/// # Panics
+///
+/// Panics if the message belongs to another group than the public key.
+pub fn encrypt<K: FixedBasePow>(public_key: &K, message: Scalar) -> (CipherText, Randomness) {
+    let random = Scalar::random(public_key.public_key().group());
+
+    let big_g = public_key.g_pow(&random);
+    let big_h = public_key.h_pow(&random) * public_key.g_pow(&message);
+
+    (CipherText { big_g, big_h }, Randomness { random })
+}
+
+/// Decrypt the given cipher text of a plain text within `[0, max_plain_text]`
+/// using the baby-step giant-step algorithm (see `DiscreteLog`).
+///
+/// Returns `Error::PlainTextNotFound` if the plain text is larger than `max_plain_text`,
+/// e.g. if the cipher text is not a valid encryption under the corresponding public key.
+/// Prefer a `Decryptor` when decrypting many cipher texts, as it computes the table only once.
+///
+/// - private_key: The private key used to decrypt the cipher text
+/// - cipher_text: The cipher text to decrypt
+/// - max_plain_text: The largest plain text which can be recovered
+pub fn decrypt(private_key: PrivateKey, cipher_text: CipherText, max_plain_text: usize) -> Result<Scalar, Error> {
+    Decryptor::new(private_key, max_plain_text).decrypt(&cipher_text)
+}
 
-    let h: &ModInt = &cipher_text.big_h;
-    let g: &ModInt = &cipher_text.big_g;
-    let x: &ModInt = &private_key.x;
+/// # Decryptor
+///
+/// Decrypts cipher texts of plain texts within the range `[0, max_plain_text]`
+/// using the baby-step giant-step algorithm (see `DiscreteLog`).
+///
+/// The table of baby steps is computed once on creation and reused for
+/// all decryptions, so that each decryption requires at most
+/// `sqrt(max_plain_text + 1)` multiplications.
+#[derive(Clone, Debug)]
+pub struct Decryptor {
+    private_key: PrivateKey,
+    discrete_log: DiscreteLog,
+}
 
-    let g_to_m: ModInt = h.clone() / (g.clone().pow(x.clone()));
+impl Decryptor {
+    /// Create a new Decryptor by precomputing the table of baby steps.
+    ///
+    /// - private_key: The private key used to decrypt the cipher texts
+    /// - max_plain_text: The largest plain text which can be recovered
+    pub fn new(private_key: PrivateKey, max_plain_text: usize) -> Self {
+        let discrete_log = DiscreteLog::new(private_key.group(), max_plain_text);
+
+        Decryptor {
+            private_key,
+            discrete_log,
+        }
+    }
 
-    let mut i: ModInt = ModInt::zero();
-    // find cleartext value so that it matches target
-    loop {
-        let target: ModInt = ModInt::from_value_modulus(private_key.g.value.clone(), g_to_m.modulus.clone()).pow(i.clone());
+    /// Decrypt the given cipher text.
+    ///
+    /// Returns `Error::PlainTextNotFound` if the plain text is larger than
+    /// the maximum plain text of this decryptor and `Error::GroupMismatch`
+    /// if the cipher text belongs to another group than the private key.
+    pub fn decrypt(&self, cipher_text: &CipherText) -> Result<Scalar, Error> {
+        let shared_secret = cipher_text.big_g.clone().checked_pow_secret(&self.private_key.x)?;
+        let g_to_m = cipher_text.big_h.clone().checked_div(shared_secret)?;
+
+        self.discrete_log(&g_to_m)
+    }
 
-        if target.eq(&g_to_m) {
-            return i;
+    /// Find `m` within `[0, max_plain_text]` such that `g^m = g_to_m`.
+    ///
+    /// Returns `Error::PlainTextNotFound` if no such `m` exists.
+    pub fn discrete_log(&self, g_to_m: &GroupElement) -> Result<Scalar, Error> {
+        self.discrete_log.solve(g_to_m)
+    }
+}
+
+/// # Discrete Logarithm
+///
+/// Recovers `m` within the range `[0, max_plain_text]` from `g^m mod p`
+/// using the baby-step giant-step algorithm. This is the last step of
+/// decrypting a cipher text, once `g^m` has been obtained.
+#[derive(Clone, Debug)]
+pub struct DiscreteLog {
+    group: Arc<Group>,
+    max_plain_text: usize,
+    /// The amount of baby steps, i.e. `ceil(sqrt(max_plain_text + 1))`.
+    step_size: usize,
+    /// Maps `g^j mod p` to `j` for all `0 <= j < step_size`.
+    baby_steps: HashMap<BigInt, usize>,
+    /// The giant step `g^-step_size mod p`.
+    giant_step: BigInt,
+}
+
+impl DiscreteLog {
+    /// Create a new DiscreteLog by precomputing the table of baby steps.
+    ///
+    /// - group: The group whose generator `g` the logarithm is taken to
+    /// - max_plain_text: The largest plain text which can be recovered
+    pub fn new(group: &Arc<Group>, max_plain_text: usize) -> Self {
+        let p: &BigInt = &group.p.value;
+        let generator = GroupElement::generator(group);
+        let g: &BigInt = generator.value();
+
+        let amount = max_plain_text.saturating_add(1);
+        let mut step_size = amount.sqrt();
+        if step_size * step_size < amount {
+            step_size += 1;
         }
 
-        i = i + ModInt::one();
+        let mut baby_steps: HashMap<BigInt, usize> = HashMap::with_capacity(step_size);
+        let mut baby_step: BigInt = BigInt::one();
+        for j in 0..step_size {
+            // keep the smallest exponent in case the order of g is smaller than the step size
+            baby_steps.entry(baby_step.clone()).or_insert(j);
+            baby_step = (baby_step * g) % p;
+        }
+
+        // baby_step is now equal to g^step_size
+        let giant_step: BigInt = mod_inverse::mod_inverse(baby_step, p.clone()).unwrap_or_else(BigInt::zero);
+
+        DiscreteLog {
+            group: group.clone(),
+            max_plain_text,
+            step_size,
+            baby_steps,
+            giant_step,
+        }
+    }
+
+    /// Find `m` within `[0, max_plain_text]` such that `g^m = g_to_m`.
+    ///
+    /// Returns `Error::PlainTextNotFound` if no such `m` exists
+    /// and `Error::GroupMismatch` if the element belongs to another group.
+    pub fn solve(&self, g_to_m: &GroupElement) -> Result<Scalar, Error> {
+        if !same_group(&self.group, g_to_m.group()) {
+            return Err(Error::GroupMismatch);
+        }
+
+        let p = &self.group.p.value;
+        let giant_steps = self.max_plain_text / self.step_size + 1;
+        let mut gamma: BigInt = g_to_m.value().clone();
+
+        for i in 0..giant_steps {
+            if let Some(j) = self.baby_steps.get(&gamma) {
+                let m = i * self.step_size + j;
+
+                if m <= self.max_plain_text {
+                    return Ok(Scalar::new(&self.group, BigInt::from(m)));
+                }
+
+                break;
+            }
+
+            gamma = (gamma * &self.giant_step) % p;
+        }
+
+        Err(Error::PlainTextNotFound)
     }
 }
 
@@ -145,77 +562,344 @@ pub fn decrypt(private_key: PrivateKey, cipher_text: CipherText) -> ModInt {
 #[cfg(test)]
 mod encryption_test {
 
+    use ::arithmetic::group::{Group, ValidationError};
+    use ::arithmetic::group_element::GroupElement;
+    use ::arithmetic::scalar::Scalar;
+    use ::arithmetic::standard_groups::MODP_2048;
+    use ::el_gamal::encryption::KeyError;
+    use ::el_gamal::encryption::KeyPair;
     use ::el_gamal::encryption::PrivateKey;
     use ::el_gamal::encryption::PublicKey;
-    use ::el_gamal::encryption::{encrypt, decrypt};
+    use ::el_gamal::encryption::PublicKeyRepr;
+    use ::el_gamal::encryption::{encrypt, decrypt, Decryptor};
+    use ::error::Error;
     use ::arithmetic::mod_int::ModInt;
     use arithmetic::mod_int::From;
     use ::num::bigint::BigInt;
     use ::num::Zero;
     use ::num::One;
+    use ::num::traits::Pow;
+    use std::env;
+    use std::fs;
+    use std::io;
+    use std::path::PathBuf;
+    use std::process;
+    use std::sync::Arc;
 
     #[test]
     fn encrypt_decrypt() {
-        let message: ModInt = ModInt::one();
+        let group = Arc::new(Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4)));
+        let message = Scalar::new(&group, BigInt::one());
 
         let priv_key: PrivateKey = PrivateKey {
-            p: ModInt::from_value_modulus(BigInt::from(5), BigInt::zero()),
-            q: ModInt::from_value_modulus(BigInt::from(2), BigInt::zero()),
-            g: ModInt::from_value_modulus(BigInt::from(2), BigInt::zero()),
-            x: ModInt::from_value_modulus(BigInt::from(5), BigInt::zero())
+            x: Scalar::new(&group, BigInt::from(1234567))
         };
 
         //h := (g^x) mod p
-        //2 := 2^5 mod 5
-        let pub_key: PublicKey = PublicKey {
-            p: ModInt::from_value_modulus(BigInt::from(5), BigInt::zero()),
-            q: ModInt::from_value_modulus(BigInt::from(2), BigInt::zero()),
-            h: ModInt::from_value_modulus(BigInt::from(32), BigInt::from(5)),
-            g: ModInt::from_value_modulus(BigInt::from(2), BigInt::from(5))
-        };
+        //1680623351278 := 4^1234567 mod 2199023255867
+        let pub_key: PublicKey = PublicKey::from_group(&group, BigInt::from(1680623351278i64)).unwrap();
 
-        let c = encrypt(&pub_key, message);
+        let (c, _) = encrypt(&pub_key, message);
 
-        let result_message = decrypt(priv_key, c);
+        let result_message = decrypt(priv_key, c, 100).unwrap();
 
-        assert_eq!(ModInt::one().value, result_message.value);
+        assert_eq!(BigInt::one(), *result_message.value());
+    }
+
+    #[test]
+    fn decryptor() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let message = |value: u64| Scalar::new(key_pair.public_key.group(), BigInt::from(value));
+
+        let decryptor = Decryptor::new(key_pair.private_key.clone(), 1_000_000);
+
+        for value in [0, 1, 2, 999, 1000, 1001, 123_456, 999_999, 1_000_000].iter() {
+            let (c, _) = encrypt(&key_pair.public_key, message(*value));
+
+            let result_message = decryptor.decrypt(&c).unwrap();
+
+            assert_eq!(message(*value), result_message);
+        }
+
+        let (c, _) = encrypt(&key_pair.public_key, message(1_000_001));
+        match decryptor.decrypt(&c) {
+            Err(Error::PlainTextNotFound) => (),
+            other => panic!("expected no plain text to be found, got {:?}", other)
+        }
+
+        let (c, _) = encrypt(&key_pair.public_key, message(7));
+        assert_eq!(decrypt(key_pair.private_key.clone(), c.clone(), 100).unwrap(), decryptor.decrypt(&c).unwrap());
+    }
+
+    #[test]
+    fn decryptor_small_bounds() {
+        let group = Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let message = |value: u64| Scalar::new(key_pair.public_key.group(), BigInt::from(value));
+
+        let decryptor = Decryptor::new(key_pair.private_key.clone(), 0);
+        let (c, _) = encrypt(&key_pair.public_key, message(0));
+        assert_eq!(BigInt::zero(), *decryptor.decrypt(&c).unwrap().value());
+        let (c, _) = encrypt(&key_pair.public_key, message(1));
+        assert!(decryptor.decrypt(&c).is_err());
+
+        // the bound exceeds the order of g
+        let decryptor = Decryptor::new(key_pair.private_key.clone(), 100);
+        for value in 0..11 {
+            let (c, _) = encrypt(&key_pair.public_key, message(value));
+
+            assert_eq!(BigInt::from(value), *decryptor.decrypt(&c).unwrap().value());
+        }
+    }
+
+    #[test]
+    fn decrypt_invalid_cipher_text() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let other_key_pair = KeyPair::generate(&group);
+
+        // the cipher text was encrypted under another key of the same group
+        let (c, _) = encrypt(&other_key_pair.public_key, Scalar::new(other_key_pair.public_key.group(), BigInt::one()));
+        match decrypt(key_pair.private_key.clone(), c, 100) {
+            Err(Error::PlainTextNotFound) => (),
+            other => panic!("expected no plain text to be found, got {:?}", other)
+        }
+
+        // the cipher text belongs to another group
+        let small_key_pair = KeyPair::generate(&Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        let (c, _) = encrypt(&small_key_pair.public_key, Scalar::new(small_key_pair.public_key.group(), BigInt::one()));
+        match decrypt(key_pair.private_key, c, 100) {
+            Err(Error::GroupMismatch) => (),
+            other => panic!("expected a group mismatch, got {:?}", other)
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn encrypt_message_of_other_group() {
+        let key_pair = KeyPair::generate(&Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        let other_group = Arc::new(Group::new(BigInt::from(47), BigInt::from(23), BigInt::from(4)));
+
+        encrypt(&key_pair.public_key, Scalar::new(&other_group, BigInt::one()));
+    }
+
+    #[test]
+    fn decrypt_out_of_range() {
+        // the search is bounded by the maximum plain text rather than the order q
+        let key_pair = KeyPair::generate(&MODP_2048.group());
+
+        let (c, _) = encrypt(&key_pair.public_key, Scalar::new(key_pair.public_key.group(), BigInt::from(1001)));
+        match decrypt(key_pair.private_key.clone(), c.clone(), 1000) {
+            Err(Error::PlainTextNotFound) => (),
+            other => panic!("expected no plain text to be found, got {:?}", other)
+        }
+
+        assert_eq!(BigInt::from(1001), *decrypt(key_pair.private_key, c, 1001).unwrap().value());
+    }
+
+    #[test]
+    fn generate_key_pair() {
+        let group = Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4));
+
+        let key_pair = KeyPair::generate(&group);
+        let pub_key = key_pair.public_key;
+        let priv_key = key_pair.private_key;
+
+        assert_eq!(group, **pub_key.group());
+        assert_eq!(group, **priv_key.group());
+        assert!(!priv_key.x.is_zero());
+        assert_eq!(pub_key.g().pow(&priv_key.x), pub_key.h);
+        assert_eq!(pub_key.h, priv_key.public_key().h);
+
+        let message = Scalar::new(pub_key.group(), BigInt::from(3));
+        let (c, _) = encrypt(&pub_key, message);
+        let result_message = decrypt(priv_key, c, 100).unwrap();
+
+        assert_eq!(BigInt::from(3), *result_message.value());
+    }
+
+    #[test]
+    fn public_key_from_standard_group() {
+        let group = Arc::new(MODP_2048.group());
+        let h = GroupElement::generator(&group).pow(&Scalar::new(&group, BigInt::from(1234567)));
+
+        let pub_key = PublicKey::from_group(&group, h.value().clone()).unwrap();
+
+        assert_eq!(h, pub_key.h);
+        assert_eq!(GroupElement::generator(&group), pub_key.g());
+        assert_eq!(group, *pub_key.group());
+    }
+
+    #[test]
+    fn validate_public_key() {
+        let group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        let pub_key = KeyPair::generate(&group).public_key;
+        assert_eq!(Ok(()), pub_key.validate());
+
+        // 5 is not a quadratic residue modulo 23
+        match PublicKey::from_group(&group, BigInt::from(5)) {
+            Err(Error::ElementNotInGroup) => (),
+            other => panic!("expected an element outside of the group, got {:?}", other)
+        }
+
+        let pub_key = PublicKey::from_group(&group, BigInt::from(1)).unwrap();
+        assert_eq!(Err(ValidationError::PublicValueIsOne), pub_key.validate());
+
+        let invalid_group = Arc::new(Group::new(BigInt::from(23), BigInt::from(12), BigInt::from(4)));
+        let pub_key = PublicKey::from_group(&invalid_group, BigInt::from(1)).unwrap();
+        assert_eq!(Err(ValidationError::OrderNotPrime), pub_key.validate());
+    }
+
+    fn temp_path(file_name: &str) -> PathBuf {
+        env::temp_dir().join(format!("crypto_rs_{}_{}", process::id(), file_name))
+    }
+
+    fn small_group() -> Arc<Group> {
+        Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)))
     }
 
     #[test]
     fn write_read_public_key() {
-        let pub_key: PublicKey = PublicKey {
-            p: ModInt::from_value_modulus(BigInt::from(5), BigInt::zero()),
-            q: ModInt::from_value_modulus(BigInt::from(2), BigInt::zero()),
-            h: ModInt::from_value_modulus(BigInt::from(32), BigInt::from(5)),
-            g: ModInt::from_value_modulus(BigInt::from(2), BigInt::from(5))
-        };
+        let pub_key: PublicKey = PublicKey::from_group(&small_group(), BigInt::from(13)).unwrap();
 
-        pub_key.to_file("public_key.json");
+        let path = temp_path("public_key.json");
+        pub_key.save(&path).unwrap();
 
-        let read_pub_key = PublicKey::new("public_key.json");
+        let read_pub_key = PublicKey::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
 
-        assert_eq!(read_pub_key.p, pub_key.p);
-        assert_eq!(read_pub_key.q, pub_key.q);
+        assert_eq!(read_pub_key.group(), pub_key.group());
         assert_eq!(read_pub_key.h, pub_key.h);
-        assert_eq!(read_pub_key.g, pub_key.g);
     }
 
     #[test]
     fn write_read_private_key() {
         let priv_key: PrivateKey = PrivateKey {
-            p: ModInt::from_value_modulus(BigInt::from(5), BigInt::zero()),
-            q: ModInt::from_value_modulus(BigInt::from(2), BigInt::zero()),
-            g: ModInt::from_value_modulus(BigInt::from(2), BigInt::zero()),
-            x: ModInt::from_value_modulus(BigInt::from(5), BigInt::zero())
+            x: Scalar::new(&small_group(), BigInt::from(5))
         };
 
-        priv_key.to_file("private_key.json");
+        let path = temp_path("private_key.json");
+        priv_key.save(&path).unwrap();
 
-        let read_priv_key = PrivateKey::new("private_key.json");
+        let read_priv_key = PrivateKey::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
 
-        assert_eq!(read_priv_key.p, priv_key.p);
-        assert_eq!(read_priv_key.q, priv_key.q);
-        assert_eq!(read_priv_key.g, priv_key.g);
+        assert_eq!(read_priv_key.group(), priv_key.group());
         assert_eq!(read_priv_key.x, priv_key.x);
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[cfg(unix)]
+    fn private_key_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let priv_key: PrivateKey = PrivateKey {
+            x: Scalar::new(&small_group(), BigInt::from(5))
+        };
+
+        let path = temp_path("private_key_permissions.json");
+        // a pre-existing file must get restricted as well
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        priv_key.save(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(0o600, mode & 0o777);
+    }
+
+    #[test]
+    fn load_missing_key() {
+        let path = temp_path("missing_key.json");
+
+        match PublicKey::load(&path) {
+            Err(KeyError::Io(e)) => assert_eq!(io::ErrorKind::NotFound, e.kind()),
+            other => panic!("expected an I/O error, got {:?}", other)
+        }
+
+        match PrivateKey::load(&path) {
+            Err(KeyError::Io(e)) => assert_eq!(io::ErrorKind::NotFound, e.kind()),
+            other => panic!("expected an I/O error, got {:?}", other)
+        }
+    }
+
+    #[cfg(feature = "serde-hex")]
+    #[test]
+    fn serialize_hex() {
+        use ::arithmetic::in_group;
+        use ::cai::uciv::ImageSet;
+        use ::el_gamal::ciphertext::CipherText;
+        use ::el_gamal::threshold::Commitments;
+
+        let group = small_group();
+        let pub_key: PublicKey = PublicKey::from_group(&group, BigInt::from(13)).unwrap();
+
+        let json = ::serde_json::to_string(&pub_key).unwrap();
+        assert_eq!(r#"{"p":["17","0"],"q":["b","0"],"h":["d","17"],"g":["4","17"]}"#, json);
+
+        let read_pub_key: PublicKey = ::serde_json::from_str(&json).unwrap();
+        assert_eq!(pub_key.h, read_pub_key.h);
+
+        let (cipher_text, _) = encrypt(&pub_key, Scalar::new(&group, BigInt::from(3)));
+        let json = ::serde_json::to_string(&cipher_text).unwrap();
+        assert_eq!(cipher_text, in_group::from_str::<CipherText>(&group, &json).unwrap());
+
+        let json = ::serde_json::to_string(&group).unwrap();
+        assert_eq!(r#"{"p":["17","0"],"q":["b","0"],"g":["4","17"]}"#, json);
+        assert_eq!(*group, ::serde_json::from_str(&json).unwrap());
+
+        let commitments = Commitments { dealer: 1, values: vec![pub_key.h.clone()] };
+        let json = ::serde_json::to_string(&commitments).unwrap();
+        assert_eq!(r#"{"dealer":1,"values":[["d","17"]]}"#, json);
+        assert_eq!(commitments, in_group::from_str(&group, &json).unwrap());
+
+        let image_set = ImageSet { images: vec![pub_key.h.clone()] };
+        let json = ::serde_json::to_string(&image_set).unwrap();
+        assert_eq!(r#"{"images":[["d","17"]]}"#, json);
+        assert_eq!(image_set.images, in_group::from_str::<ImageSet>(&group, &json).unwrap().images);
+    }
+
+    #[test]
+    fn load_invalid_key() {
+        let path = temp_path("invalid_key.json");
+        fs::write(&path, "{\"p\": 5}").unwrap();
+
+        let pub_key_result = PublicKey::load(&path);
+        let priv_key_result = PrivateKey::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        match pub_key_result {
+            Err(KeyError::Parse(_)) => (),
+            other => panic!("expected a parse error, got {:?}", other)
+        }
+
+        match priv_key_result {
+            Err(KeyError::Parse(_)) => (),
+            other => panic!("expected a parse error, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn load_key_outside_group() {
+        // 5 is not a quadratic residue modulo 23
+        let repr = PublicKeyRepr {
+            p: ModInt::from_value(BigInt::from(23)),
+            q: ModInt::from_value(BigInt::from(11)),
+            h: ModInt::from_value_modulus(BigInt::from(5), BigInt::from(23)),
+            g: ModInt::from_value_modulus(BigInt::from(4), BigInt::from(23)),
+        };
+
+        let path = temp_path("key_outside_group.json");
+        fs::write(&path, ::serde_json::to_string(&repr).unwrap()).unwrap();
+
+        let pub_key_result = PublicKey::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        match pub_key_result {
+            Err(KeyError::Parse(e)) => assert!(e.to_string().contains("subgroup"), "unexpected error: {}", e),
+            other => panic!("expected a parse error, got {:?}", other)
+        }
+    }
+}
@@ -1,247 +1,860 @@
+use ::arithmetic::encoding::{self, ByteReader};
+use ::arithmetic::group::Group;
+use ::arithmetic::group_element::{same_group, GroupElement};
+use ::arithmetic::in_group::{self, DeserializeInGroup};
 use ::arithmetic::mod_int::ModInt;
-use ::arithmetic::mod_int::RandModInt;
-use ::el_gamal::ciphertext::CipherText;
+use ::arithmetic::scalar::Scalar;
+use ::el_gamal::ciphertext::{CipherText, Randomness};
 use ::el_gamal::encryption::{PublicKey};
-use arithmetic::mod_int::From;
-use num::bigint::BigInt;
-use num::{Zero};
-use num::traits::pow::Pow;
-use std::ops::Div;
+use ::el_gamal::precomputed::FixedBasePow;
+use num::bigint::{BigInt, RandBigInt};
+use num::traits::Pow;
+use num::{Integer, One, Zero};
+use rand;
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer};
 use std::ops::Mul;
 use std::ops::Neg;
 use std::ops::Sub;
+use std::sync::Arc;
 use std::vec::Vec;
 use std::ops::Add;
-use ::el_gamal::serializer::Serializer;
-
-#[derive(Eq, PartialEq, Serialize, Deserialize, Hash, Clone, Debug)]
+use ::el_gamal::transcript::Transcript;
+use ::error::Error;
+use ::parallel;
+
+/// The amount of bits of the random exponents combining the
+/// verification equations in `MembershipProof::verify_batch`.
+const BATCH_EXPONENT_BITS: usize = 64;
+
+/// Proofs are serialized along with the parameters `p` and `q` of their group.
+/// Deserialization (see `DeserializeInGroup`) fails if these do not match the given group.
+#[derive(Eq, PartialEq, Serialize, Hash, Clone, Debug)]
+#[serde(into = "MembershipProofRepr")]
 pub struct MembershipProof {
+    s_responses: Vec<Scalar>,
+    c_responses: Vec<Scalar>,
+    y_responses: Vec<GroupElement>,
+    z_responses: Vec<GroupElement>,
+
+    group: Arc<Group>,
+}
+
+/// The serialized form of a `MembershipProof`.
+#[derive(Clone, Serialize, Deserialize)]
+struct MembershipProofRepr {
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex::vec"))]
     s_responses: Vec<ModInt>,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex::vec"))]
     c_responses: Vec<ModInt>,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex::vec"))]
     y_responses: Vec<ModInt>,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex::vec"))]
     z_responses: Vec<ModInt>,
 
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
     p: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
     q: ModInt,
 }
 
+impl ::std::convert::From<MembershipProof> for MembershipProofRepr {
+    fn from(proof: MembershipProof) -> Self {
+        MembershipProofRepr {
+            s_responses: proof.s_responses.iter().map(Scalar::to_mod_int).collect(),
+            c_responses: proof.c_responses.iter().map(Scalar::to_mod_int).collect(),
+            y_responses: proof.y_responses.iter().map(GroupElement::to_mod_int).collect(),
+            z_responses: proof.z_responses.iter().map(GroupElement::to_mod_int).collect(),
+            p: proof.group.p.clone(),
+            q: proof.group.q.clone(),
+        }
+    }
+}
+
+impl DeserializeInGroup for MembershipProof {
+    fn deserialize_in<'de, D: Deserializer<'de>>(group: &Arc<Group>, deserializer: D) -> Result<Self, D::Error> {
+        let repr = MembershipProofRepr::deserialize(deserializer)?;
+
+        if repr.p.value != group.p.value || repr.q.value != group.q.value {
+            return Err(D::Error::custom(Error::GroupMismatch));
+        }
+
+        Ok(MembershipProof {
+            s_responses: in_group::scalars(group, &repr.s_responses)?,
+            c_responses: in_group::scalars(group, &repr.c_responses)?,
+            y_responses: in_group::elements(group, &repr.y_responses)?,
+            z_responses: in_group::elements(group, &repr.z_responses)?,
+            group: group.clone(),
+        })
+    }
+}
+
+/// Returns true if the cipher text and all given scalars belong to the given group.
+fn in_same_group(group: &Arc<Group>, cipher_text: &CipherText, scalars: &[Scalar]) -> bool {
+    same_group(group, cipher_text.big_g.group())
+        && same_group(group, cipher_text.big_h.group())
+        && scalars.iter().all(|scalar| same_group(group, scalar.group()))
+}
+
 impl MembershipProof {
-    pub fn new(public_key: PublicKey, plain_text: ModInt, cipher_text: CipherText, domains: Vec<ModInt>) -> MembershipProof {
-        let mut y_response: Vec<ModInt> = vec![];
-        let mut z_response: Vec<ModInt> = vec![];
-        let mut s_response: Vec<ModInt> = vec![];
-        let mut c_response: Vec<ModInt> = vec![];
-
-        let g = ModInt {
-            value: public_key.g.value.clone(),
-            modulus: public_key.p.value.clone(),
-        };
+    /// Create a new proof that the given cipher text encrypts one of the values of the domain.
+    ///
+    /// - key: The public key used to encrypt the plain text, or a `PrecomputedPublicKey` of it
+    /// - plain_text: The encrypted plain-text message
+    /// - cipher_text: The cipher text for which to create the proof
+    /// - randomness: The randomness used to encrypt the plain text
+    /// - domains: The set of values the plain text must be an element of
+    /// - context: The context the proof is bound to, e.g. the IDs of the election, the voter and the question
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plain text is not an element of the domain or if any
+    /// of the values belongs to another group than the public key.
+    /// Use `MembershipProof::try_new` to handle these cases.
+    pub fn new<K: FixedBasePow>(key: K, plain_text: Scalar, cipher_text: CipherText, randomness: Randomness, domains: Vec<Scalar>, context: &[u8]) -> MembershipProof {
+        match MembershipProof::try_new(key, plain_text, cipher_text, randomness, domains, context) {
+            Ok(proof) => proof,
+            Err(e) => panic!("{}", e)
+        }
+    }
 
-        let h = ModInt {
-            value: public_key.h.value.clone(),
-            modulus: public_key.p.value.clone(),
+    /// Create a new proof as described in `MembershipProof::new`.
+    ///
+    /// Returns `Error::PlainTextNotInDomain` if the plain text is not an element of the domain
+    /// and `Error::GroupMismatch` if the plain text, the cipher text, the randomness or the domain
+    /// belong to another group than the public key.
+    pub fn try_new<K: FixedBasePow>(key: K, plain_text: Scalar, cipher_text: CipherText, randomness: Randomness, domains: Vec<Scalar>, context: &[u8]) -> Result<MembershipProof, Error> {
+        let public_key = key.public_key();
+        let group = public_key.group();
+
+        let secrets = [plain_text.clone(), randomness.random.clone()];
+        if !in_same_group(group, &cipher_text, &domains) || !in_same_group(group, &cipher_text, &secrets) {
+            return Err(Error::GroupMismatch);
+        }
+
+        let message_idx = match domains.iter().position(|domain_val| *domain_val == plain_text) {
+            Some(idx) => idx,
+            None => return Err(Error::PlainTextNotInDomain)
         };
 
-        let t = ModInt::gen_modint(public_key.q.clone());
+        let mut y_response: Vec<GroupElement> = vec![];
+        let mut z_response: Vec<GroupElement> = vec![];
+        let mut s_response: Vec<Scalar> = vec![];
+        let mut c_response: Vec<Scalar> = vec![];
 
-        let mut string_to_hash = String::new();
-        string_to_hash += &g.to_string();
-        string_to_hash += &h.to_string();
-        string_to_hash += &cipher_text.big_g.to_string();
-        string_to_hash += &cipher_text.big_h.to_string();
+        let t = Scalar::random(group);
 
-        let mut message_idx = 0;
-        for i in 0..domains.len() {
-            let mut y: ModInt;
-            let mut z: ModInt;
+        let mut transcript = MembershipProof::transcript(public_key, &cipher_text, &domains, context);
 
-            let domain_val: ModInt = (*domains.get(i).unwrap()).clone();
+        for (i, domain_val) in domains.iter().enumerate() {
+            let y: GroupElement;
+            let z: GroupElement;
 
-            if domain_val.eq(&plain_text) {
+            if i == message_idx {
                 // we need to add fake values
-                s_response.push(ModInt::zero());
-                c_response.push(ModInt::zero());
-
-                y = g.clone().pow(t.clone());
-                z = h.clone().pow(t.clone());
+                s_response.push(Scalar::zero(group));
+                c_response.push(Scalar::zero(group));
 
-                message_idx = i;
+                y = key.g_pow(&t);
+                z = key.h_pow(&t);
             } else {
                 // add fake commitments as well as the corresponding response
                 // for a value which is not the plaintext message
-                let s = ModInt::gen_modint(public_key.q.clone());
-                let c = ModInt::gen_modint(public_key.q.clone());
+                let s = Scalar::random(group);
+                let c = Scalar::random(group);
 
                 s_response.push(s.clone());
                 c_response.push(c.clone());
 
                 let neg_c = c.neg();
-                let g_pow = g.clone().pow(domain_val.clone());
+                let g_pow = key.g_pow(domain_val);
 
-                y = g.clone().pow(s.clone()).mul(cipher_text.big_g.clone().pow(neg_c.clone()));
-                z = h.clone().pow(s.clone()).mul(cipher_text.big_h.clone().div(g_pow).pow(neg_c.clone()));
+                y = key.g_pow(&s).mul(cipher_text.big_g.clone().pow(&neg_c));
+                z = key.h_pow(&s).mul((cipher_text.big_h.clone() / g_pow).pow(&neg_c));
             }
 
-            y_response.push(y.clone());
-            z_response.push(z.clone());
+            transcript.append_element(b"y", &y);
+            transcript.append_element(b"z", &z);
 
-            string_to_hash += &y.to_string();
-            string_to_hash += &z.to_string();
+            y_response.push(y);
+            z_response.push(z);
         }
 
-        let c_hash = Serializer::string_to_sha512(string_to_hash);
-        let mut c_0 = ModInt::from_hex_string(c_hash, public_key.q.value.clone());
+        let mut c_0 = transcript.challenge(b"c", group);
 
         for fake_c in c_response.clone() {
             c_0 = c_0.sub(fake_c);
         }
 
-        s_response[message_idx] = c_0.clone().mul(cipher_text.random.clone()).add(t.clone());
+        s_response[message_idx] = c_0.clone().mul(randomness.random).add(t);
         c_response[message_idx] = c_0;
 
-        MembershipProof {
+        Ok(MembershipProof {
             s_responses: s_response,
             c_responses: c_response,
             y_responses: y_response,
             z_responses: z_response,
-            p: public_key.p,
-            q: public_key.q,
-        }
+            group: group.clone(),
+        })
+    }
+
+    /// Verify this proof for validity.
+    ///
+    /// - public_key: The public key used to encrypt the plain text
+    /// - cipher_text: The cipher text for which the proof was created
+    /// - domain: The set of values the plain text must be an element of
+    /// - context: The context the proof was created for
+    ///
+    /// Note, that the public key is trusted. Keys from untrusted sources
+    /// must be checked using `PublicKey::validate` beforehand.
+    ///
+    /// Returns false for malformed proofs, e.g. if the amount of responses
+    /// does not match the size of the domain.
+    pub fn verify(&self, public_key: PublicKey, cipher_text: CipherText, domain: Vec<Scalar>, context: &[u8]) -> bool {
+        self.try_verify(public_key, cipher_text, domain, context).unwrap_or(false)
     }
 
-    pub fn verify(&self, public_key: PublicKey, cipher_text: CipherText, domain: Vec<ModInt>) -> bool {
-        if domain.len() < self.c_responses.len() || domain.len() < self.s_responses.len() {
-            // The domain of the message is bigger than specified.
-            // Therefore, the proof that the message is within the given domain is invalid.
-            panic!("Domain has not the same length as the values of the proof.")
+    /// Verify the proofs of many ballots as described in `MembershipProof::verify`.
+    ///
+    /// If the `parallel` feature is enabled, the ballots are distributed over all
+    /// available cores. Otherwise, they are verified one after another.
+    ///
+    /// - public_key: The public key used to encrypt the plain texts
+    /// - ballots: The cipher texts along with their proofs and the contexts the proofs were created for
+    /// - domain: The set of values the plain texts must be an element of
+    ///
+    /// Returns whether each proof is valid, in the order of the ballots.
+    pub fn par_verify(public_key: &PublicKey, ballots: &[(CipherText, MembershipProof, &[u8])], domain: &[Scalar]) -> Vec<bool> {
+        parallel::map(ballots, parallel::threads(), |&(ref cipher_text, ref proof, context)| {
+            proof.verify(public_key.clone(), cipher_text.clone(), domain.to_vec(), context)
+        })
+    }
+
+    /// Verify this proof as described in `MembershipProof::verify`, returning
+    /// an error if the proof or its inputs are malformed.
+    ///
+    /// Returns `Error::LengthMismatch` if the amount of responses does not match
+    /// the size of the domain and `Error::GroupMismatch` if the proof, the cipher text
+    /// or the domain belong to another group than the public key.
+    pub fn try_verify(&self, public_key: PublicKey, cipher_text: CipherText, domain: Vec<Scalar>, context: &[u8]) -> Result<bool, Error> {
+        for responses in [self.s_responses.len(), self.c_responses.len(), self.y_responses.len(), self.z_responses.len()].iter() {
+            if *responses != domain.len() {
+                // The domain of the message is not the one the proof was created for.
+                // Therefore, the proof that the message is within the given domain is invalid.
+                return Err(Error::LengthMismatch { expected: domain.len(), actual: *responses });
+            }
         }
 
-        let g = ModInt {
-            value: public_key.g.value.clone(),
-            modulus: public_key.p.value.clone(),
-        };
+        let group = public_key.group();
+        if !same_group(group, &self.group) || !in_same_group(group, &cipher_text, &domain) {
+            return Err(Error::GroupMismatch);
+        }
 
-        let h = ModInt {
-            value: public_key.h.value.clone(),
-            modulus: public_key.p.value.clone(),
-        };
+        let g = public_key.g();
+        let h = public_key.h.clone();
 
-        let mut c_choices = ModInt {
-            value: BigInt::zero(),
-            modulus: public_key.q.value.clone()
-        };
+        let mut c_choices = Scalar::zero(group);
 
-        let mut string_to_hash = String::new();
-        string_to_hash += &g.to_string();
-        string_to_hash += &h.to_string();
-        string_to_hash += &cipher_text.big_g.to_string();
-        string_to_hash += &cipher_text.big_h.to_string();
+        let mut transcript = MembershipProof::transcript(&public_key, &cipher_text, &domain, context);
 
-        for i in 0..self.c_responses.len() {
-            let domain_val = domain.get(i).unwrap();
-            let g_pow = g.clone().pow(domain_val.clone());
+        for (i, domain_val) in domain.iter().enumerate() {
+            let g_pow = g.clone().checked_pow(domain_val)?;
 
-            let s: ModInt = (*self.s_responses.get(i).unwrap()).clone();
-            let c: ModInt = (*self.c_responses.get(i).unwrap()).clone();
+            let s: Scalar = self.s_responses[i].clone();
+            let c: Scalar = self.c_responses[i].clone();
             let neg_c = c.clone().neg();
 
+            c_choices = c_choices.add(c);
+
+            // y = g^s * G^(-c) and z = h^s * (H / g^d)^(-c)
+            let y = GroupElement::multiexp(group, &[g.clone(), cipher_text.big_g.clone()], &[s.clone(), neg_c.clone()])?;
+            let z = GroupElement::multiexp(group, &[h.clone(), cipher_text.big_h.clone().checked_div(g_pow)?], &[s, neg_c])?;
+
+            transcript.append_element(b"y", &y);
+            transcript.append_element(b"z", &z);
+        }
+
+        let new_c = transcript.challenge(b"c", group);
+
+        c_choices.checked_eq(&new_c)
+    }
+
+    /// Verify a batch of proofs, which were created for the same public key and domain,
+    /// e.g. all ballots of a question when tallying.
+    ///
+    /// Instead of verifying each proof separately, the verification equations of all
+    /// proofs are raised to random exponents of `BATCH_EXPONENT_BITS` bits and combined
+    /// into a single multi-exponentiation (see `arithmetic::multiexp`). A batch containing
+    /// an invalid proof passes this check with a probability of at most `2^-BATCH_EXPONENT_BITS`.
+    /// If the check fails, the batch is split in halves until the invalid proofs are found.
+    ///
+    /// Combining the equations is only sound within the subgroup of prime order `q`,
+    /// which all group elements lie in by construction. Malformed proofs and proofs
+    /// or cipher texts of other groups are verified separately using `MembershipProof::verify`.
+    ///
+    /// - public_key: The valid public key used to encrypt the plain texts (see `PublicKey::validate`)
+    /// - batch: The cipher texts along with their proofs and the contexts the proofs were created for
+    /// - domain: The set of values the plain texts must be an element of
+    ///
+    /// Returns the indices of the invalid proofs in ascending order,
+    /// i.e. an empty vector if all proofs are valid.
+    pub fn verify_batch(public_key: &PublicKey, batch: &[(CipherText, MembershipProof, &[u8])], domain: &[Scalar]) -> Vec<usize> {
+        let mut invalid: Vec<usize> = Vec::new();
+        let mut entries: Vec<BatchEntry> = Vec::with_capacity(batch.len());
+
+        for (index, &(ref cipher_text, ref proof, context)) in batch.iter().enumerate() {
+            match proof.batch_entry(index, public_key, cipher_text, domain, context) {
+                Some(entry) => entries.push(entry),
+                None => {
+                    if !proof.verify(public_key.clone(), cipher_text.clone(), domain.to_vec(), context) {
+                        invalid.push(index);
+                    }
+                }
+            }
+        }
+
+        MembershipProof::find_invalid(public_key, &entries, &mut invalid);
+
+        invalid.sort();
+        invalid
+    }
+
+    /// Prepare this proof for batch verification, i.e. check its challenge and
+    /// combine its verification equations using random exponents.
+    ///
+    /// Returns `None` if the proof cannot be batched and must be verified separately,
+    /// e.g. if it belongs to another group or its challenge is invalid.
+    fn batch_entry(&self, index: usize, public_key: &PublicKey, cipher_text: &CipherText, domain: &[Scalar], context: &[u8]) -> Option<BatchEntry> {
+        let group = public_key.group();
+        let q = &group.q.value;
+
+        for responses in [self.s_responses.len(), self.c_responses.len(), self.y_responses.len(), self.z_responses.len()].iter() {
+            if *responses != domain.len() {
+                return None;
+            }
+        }
+
+        if !same_group(group, &self.group) || !in_same_group(group, cipher_text, domain) {
+            return None;
+        }
+
+        // in contrast to MembershipProof::verify, the challenge is computed from the
+        // commitments y and z of the proof, which the batched equations are checked for
+        let mut transcript = MembershipProof::transcript(public_key, cipher_text, domain, context);
+        let mut c_choices = Scalar::zero(group);
+
+        for (i, c) in self.c_responses.iter().enumerate() {
+            transcript.append_element(b"y", &self.y_responses[i]);
+            transcript.append_element(b"z", &self.z_responses[i]);
+
             c_choices = c_choices.add(c.clone());
+        }
+
+        if c_choices != transcript.challenge(b"c", group) {
+            return None;
+        }
 
-            let y = g.clone().pow(s.clone()).mul(cipher_text.big_g.clone().pow(neg_c.clone()));
-            let z = h.clone().pow(s.clone()).mul(cipher_text.big_h.clone().div(g_pow).pow(neg_c.clone()));
+        // y * G^c = g^s and z * H^c = h^s * g^(d * c), raised to the random exponents a and b
+        let mut rng = rand::thread_rng();
+        let bound = BigInt::one() << BATCH_EXPONENT_BITS;
+        let mut entry = BatchEntry {
+            index,
+            g_exponent: BigInt::zero(),
+            h_exponent: BigInt::zero(),
+            big_g: cipher_text.big_g.clone(),
+            big_g_exponent: BigInt::zero(),
+            big_h: cipher_text.big_h.clone(),
+            big_h_exponent: BigInt::zero(),
+            commitments: Vec::with_capacity(2 * domain.len()),
+            weights: Vec::with_capacity(2 * domain.len()),
+        };
+
+        for (i, domain_val) in domain.iter().enumerate() {
+            let a = rng.gen_bigint_range(&BigInt::zero(), &bound);
+            let b = rng.gen_bigint_range(&BigInt::zero(), &bound);
+
+            let s = self.s_responses[i].value();
+            let c = self.c_responses[i].value();
 
-            string_to_hash += &y.to_string();
-            string_to_hash += &z.to_string();
+            entry.g_exponent = (entry.g_exponent + s * &a + domain_val.value() * c * &b).mod_floor(q);
+            entry.h_exponent = (entry.h_exponent + s * &b).mod_floor(q);
+            entry.big_g_exponent = (entry.big_g_exponent + c * &a).mod_floor(q);
+            entry.big_h_exponent = (entry.big_h_exponent + c * &b).mod_floor(q);
+
+            entry.commitments.push(self.y_responses[i].clone());
+            entry.weights.push(Scalar::new(group, a));
+            entry.commitments.push(self.z_responses[i].clone());
+            entry.weights.push(Scalar::new(group, b));
         }
 
-        let c_hash: String = Serializer::string_to_sha512(string_to_hash);
-        let new_c = ModInt::from_hex_string(c_hash, self.q.value.clone());
+        Some(entry)
+    }
 
-        return c_choices.eq(&new_c);
+    /// Check the combined equations of the given entries and
+    /// collect the indices of the invalid ones by bisection.
+    fn find_invalid(public_key: &PublicKey, entries: &[BatchEntry], invalid: &mut Vec<usize>) {
+        if entries.is_empty() || MembershipProof::check_batch(public_key, entries) {
+            return;
+        }
+
+        // a valid proof always passes, hence a single failing entry is invalid
+        if entries.len() == 1 {
+            invalid.push(entries[0].index);
+            return;
+        }
+
+        let (left, right) = entries.split_at(entries.len() / 2);
+        MembershipProof::find_invalid(public_key, left, invalid);
+        MembershipProof::find_invalid(public_key, right, invalid);
+    }
+
+    /// Returns true if `Π (y^a * z^b) = g^Σ(..) * h^Σ(..) * Π (G^-Σ(c * a) * H^-Σ(c * b))`
+    /// holds for the given entries.
+    fn check_batch(public_key: &PublicKey, entries: &[BatchEntry]) -> bool {
+        let group = public_key.group();
+
+        let mut g_exponent = BigInt::zero();
+        let mut h_exponent = BigInt::zero();
+        let mut bases: Vec<GroupElement> = Vec::with_capacity(2 * entries.len() + 2);
+        let mut exponents: Vec<Scalar> = Vec::with_capacity(2 * entries.len() + 2);
+        let mut commitments: Vec<GroupElement> = Vec::new();
+        let mut weights: Vec<Scalar> = Vec::new();
+
+        for entry in entries {
+            g_exponent += &entry.g_exponent;
+            h_exponent += &entry.h_exponent;
+
+            bases.push(entry.big_g.clone());
+            exponents.push(Scalar::new(group, -entry.big_g_exponent.clone()));
+            bases.push(entry.big_h.clone());
+            exponents.push(Scalar::new(group, -entry.big_h_exponent.clone()));
+
+            commitments.extend(entry.commitments.iter().cloned());
+            weights.extend(entry.weights.iter().cloned());
+        }
+
+        bases.push(public_key.g());
+        exponents.push(Scalar::new(group, g_exponent));
+        bases.push(public_key.h.clone());
+        exponents.push(Scalar::new(group, h_exponent));
+
+        match (GroupElement::multiexp(group, &commitments, &weights), GroupElement::multiexp(group, &bases, &exponents)) {
+            (Ok(left), Ok(right)) => left == right,
+            _ => false
+        }
+    }
+
+    /// Encode this proof as the amount of responses, followed by the fixed-width
+    /// big-endian encodings of the responses `s`, `c`, `y` and `z` (see `Scalar::to_bytes`
+    /// and `GroupElement::to_bytes`).
+    /// The parameters `p` and `q` are not encoded, as they are part of the public key.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        encoding::write_length(&mut bytes, self.s_responses.len());
+        encoding::write_scalars(&mut bytes, &self.s_responses);
+        encoding::write_scalars(&mut bytes, &self.c_responses);
+        encoding::write_elements(&mut bytes, &self.y_responses);
+        encoding::write_elements(&mut bytes, &self.z_responses);
+
+        bytes
+    }
+
+    /// Decode a proof encoded by `MembershipProof::to_bytes`.
+    ///
+    /// Returns `Error::InvalidEncoding` if the encoding is malformed, `Error::ValueNotReduced`
+    /// if a response is not reduced and `Error::ElementNotInGroup` if one of the responses
+    /// `y` and `z` is not an element of the subgroup of order `q`.
+    ///
+    /// - bytes: The encoded proof
+    /// - public_key: The public key the proof was created for
+    pub fn from_bytes(bytes: &[u8], public_key: &PublicKey) -> Result<MembershipProof, Error> {
+        let group = public_key.group();
+
+        let element_length = 2 * ModInt::byte_length(&group.q.value) + 2 * ModInt::byte_length(&group.p.value);
+
+        let mut reader = ByteReader::new(bytes);
+        let amount = reader.read_length(element_length)?;
+
+        let s_responses = reader.read_scalars(amount, group)?;
+        let c_responses = reader.read_scalars(amount, group)?;
+        let y_responses = reader.read_elements(amount, group)?;
+        let z_responses = reader.read_elements(amount, group)?;
+        reader.finish()?;
+
+        Ok(MembershipProof {
+            s_responses,
+            c_responses,
+            y_responses,
+            z_responses,
+            group: group.clone(),
+        })
+    }
+
+    /// Create the transcript of the statement, i.e. of the context, the public key,
+    /// the cipher text and the domain of the plain text.
+    fn transcript(public_key: &PublicKey, cipher_text: &CipherText, domain: &[Scalar], context: &[u8]) -> Transcript {
+        let mut transcript = Transcript::new(b"membership-proof");
+        transcript.append_message(b"context", context);
+        transcript.append_public_key(public_key);
+        transcript.append_cipher_text(b"cipher-text", cipher_text);
+        transcript.append_scalars(b"domain", domain);
+
+        transcript
     }
 }
 
+/// A proof prepared by `MembershipProof::batch_entry`, i.e. its verification equations
+/// raised to random exponents and combined per base.
+struct BatchEntry {
+    /// The index of the proof within the batch
+    index: usize,
+    g_exponent: BigInt,
+    h_exponent: BigInt,
+    big_g: GroupElement,
+    big_g_exponent: BigInt,
+    big_h: GroupElement,
+    big_h_exponent: BigInt,
+    /// The commitments y and z of the proof
+    commitments: Vec<GroupElement>,
+    /// The random exponents of the commitments
+    weights: Vec<Scalar>,
+}
+
 #[cfg(test)]
 mod membership_proof_test {
 
-    use ::el_gamal::encryption::PublicKey;
+    use ::arithmetic::group::Group;
+    use ::arithmetic::in_group;
+    use ::arithmetic::scalar::Scalar;
+    use ::el_gamal::encryption::{KeyPair, PublicKey};
+    use ::el_gamal::ciphertext::CipherText;
     use ::el_gamal::encryption::{encrypt};
-    use ::arithmetic::mod_int::ModInt;
-    use arithmetic::mod_int::From;
+    use ::el_gamal::precomputed::PrecomputedPublicKey;
     use ::num::bigint::BigInt;
-    use ::num::Zero;
-    use ::num::One;
     use ::el_gamal::membership_proof::MembershipProof;
+    use ::error::Error;
+    use std::sync::Arc;
     use std::vec::Vec;
     use std::clone::Clone;
 
+    const CONTEXT: &[u8] = b"election-1/voter-1/question-1";
+
+    //h := (g^x) mod p
+    //1680623351278 := 4^1234567 mod 2199023255867
+    fn public_key() -> PublicKey {
+        let group = Arc::new(Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4)));
+
+        PublicKey::from_group(&group, BigInt::from(1680623351278i64)).unwrap()
+    }
+
+    fn plain_text(pub_key: &PublicKey, value: i64) -> Scalar {
+        Scalar::new(pub_key.group(), BigInt::from(value))
+    }
+
     #[test]
     pub fn test_one_or_proof() {
-        let message: ModInt = ModInt {
-            value: BigInt::one(),
-            modulus: BigInt::from(5) // must be equal to the value p of the public key
-        };
+        let pub_key = public_key();
+        let message = plain_text(&pub_key, 1);
 
-        //h := (g^x) mod p
-        //2 := 2^5 mod 5
-        let pub_key: PublicKey = PublicKey {
-            p: ModInt::from_value_modulus(BigInt::from(5), BigInt::zero()),
-            q: ModInt::from_value_modulus(BigInt::from(2), BigInt::zero()),
-            h: ModInt::from_value_modulus(BigInt::from(32), BigInt::from(5)),
-            g: ModInt::from_value_modulus(BigInt::from(2), BigInt::from(5))
-        };
-
-        let cipher_text = encrypt(&pub_key, message.clone());
+        let (cipher_text, randomness) = encrypt(&pub_key, message.clone());
 
         let mut domains = Vec::new();
-        domains.push(ModInt::zero());
-        domains.push(ModInt::one());
+        domains.push(plain_text(&pub_key, 0));
+        domains.push(plain_text(&pub_key, 1));
 
 
         let proof = MembershipProof::new(
             pub_key.clone(),
             message,
             cipher_text.clone(),
-            domains.clone()
+            randomness,
+            domains.clone(),
+            CONTEXT
         );
 
-        let is_proven = proof.verify(pub_key.clone(), cipher_text.clone(), domains.clone());
+        let is_proven = proof.verify(pub_key.clone(), cipher_text.clone(), domains.clone(), CONTEXT);
 
         assert!(is_proven);
     }
 
     #[test]
-    pub fn test_zero_or_proof() {
-        let message: ModInt = ModInt {
-            value: BigInt::zero(),
-            modulus: BigInt::from(5) // must be equal to the value p of the public key
-        };
+    pub fn test_wrong_context_rejected() {
+        let pub_key = public_key();
 
-        //h := (g^x) mod p
-        //2 := 2^5 mod 5
-        let pub_key: PublicKey = PublicKey {
-            p: ModInt::from_value_modulus(BigInt::from(5), BigInt::zero()),
-            q: ModInt::from_value_modulus(BigInt::from(2), BigInt::zero()),
-            h: ModInt::from_value_modulus(BigInt::from(32), BigInt::from(5)),
-            g: ModInt::from_value_modulus(BigInt::from(2), BigInt::from(5))
-        };
+        let domains = vec![plain_text(&pub_key, 0), plain_text(&pub_key, 1)];
+        let (cipher_text, randomness) = encrypt(&pub_key, plain_text(&pub_key, 1));
+        let proof = MembershipProof::new(pub_key.clone(), plain_text(&pub_key, 1), cipher_text.clone(), randomness, domains.clone(), CONTEXT);
+
+        assert!(proof.verify(pub_key.clone(), cipher_text.clone(), domains.clone(), CONTEXT));
 
-        let cipher_text = encrypt(&pub_key, message.clone());
+        // the proof cannot be replayed onto another election or another voter
+        assert!(!proof.verify(pub_key.clone(), cipher_text.clone(), domains.clone(), b"election-2/voter-1/question-1"));
+        assert!(!proof.verify(pub_key, cipher_text, domains, b"election-1/voter-2/question-1"));
+    }
+
+    #[test]
+    pub fn test_zero_or_proof() {
+        let pub_key = public_key();
+        let message = plain_text(&pub_key, 0);
+
+        let (cipher_text, randomness) = encrypt(&pub_key, message.clone());
 
         let mut domains = Vec::new();
-        domains.push(ModInt::zero());
-        domains.push(ModInt::one());
+        domains.push(plain_text(&pub_key, 0));
+        domains.push(plain_text(&pub_key, 1));
 
 
         let proof = MembershipProof::new(
             pub_key.clone(),
             message, // <- other message than encrypted
             cipher_text.clone(),
-            domains.clone()
+            randomness,
+            domains.clone(),
+            CONTEXT
         );
 
-        let is_proven = proof.verify(pub_key.clone(), cipher_text.clone(), domains.clone());
+        let is_proven = proof.verify(pub_key.clone(), cipher_text.clone(), domains.clone(), CONTEXT);
 
         assert!(is_proven);
     }
+
+    #[test]
+    pub fn test_precomputed_public_key() {
+        let pub_key = public_key();
+        let precomputed = PrecomputedPublicKey::new(pub_key.clone());
+
+        let domains = vec![plain_text(&pub_key, 0), plain_text(&pub_key, 1), plain_text(&pub_key, 2)];
+
+        for message in domains.iter() {
+            let (cipher_text, randomness) = encrypt(&precomputed, message.clone());
+            let proof = MembershipProof::new(&precomputed, message.clone(), cipher_text.clone(), randomness, domains.clone(), CONTEXT);
+
+            assert!(proof.verify(pub_key.clone(), cipher_text, domains.clone(), CONTEXT));
+        }
+    }
+
+    #[test]
+    pub fn test_verify_batch() {
+        let pub_key = public_key();
+
+        let domains = vec![plain_text(&pub_key, 0), plain_text(&pub_key, 1), plain_text(&pub_key, 2)];
+        let contexts: Vec<Vec<u8>> = (0..8)
+            .map(|voter| format!("election-1/voter-{}/question-1", voter).into_bytes())
+            .collect();
+
+        let mut ballots: Vec<(CipherText, MembershipProof)> = Vec::new();
+        for (voter, context) in contexts.iter().enumerate() {
+            let message = domains[voter % domains.len()].clone();
+            let (cipher_text, randomness) = encrypt(&pub_key, message.clone());
+            let proof = MembershipProof::new(pub_key.clone(), message, cipher_text.clone(), randomness, domains.clone(), context);
+
+            assert!(proof.batch_entry(voter, &pub_key, &cipher_text, &domains, context).is_some());
+            ballots.push((cipher_text, proof));
+        }
+
+        let batch = |ballots: &[(CipherText, MembershipProof)]| -> Vec<(CipherText, MembershipProof, &[u8])> {
+            ballots.iter().zip(contexts.iter())
+                .map(|((cipher_text, proof), context)| (cipher_text.clone(), proof.clone(), context.as_slice()))
+                .collect()
+        };
+
+        assert!(MembershipProof::verify_batch(&pub_key, &batch(&ballots), &domains).is_empty());
+
+        // the proof of another voter does not match the context
+        ballots[1].1 = ballots[2].1.clone();
+
+        // the responses s are not part of the challenge, so only the combined equation fails
+        ballots[3].1.s_responses[0] = ballots[3].1.s_responses[0].clone() + plain_text(&pub_key, 1);
+
+        // the proof does not match the cipher text
+        ballots[4].0 = encrypt(&pub_key, plain_text(&pub_key, 1)).0;
+
+        // a cipher text of another group cannot be batched
+        let other_key_pair = KeyPair::generate(&Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        ballots[6].0 = encrypt(&other_key_pair.public_key, plain_text(&other_key_pair.public_key, 1)).0;
+
+        let batch = batch(&ballots);
+        let expected: Vec<usize> = batch.iter().enumerate()
+            .filter(|&(_, &(ref cipher_text, ref proof, context))| !proof.verify(pub_key.clone(), cipher_text.clone(), domains.clone(), context))
+            .map(|(index, _)| index)
+            .collect();
+
+        assert_eq!(vec![1, 3, 4, 6], expected);
+        assert_eq!(expected, MembershipProof::verify_batch(&pub_key, &batch, &domains));
+    }
+
+    #[test]
+    pub fn test_par_verify() {
+        let pub_key = public_key();
+
+        let domains = vec![plain_text(&pub_key, 0), plain_text(&pub_key, 1)];
+        let contexts: Vec<Vec<u8>> = (0..10)
+            .map(|voter| format!("election-1/voter-{}/question-1", voter).into_bytes())
+            .collect();
+
+        let mut ballots: Vec<(CipherText, MembershipProof, &[u8])> = contexts.iter().enumerate()
+            .map(|(voter, context)| {
+                let message = domains[voter % domains.len()].clone();
+                let (cipher_text, randomness) = encrypt(&pub_key, message.clone());
+                let proof = MembershipProof::new(pub_key.clone(), message, cipher_text.clone(), randomness, domains.clone(), context);
+
+                (cipher_text, proof, context.as_slice())
+            })
+            .collect();
+
+        // the proof of another voter does not match the context
+        ballots[2].1 = ballots[3].1.clone();
+
+        // the proof does not match the cipher text
+        ballots[7].0 = encrypt(&pub_key, plain_text(&pub_key, 1)).0;
+
+        let expected: Vec<bool> = ballots.iter()
+            .map(|&(ref cipher_text, ref proof, context)| proof.verify(pub_key.clone(), cipher_text.clone(), domains.clone(), context))
+            .collect();
+
+        assert_eq!(vec![true, true, false, true, true, true, true, false, true, true], expected);
+        assert_eq!(expected, MembershipProof::par_verify(&pub_key, &ballots, &domains));
+        assert!(MembershipProof::par_verify(&pub_key, &[], &domains).is_empty());
+    }
+
+    #[test]
+    pub fn test_plain_text_representatives() {
+        let pub_key = public_key();
+
+        let domains = vec![plain_text(&pub_key, 0), plain_text(&pub_key, 1)];
+
+        // q + 1 and 1 - q both represent the domain value 1
+        let representatives = vec![
+            plain_text(&pub_key, 1099511627934i64),
+            plain_text(&pub_key, -1099511627932i64),
+        ];
+
+        for message in representatives {
+            let (cipher_text, randomness) = encrypt(&pub_key, message.clone());
+            let proof = MembershipProof::try_new(pub_key.clone(), message, cipher_text.clone(), randomness, domains.clone(), CONTEXT).unwrap();
+
+            assert!(proof.verify(pub_key.clone(), cipher_text, domains.clone(), CONTEXT));
+        }
+    }
+
+    #[test]
+    pub fn test_message_not_in_domain() {
+        let pub_key = public_key();
+        let message = plain_text(&pub_key, 2);
+
+        let (cipher_text, randomness) = encrypt(&pub_key, message.clone());
+        let domains = vec![plain_text(&pub_key, 0), plain_text(&pub_key, 1)];
+
+        match MembershipProof::try_new(pub_key, message, cipher_text, randomness, domains, CONTEXT) {
+            Err(Error::PlainTextNotInDomain) => (),
+            other => panic!("expected the plain-text not to be in the domain, got {:?}", other)
+        }
+    }
+
+    #[test]
+    pub fn test_other_group() {
+        let pub_key = public_key();
+        let other_pub_key = KeyPair::generate(&Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4))).public_key;
+
+        let message = plain_text(&pub_key, 1);
+        let (cipher_text, randomness) = encrypt(&pub_key, message.clone());
+        let domains = vec![plain_text(&pub_key, 0), plain_text(&pub_key, 1)];
+        let other_domains = vec![plain_text(&other_pub_key, 0), plain_text(&other_pub_key, 1)];
+
+        match MembershipProof::try_new(pub_key.clone(), message.clone(), cipher_text.clone(), randomness.clone(), other_domains.clone(), CONTEXT) {
+            Err(Error::GroupMismatch) => (),
+            other => panic!("expected a group mismatch, got {:?}", other)
+        }
+
+        let proof = MembershipProof::new(pub_key.clone(), message, cipher_text.clone(), randomness, domains.clone(), CONTEXT);
+
+        match proof.try_verify(pub_key.clone(), cipher_text.clone(), other_domains, CONTEXT) {
+            Err(Error::GroupMismatch) => (),
+            other => panic!("expected a group mismatch, got {:?}", other)
+        }
+
+        let (other_cipher_text, _) = encrypt(&other_pub_key, plain_text(&other_pub_key, 1));
+        match proof.try_verify(pub_key.clone(), other_cipher_text, domains.clone(), CONTEXT) {
+            Err(Error::GroupMismatch) => (),
+            other => panic!("expected a group mismatch, got {:?}", other)
+        }
+
+        assert!(!proof.verify(other_pub_key, cipher_text, domains, CONTEXT));
+    }
+
+    #[test]
+    pub fn test_domain_length_mismatch() {
+        let pub_key = public_key();
+        let message = plain_text(&pub_key, 1);
+
+        let (cipher_text, randomness) = encrypt(&pub_key, message.clone());
+        let domains = vec![plain_text(&pub_key, 0), plain_text(&pub_key, 1)];
+
+        let proof = MembershipProof::new(pub_key.clone(), message, cipher_text.clone(), randomness, domains, CONTEXT);
+
+        let smaller_domains = vec![plain_text(&pub_key, 1)];
+        let bigger_domains = vec![plain_text(&pub_key, 0), plain_text(&pub_key, 1), plain_text(&pub_key, 2)];
+
+        match proof.try_verify(pub_key.clone(), cipher_text.clone(), smaller_domains.clone(), CONTEXT) {
+            Err(Error::LengthMismatch { expected: 1, actual: 2 }) => (),
+            other => panic!("expected a length mismatch, got {:?}", other)
+        }
+        assert!(!proof.verify(pub_key.clone(), cipher_text.clone(), smaller_domains, CONTEXT));
+
+        match proof.try_verify(pub_key.clone(), cipher_text.clone(), bigger_domains.clone(), CONTEXT) {
+            Err(Error::LengthMismatch { expected: 3, actual: 2 }) => (),
+            other => panic!("expected a length mismatch, got {:?}", other)
+        }
+        assert!(!proof.verify(pub_key.clone(), cipher_text.clone(), bigger_domains, CONTEXT));
+    }
+
+    #[test]
+    pub fn test_deserialize_in_group() {
+        let pub_key = public_key();
+
+        let domains = vec![plain_text(&pub_key, 0), plain_text(&pub_key, 1)];
+        let (cipher_text, randomness) = encrypt(&pub_key, plain_text(&pub_key, 1));
+        let proof = MembershipProof::new(pub_key.clone(), plain_text(&pub_key, 1), cipher_text.clone(), randomness, domains.clone(), CONTEXT);
+
+        let json = ::serde_json::to_string(&proof).unwrap();
+        let decoded: MembershipProof = in_group::from_str(pub_key.group(), &json).unwrap();
+        assert_eq!(proof, decoded);
+        assert!(decoded.verify(pub_key, cipher_text, domains, CONTEXT));
+
+        // the proof was created for another group
+        let other_group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        assert!(in_group::from_str::<MembershipProof>(&other_group, &json).is_err());
+    }
+
+    #[test]
+    pub fn test_to_from_bytes() {
+        let pub_key = public_key();
+
+        let domains = vec![plain_text(&pub_key, 0), plain_text(&pub_key, 1)];
+        let (cipher_text, randomness) = encrypt(&pub_key, plain_text(&pub_key, 1));
+        let proof = MembershipProof::new(pub_key.clone(), plain_text(&pub_key, 1), cipher_text.clone(), randomness, domains.clone(), CONTEXT);
+
+        // the amount of responses followed by 2 * 2 scalars and 2 * 2 elements of 6 bytes each
+        let bytes = proof.to_bytes();
+        assert_eq!(4 + 8 * 6, bytes.len());
+
+        let decoded = MembershipProof::from_bytes(&bytes, &pub_key).unwrap();
+        assert_eq!(proof, decoded);
+        assert!(decoded.verify(pub_key.clone(), cipher_text, domains, CONTEXT));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        match MembershipProof::from_bytes(&trailing, &pub_key) {
+            Err(Error::InvalidEncoding) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // claim more responses than there are bytes
+        let mut too_many = bytes.clone();
+        too_many[3] = 3;
+        match MembershipProof::from_bytes(&too_many, &pub_key) {
+            Err(Error::InvalidEncoding) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
@@ -1,5 +1,10 @@
 pub mod additive;
 pub mod ciphertext;
+pub mod decryption_proof;
 pub mod encryption;
 pub mod membership_proof;
-pub mod serializer;
\ No newline at end of file
+pub mod precomputed;
+pub mod schnorr_proof;
+pub mod serializer;
+pub mod threshold;
+pub mod transcript;
\ No newline at end of file
@@ -0,0 +1,179 @@
+use ::arithmetic::fixed_base::FixedBaseTable;
+use ::arithmetic::group_element::{same_group, GroupElement};
+use ::arithmetic::scalar::Scalar;
+use ::el_gamal::encryption::PublicKey;
+use ::error::Error;
+
+/// # Fixed-Base Exponentiation
+///
+/// Exponentiations of the fixed bases `g` and `h` of a public key.
+///
+/// Encryption and the creation of proofs are generic over this trait, so that
+/// they accept both a plain `PublicKey` and a `PrecomputedPublicKey`.
+///
+/// The public key must be valid (see `PublicKey::validate`),
+/// i.e. `g` and `h` must have order `q`.
+///
+/// Both implementations are side-channel safe for secret exponents, as long as `p`
+/// is odd: `PublicKey` uses the Montgomery ladder of `GroupElement::pow_secret`, and
+/// `PrecomputedPublicKey` scans all table entries of each window (see `FixedBaseTable`).
+/// For even `p`, both fall back to `BigInt` arithmetic, which is not protected.
+pub trait FixedBasePow {
+    /// Returns the public key the bases belong to.
+    fn public_key(&self) -> &PublicKey;
+
+    /// Returns `g^exponent`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the exponent belongs to another group than the public key.
+    fn g_pow(&self, exponent: &Scalar) -> GroupElement;
+
+    /// Returns `h^exponent`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the exponent belongs to another group than the public key.
+    fn h_pow(&self, exponent: &Scalar) -> GroupElement;
+}
+
+/// Exponentiates using `GroupElement::pow_secret`, i.e. without any precomputation.
+impl FixedBasePow for PublicKey {
+    fn public_key(&self) -> &PublicKey {
+        self
+    }
+
+    fn g_pow(&self, exponent: &Scalar) -> GroupElement {
+        self.g().pow_secret(exponent)
+    }
+
+    fn h_pow(&self, exponent: &Scalar) -> GroupElement {
+        self.h.clone().pow_secret(exponent)
+    }
+}
+
+impl<T: FixedBasePow> FixedBasePow for &T {
+    fn public_key(&self) -> &PublicKey {
+        (**self).public_key()
+    }
+
+    fn g_pow(&self, exponent: &Scalar) -> GroupElement {
+        (**self).g_pow(exponent)
+    }
+
+    fn h_pow(&self, exponent: &Scalar) -> GroupElement {
+        (**self).h_pow(exponent)
+    }
+}
+
+/// # Precomputed Public Key
+///
+/// A public key along with fixed-base tables for `g` and `h`
+/// (see `arithmetic::fixed_base::FixedBaseTable`).
+///
+/// Building the tables requires about `4 * bits(q)` multiplications per base,
+/// i.e. roughly as many as two exponentiations. Afterwards, each exponentiation
+/// of `g` or `h` requires `bits(q) / 4` multiplications instead of the
+/// `2 * bits(q)` of `GroupElement::pow_secret`. Hence, the tables pay off as soon as
+/// more than a single ballot or proof is created for the same public key.
+#[derive(Clone, Debug)]
+pub struct PrecomputedPublicKey {
+    public_key: PublicKey,
+    g_table: FixedBaseTable,
+    h_table: FixedBaseTable,
+}
+
+impl PrecomputedPublicKey {
+    /// Build the tables for the given public key, which must be valid
+    /// (see `PublicKey::validate`).
+    ///
+    /// - public_key: The public key to precompute the bases of
+    pub fn new(public_key: PublicKey) -> Self {
+        let group = public_key.group();
+        let p = &group.p.value;
+        let bits = group.q.value.bits();
+
+        PrecomputedPublicKey {
+            g_table: FixedBaseTable::new(public_key.g().value(), p, bits),
+            h_table: FixedBaseTable::new(public_key.h.value(), p, bits),
+            public_key,
+        }
+    }
+
+    /// Look up `base^exponent` in the given table.
+    fn pow(&self, table: &FixedBaseTable, exponent: &Scalar) -> GroupElement {
+        let group = self.public_key.group();
+        if !same_group(group, exponent.group()) {
+            panic!("{}", Error::GroupMismatch);
+        }
+
+        GroupElement::new_unchecked(group, table.pow(exponent.value()))
+    }
+}
+
+impl FixedBasePow for PrecomputedPublicKey {
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    fn g_pow(&self, exponent: &Scalar) -> GroupElement {
+        self.pow(&self.g_table, exponent)
+    }
+
+    fn h_pow(&self, exponent: &Scalar) -> GroupElement {
+        self.pow(&self.h_table, exponent)
+    }
+}
+
+#[cfg(test)]
+mod precomputed_test {
+
+    use ::arithmetic::group::Group;
+    use ::arithmetic::scalar::Scalar;
+    use ::el_gamal::encryption::{decrypt, encrypt, KeyPair};
+    use ::el_gamal::precomputed::{FixedBasePow, PrecomputedPublicKey};
+    use ::num::bigint::BigInt;
+    use ::num::traits::Pow;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_pow() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let precomputed = PrecomputedPublicKey::new(key_pair.public_key.clone());
+
+        for _ in 0..20 {
+            let exponent = Scalar::random(key_pair.public_key.group());
+
+            let g_pow = key_pair.public_key.g().pow(&exponent);
+            let h_pow = key_pair.public_key.h.clone().pow(&exponent);
+
+            assert_eq!(g_pow, precomputed.g_pow(&exponent));
+            assert_eq!(h_pow, precomputed.h_pow(&exponent));
+            assert_eq!(g_pow, key_pair.public_key.g_pow(&exponent));
+            assert_eq!(h_pow, key_pair.public_key.h_pow(&exponent));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pow_other_group() {
+        let group = Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4));
+        let precomputed = PrecomputedPublicKey::new(KeyPair::generate(&group).public_key);
+        let other_group = Arc::new(Group::new(BigInt::from(47), BigInt::from(23), BigInt::from(4)));
+
+        precomputed.g_pow(&Scalar::new(&other_group, BigInt::from(3)));
+    }
+
+    #[test]
+    fn test_encrypt() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+        let precomputed = PrecomputedPublicKey::new(key_pair.public_key.clone());
+
+        let message = Scalar::new(key_pair.public_key.group(), BigInt::from(5));
+        let (cipher_text, _) = encrypt(&precomputed, message.clone());
+
+        assert_eq!(message, decrypt(key_pair.private_key, cipher_text, 1000).unwrap());
+    }
+}
@@ -0,0 +1,199 @@
+use ::arithmetic::group::Group;
+use ::arithmetic::group_element::GroupElement;
+use ::arithmetic::in_group::{self, DeserializeInGroup};
+use ::arithmetic::mod_int::ModInt;
+use ::arithmetic::scalar::Scalar;
+use ::el_gamal::encryption::{PrivateKey, PublicKey};
+use ::el_gamal::transcript::Transcript;
+use ::error::Error;
+use serde::{Deserialize, Deserializer};
+use std::ops::{Add, Mul, Neg};
+use std::sync::Arc;
+
+/// # Schnorr Proof
+///
+/// A non-interactive Schnorr proof of knowledge of the secret `x`
+/// such that `h = g^x`.
+///
+/// Publishing this proof along with a public key shows that its owner
+/// knows the corresponding private key. This prevents rogue-key attacks
+/// when public keys are combined by multiplying their values of `h`,
+/// e.g. during a distributed key generation.
+#[derive(Eq, PartialEq, Serialize, Hash, Clone, Debug)]
+#[serde(into = "SchnorrProofRepr")]
+pub struct SchnorrProof {
+    challenge: Scalar,
+    response: Scalar,
+}
+
+/// The serialized form of a `SchnorrProof`.
+#[derive(Clone, Serialize, Deserialize)]
+struct SchnorrProofRepr {
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    challenge: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    response: ModInt,
+}
+
+impl ::std::convert::From<SchnorrProof> for SchnorrProofRepr {
+    fn from(proof: SchnorrProof) -> Self {
+        SchnorrProofRepr {
+            challenge: proof.challenge.to_mod_int(),
+            response: proof.response.to_mod_int(),
+        }
+    }
+}
+
+impl DeserializeInGroup for SchnorrProof {
+    fn deserialize_in<'de, D: Deserializer<'de>>(group: &Arc<Group>, deserializer: D) -> Result<Self, D::Error> {
+        let repr = SchnorrProofRepr::deserialize(deserializer)?;
+
+        Ok(SchnorrProof {
+            challenge: in_group::scalar(group, &repr.challenge)?,
+            response: in_group::scalar(group, &repr.response)?,
+        })
+    }
+}
+
+impl SchnorrProof {
+    /// Create a new proof of knowledge of the given private key.
+    ///
+    /// - private_key: The private key `x` of the public value `h = g^x`
+    /// - context: The context the proof is bound to, e.g. the ID of the election and the key holder
+    pub fn new(private_key: PrivateKey, context: &[u8]) -> SchnorrProof {
+        let public_key = private_key.public_key();
+
+        let t = Scalar::random(private_key.group());
+        let a = public_key.g().pow_secret(&t);
+
+        let c = SchnorrProof::challenge(&public_key, &a, context);
+        let s = c.clone().mul(private_key.x).add(t);
+
+        SchnorrProof {
+            challenge: c,
+            response: s,
+        }
+    }
+
+    /// Verify that the creator of this proof knows the private key
+    /// belonging to the given public key.
+    ///
+    /// - public_key: The public key `h = g^x`
+    /// - context: The context the proof was created for
+    pub fn verify(&self, public_key: PublicKey, context: &[u8]) -> bool {
+        self.try_verify(public_key, context).unwrap_or(false)
+    }
+
+    /// Verify this proof as described in `SchnorrProof::verify`, returning
+    /// an error if the proof belongs to another group than the public key.
+    pub fn try_verify(&self, public_key: PublicKey, context: &[u8]) -> Result<bool, Error> {
+        let bases = [public_key.g(), public_key.h.clone()];
+
+        // a = g^s * h^-c
+        let neg_c: Scalar = self.challenge.clone().neg();
+        let a = GroupElement::multiexp(public_key.group(), &bases, &[self.response.clone(), neg_c])?;
+
+        let new_c = SchnorrProof::challenge(&public_key, &a, context);
+
+        self.challenge.checked_eq(&new_c)
+    }
+
+    fn challenge(public_key: &PublicKey, a: &GroupElement, context: &[u8]) -> Scalar {
+        let mut transcript = Transcript::new(b"schnorr-proof");
+        transcript.append_message(b"context", context);
+        transcript.append_public_key(public_key);
+        transcript.append_element(b"a", a);
+
+        transcript.challenge(b"c", public_key.group())
+    }
+}
+
+#[cfg(test)]
+mod schnorr_proof_test {
+
+    use ::arithmetic::group::Group;
+    use ::arithmetic::in_group;
+    use ::arithmetic::scalar::Scalar;
+    use ::el_gamal::encryption::{KeyPair, PrivateKey, PublicKey};
+    use ::el_gamal::schnorr_proof::SchnorrProof;
+    use ::error::Error;
+    use ::num::bigint::BigInt;
+    use ::serde_json;
+    use std::sync::Arc;
+
+    const CONTEXT: &[u8] = b"election-1/trustee-1";
+
+    #[test]
+    fn test_schnorr_proof() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+
+        let proof = SchnorrProof::new(key_pair.private_key.clone(), CONTEXT);
+        assert!(proof.verify(key_pair.public_key.clone(), CONTEXT));
+
+        // the proof can be published along with the public key
+        let json = serde_json::to_string(&proof).unwrap();
+        let deserialized: SchnorrProof = in_group::from_str(key_pair.public_key.group(), &json).unwrap();
+        assert!(deserialized.verify(key_pair.public_key.clone(), CONTEXT));
+
+        let other_key_pair = KeyPair::generate(&group);
+        assert!(!proof.verify(other_key_pair.public_key, CONTEXT));
+    }
+
+    #[test]
+    fn test_wrong_context_rejected() {
+        let group = Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4));
+        let key_pair = KeyPair::generate(&group);
+
+        let proof = SchnorrProof::new(key_pair.private_key.clone(), CONTEXT);
+
+        assert!(proof.verify(key_pair.public_key.clone(), CONTEXT));
+        assert!(!proof.verify(key_pair.public_key, b"election-2/trustee-1"));
+    }
+
+    #[test]
+    fn test_schnorr_proof_wrong_secret() {
+        let group = Arc::new(Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4)));
+
+        // h = 4^1234567 mod p
+        let public_key = PublicKey::from_group(&group, BigInt::from(1680623351278i64)).unwrap();
+        let private_key = PrivateKey {
+            x: Scalar::new(&group, BigInt::from(1234567)),
+        };
+
+        assert!(SchnorrProof::new(private_key, CONTEXT).verify(public_key.clone(), CONTEXT));
+
+        let wrong_private_key = PrivateKey {
+            x: Scalar::new(&group, BigInt::from(7654321)),
+        };
+
+        assert!(!SchnorrProof::new(wrong_private_key, CONTEXT).verify(public_key, CONTEXT));
+    }
+
+    #[test]
+    fn test_h_outside_subgroup_rejected() {
+        let group = Arc::new(Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4)));
+        let key_pair = KeyPair::generate(&group);
+
+        // -h has order 2q, and its sign would cancel in h^-c whenever -c mod q is even
+        let minus_h = group.p.value.clone() - key_pair.public_key.h.value().clone();
+
+        match PublicKey::from_group(&group, minus_h) {
+            Err(Error::ElementNotInGroup) => (),
+            other => panic!("expected an element outside of the group, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_other_group_rejected() {
+        let key_pair = KeyPair::generate(&Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4)));
+        let other_key_pair = KeyPair::generate(&Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        let proof = SchnorrProof::new(key_pair.private_key, CONTEXT);
+
+        match proof.try_verify(other_key_pair.public_key.clone(), CONTEXT) {
+            Err(Error::GroupMismatch) => (),
+            other => panic!("expected a group mismatch, got {:?}", other)
+        }
+        assert!(!proof.verify(other_key_pair.public_key, CONTEXT));
+    }
+}
@@ -3,6 +3,7 @@ use sha2::{Sha512, Digest};
 pub struct Serializer {}
 
 impl Serializer {
+    #[deprecated(note = "hash the inputs of proofs using `el_gamal::transcript::Transcript` instead")]
     pub fn string_to_sha512(string: String) -> String {
         // create a Sha512 object
         let mut hasher = Sha512::default();
@@ -25,6 +26,7 @@ mod serializer_test {
     use ::el_gamal::serializer::Serializer;
 
     #[test]
+    #[allow(deprecated)]
     fn test_string_to_sha512_hex() {
         let result = Serializer::string_to_sha512("1234".to_string());
 
@@ -0,0 +1,914 @@
+use ::arithmetic::group::Group;
+use ::arithmetic::group_element::{same_group, GroupElement};
+use ::arithmetic::in_group::{self, DeserializeInGroup};
+use ::arithmetic::mod_int::ModInt;
+use ::arithmetic::mod_inverse;
+use ::arithmetic::scalar::Scalar;
+use ::el_gamal::ciphertext::CipherText;
+use ::el_gamal::decryption_proof::DecryptionProof;
+use ::el_gamal::encryption::{DiscreteLog, PrivateKey, PublicKey};
+use ::el_gamal::schnorr_proof::SchnorrProof;
+use ::error::Error;
+use num::bigint::BigInt;
+use num::Integer;
+use num::One;
+use serde::{Deserialize, Deserializer};
+use std::convert::TryFrom;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+/// # Threshold Parameters
+///
+/// The group along with the amount of trustees `n` and the threshold `t`,
+/// i.e. the amount of trustees required to decrypt a cipher text.
+///
+/// Trustees are identified by their index within `[1, n]`.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ThresholdParameters {
+    pub group: Arc<Group>,
+    pub threshold: usize,
+    pub trustees: usize,
+}
+
+impl ThresholdParameters {
+    /// Create new threshold parameters.
+    ///
+    /// Returns `Error::InvalidThreshold` if the threshold is not within `[1, trustees]`
+    /// or if the amount of trustees is not smaller than the order `q` of the group.
+    ///
+    /// - group: The parameters `(p, q, g)` of the group to use
+    /// - threshold: The amount of trustees `t` required for decryption
+    /// - trustees: The total amount of trustees `n`
+    pub fn new(group: &Arc<Group>, threshold: usize, trustees: usize) -> Result<Self, Error> {
+        if threshold < 1 || threshold > trustees || BigInt::from(trustees) >= group.q.value {
+            return Err(Error::InvalidThreshold { threshold, trustees });
+        }
+
+        Ok(ThresholdParameters {
+            group: group.clone(),
+            threshold,
+            trustees,
+        })
+    }
+
+    fn check_index(&self, index: usize) -> Result<(), Error> {
+        if index < 1 || index > self.trustees {
+            return Err(Error::IndexOutOfBounds { index, length: self.trustees });
+        }
+
+        Ok(())
+    }
+}
+
+/// # Trustee
+///
+/// A dealer in the distributed key generation of Pedersen.
+///
+/// Each trustee samples a secret polynomial `f_i` of degree `t - 1` over `Z_q`,
+/// publishes Feldman commitments `C_ik = g^a_ik` to its coefficients and sends
+/// the secret share `f_i(j)` privately to every trustee `j`.
+/// The joint secret key `x = f_1(0) + ... + f_n(0)` is never known to any party.
+#[derive(Clone)]
+pub struct Trustee {
+    pub index: usize,
+    parameters: ThresholdParameters,
+    /// The coefficients `a_i0, ..., a_i(t-1)` of the secret polynomial.
+    coefficients: Vec<Scalar>,
+}
+
+impl Trustee {
+    /// Create a new trustee by sampling a random secret polynomial.
+    ///
+    /// Returns `Error::IndexOutOfBounds` if the index is not within `[1, n]`.
+    ///
+    /// - parameters: The threshold parameters
+    /// - index: The index of this trustee
+    pub fn new(parameters: &ThresholdParameters, index: usize) -> Result<Self, Error> {
+        parameters.check_index(index)?;
+
+        let coefficients = (0..parameters.threshold)
+            .map(|_| Scalar::random(&parameters.group))
+            .collect();
+
+        Ok(Trustee {
+            index,
+            parameters: parameters.clone(),
+            coefficients,
+        })
+    }
+
+    /// Returns the public commitments `C_ik = g^a_ik` to the coefficients
+    /// of the secret polynomial, which are broadcast to all trustees.
+    pub fn commitments(&self) -> Commitments {
+        let g = GroupElement::generator(&self.parameters.group);
+
+        Commitments {
+            dealer: self.index,
+            values: self.coefficients.iter().map(|a| g.clone().pow_secret(a)).collect(),
+        }
+    }
+
+    /// Returns a proof of knowledge of the secret `a_i0` of the commitment `C_i0`,
+    /// which is broadcast along with the commitments (see `Commitments::verify_possession`).
+    ///
+    /// - context: The context the proof is bound to, e.g. the ID of the election
+    pub fn prove_possession(&self, context: &[u8]) -> SchnorrProof {
+        SchnorrProof::new(PrivateKey {
+            x: self.coefficients[0].clone(),
+        }, context)
+    }
+
+    /// Returns the secret share `f_i(j)` for the trustee `j`,
+    /// which must be sent to the recipient over a private channel.
+    ///
+    /// Returns `Error::IndexOutOfBounds` if the recipient is not within `[1, n]`.
+    ///
+    /// - recipient: The index `j` of the receiving trustee
+    pub fn share_for(&self, recipient: usize) -> Result<SecretShare, Error> {
+        self.parameters.check_index(recipient)?;
+
+        let group = &self.parameters.group;
+        let j = Scalar::new(group, BigInt::from(recipient));
+
+        // evaluate the polynomial using Horner's method
+        let mut value = Scalar::zero(group);
+        for coefficient in self.coefficients.iter().rev() {
+            value = value * j.clone() + coefficient.clone();
+        }
+
+        Ok(SecretShare {
+            dealer: self.index,
+            recipient,
+            value,
+        })
+    }
+}
+
+/// Omits the coefficients, so that they do not end up in logs or panic messages.
+impl Debug for Trustee {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Trustee")
+            .field("index", &self.index)
+            .field("parameters", &self.parameters)
+            .field("coefficients", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+/// # Commitments
+///
+/// The public Feldman commitments `C_ik = g^a_ik` of the dealer `i`.
+/// The first commitment `C_i0` is the dealer's contribution to the joint public key.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize)]
+#[serde(into = "CommitmentsRepr")]
+pub struct Commitments {
+    pub dealer: usize,
+    pub values: Vec<GroupElement>,
+}
+
+/// The serialized form of `Commitments`.
+#[derive(Clone, Serialize, Deserialize)]
+struct CommitmentsRepr {
+    dealer: usize,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex::vec"))]
+    values: Vec<ModInt>,
+}
+
+impl ::std::convert::From<Commitments> for CommitmentsRepr {
+    fn from(commitments: Commitments) -> Self {
+        CommitmentsRepr {
+            dealer: commitments.dealer,
+            values: commitments.values.iter().map(GroupElement::to_mod_int).collect(),
+        }
+    }
+}
+
+impl DeserializeInGroup for Commitments {
+    fn deserialize_in<'de, D: Deserializer<'de>>(group: &Arc<Group>, deserializer: D) -> Result<Self, D::Error> {
+        let repr = CommitmentsRepr::deserialize(deserializer)?;
+
+        Ok(Commitments {
+            dealer: repr.dealer,
+            values: in_group::elements(group, &repr.values)?,
+        })
+    }
+}
+
+impl Commitments {
+    /// Returns `g^f_i(j) = C_i0 * C_i1^j * ... * C_i(t-1)^(j^(t-1))`,
+    /// i.e. the public value matching the secret share for the trustee `j`.
+    ///
+    /// Returns `Error::GroupMismatch` if a commitment belongs to another group.
+    ///
+    /// - group: The parameters `(p, q, g)` of the group
+    /// - index: The index `j` of the trustee
+    pub fn evaluate(&self, group: &Arc<Group>, index: usize) -> Result<GroupElement, Error> {
+        let j = Scalar::new(group, BigInt::from(index));
+
+        let mut result = GroupElement::identity(group);
+        let mut exponent = Scalar::new(group, BigInt::one());
+        for commitment in self.values.iter() {
+            result = result.checked_mul(commitment.clone().checked_pow(&exponent)?)?;
+            exponent = exponent * j.clone();
+        }
+
+        Ok(result)
+    }
+
+    /// Verify that the dealer knows the secret `a_i0` of its commitment `C_i0`.
+    /// Commitments without a valid proof must be rejected before combining the
+    /// public key, as a dealer could otherwise cancel out the contributions of others.
+    ///
+    /// - parameters: The threshold parameters
+    /// - proof: The proof created by the dealer (see `Trustee::prove_possession`)
+    /// - context: The context the proof was created for
+    pub fn verify_possession(&self, parameters: &ThresholdParameters, proof: &SchnorrProof, context: &[u8]) -> bool {
+        match self.values.first() {
+            Some(c_0) if same_group(&parameters.group, c_0.group()) => proof.verify(PublicKey { h: c_0.clone() }, context),
+            _ => false
+        }
+    }
+}
+
+/// # Secret Share
+///
+/// The secret share `f_i(j)` of the dealer `i` for the recipient `j`.
+#[derive(Eq, PartialEq, Clone, Serialize)]
+#[serde(into = "SecretShareRepr")]
+pub struct SecretShare {
+    pub dealer: usize,
+    pub recipient: usize,
+    pub value: Scalar,
+}
+
+/// The serialized form of a `SecretShare`.
+#[derive(Clone, Serialize, Deserialize)]
+struct SecretShareRepr {
+    dealer: usize,
+    recipient: usize,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    value: ModInt,
+}
+
+impl ::std::convert::From<SecretShare> for SecretShareRepr {
+    fn from(share: SecretShare) -> Self {
+        SecretShareRepr {
+            dealer: share.dealer,
+            recipient: share.recipient,
+            value: share.value.to_mod_int(),
+        }
+    }
+}
+
+impl DeserializeInGroup for SecretShare {
+    fn deserialize_in<'de, D: Deserializer<'de>>(group: &Arc<Group>, deserializer: D) -> Result<Self, D::Error> {
+        let repr = SecretShareRepr::deserialize(deserializer)?;
+
+        Ok(SecretShare {
+            dealer: repr.dealer,
+            recipient: repr.recipient,
+            value: in_group::scalar(group, &repr.value)?,
+        })
+    }
+}
+
+impl SecretShare {
+    /// Verify this share against the commitments of its dealer,
+    /// i.e. check that `g^f_i(j) = C_i0 * C_i1^j * ... * C_i(t-1)^(j^(t-1))`.
+    ///
+    /// - parameters: The threshold parameters
+    /// - commitments: The commitments of the dealer of this share
+    pub fn verify(&self, parameters: &ThresholdParameters, commitments: &Commitments) -> bool {
+        if commitments.dealer != self.dealer || commitments.values.len() != parameters.threshold {
+            return false;
+        }
+
+        let group = &parameters.group;
+        if !same_group(group, self.value.group()) {
+            return false;
+        }
+
+        match commitments.evaluate(group, self.recipient) {
+            Ok(expected) => GroupElement::generator(group).pow_secret(&self.value) == expected,
+            Err(_) => false
+        }
+    }
+}
+
+/// Omits the value of the share, so that it does not end up in logs or panic messages.
+impl Debug for SecretShare {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("SecretShare")
+            .field("dealer", &self.dealer)
+            .field("recipient", &self.recipient)
+            .field("value", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+/// # Key Share
+///
+/// The share `x_j = f_1(j) + ... + f_n(j)` of the joint secret key held by the trustee `j`.
+/// Any `t` key shares determine the joint secret key, hence it must be kept secret.
+///
+/// Key shares are serialized along with the parameters `(p, q, g)` of their group.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(into = "KeyShareRepr", try_from = "KeyShareRepr")]
+pub struct KeyShare {
+    pub index: usize,
+    pub x: Scalar,
+}
+
+/// The serialized form of a `KeyShare`.
+#[derive(Clone, Serialize, Deserialize)]
+struct KeyShareRepr {
+    index: usize,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    p: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    q: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    g: ModInt,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    x: ModInt,
+}
+
+impl ::std::convert::From<KeyShare> for KeyShareRepr {
+    fn from(key_share: KeyShare) -> Self {
+        let group = key_share.group();
+
+        KeyShareRepr {
+            index: key_share.index,
+            p: group.p.clone(),
+            q: group.q.clone(),
+            g: group.g.clone(),
+            x: key_share.x.to_mod_int(),
+        }
+    }
+}
+
+impl TryFrom<KeyShareRepr> for KeyShare {
+    type Error = Error;
+
+    fn try_from(repr: KeyShareRepr) -> Result<Self, Error> {
+        let group = Arc::new(Group::new(repr.p.value, repr.q.value, repr.g.value));
+
+        Ok(KeyShare {
+            index: repr.index,
+            x: Scalar::from_mod_int(&group, &repr.x)?,
+        })
+    }
+}
+
+impl KeyShare {
+    /// Combine the secret shares received by the trustee `j` into its key share.
+    ///
+    /// Each share is verified against the commitments of its dealer.
+    /// Returns `Error::InvalidShare` if a share is invalid, so that its dealer can be accused.
+    ///
+    /// - parameters: The threshold parameters
+    /// - index: The index `j` of the receiving trustee
+    /// - shares: The secret shares for the trustee `j`, one of each trustee
+    /// - commitments: The commitments of all trustees
+    pub fn combine(parameters: &ThresholdParameters, index: usize, shares: &[SecretShare], commitments: &[Commitments]) -> Result<Self, Error> {
+        parameters.check_index(index)?;
+        check_commitments(parameters, commitments)?;
+
+        if shares.len() != parameters.trustees {
+            return Err(Error::LengthMismatch { expected: parameters.trustees, actual: shares.len() });
+        }
+
+        let mut x = Scalar::zero(&parameters.group);
+        for (k, share) in shares.iter().enumerate() {
+            if shares[..k].iter().any(|other| other.dealer == share.dealer) {
+                return Err(Error::DuplicateShare { index: share.dealer });
+            }
+
+            let valid = share.recipient == index && commitments.iter()
+                .any(|c| c.dealer == share.dealer && share.verify(parameters, c));
+
+            if !valid {
+                return Err(Error::InvalidShare { dealer: share.dealer, recipient: share.recipient });
+            }
+
+            x = x + share.value.clone();
+        }
+
+        Ok(KeyShare {
+            index,
+            x,
+        })
+    }
+
+    /// Returns the parameters `(p, q, g)` of the group this key share belongs to.
+    pub fn group(&self) -> &Arc<Group> {
+        self.x.group()
+    }
+
+    /// Create the partial decryption `G^x_j` of the given cipher text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cipher text belongs to another group than this key share.
+    ///
+    /// - cipher_text: The cipher text to decrypt
+    pub fn partial_decrypt(&self, cipher_text: &CipherText) -> PartialDecryption {
+        PartialDecryption {
+            index: self.index,
+            value: cipher_text.big_g.clone().pow_secret(&self.x),
+        }
+    }
+
+    /// Returns a proof of knowledge of this key share, which can be checked
+    /// against the verification key of the trustee (see `verification_key`).
+    ///
+    /// - context: The context the proof is bound to, e.g. the ID of the election
+    pub fn prove_possession(&self, context: &[u8]) -> SchnorrProof {
+        SchnorrProof::new(self.private_key(), context)
+    }
+
+    /// Create the partial decryption `G^x_j` of the given cipher text along with
+    /// a proof that it was computed using this key share (see `DecryptionProof`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cipher text belongs to another group than this key share.
+    ///
+    /// - cipher_text: The cipher text to decrypt
+    /// - context: The context the proof is bound to, e.g. the IDs of the election and the question
+    pub fn partial_decrypt_with_proof(&self, cipher_text: &CipherText, context: &[u8]) -> (PartialDecryption, DecryptionProof) {
+        (self.partial_decrypt(cipher_text), DecryptionProof::new(self.private_key(), cipher_text.clone(), context))
+    }
+
+    fn private_key(&self) -> PrivateKey {
+        PrivateKey {
+            x: self.x.clone(),
+        }
+    }
+}
+
+/// Omits the key share `x`, so that it does not end up in logs or panic messages.
+impl Debug for KeyShare {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("KeyShare")
+            .field("index", &self.index)
+            .field("group", self.group())
+            .field("x", &format_args!("<redacted>"))
+            .finish()
+    }
+}
+
+/// # Partial Decryption
+///
+/// The partial decryption `G^x_j` of a cipher text `(G, H)` by the trustee `j`.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize)]
+#[serde(into = "PartialDecryptionRepr")]
+pub struct PartialDecryption {
+    pub index: usize,
+    pub value: GroupElement,
+}
+
+/// The serialized form of a `PartialDecryption`.
+#[derive(Clone, Serialize, Deserialize)]
+struct PartialDecryptionRepr {
+    index: usize,
+    #[cfg_attr(feature = "serde-hex", serde(with = "::arithmetic::serde_string::hex"))]
+    value: ModInt,
+}
+
+impl ::std::convert::From<PartialDecryption> for PartialDecryptionRepr {
+    fn from(partial_decryption: PartialDecryption) -> Self {
+        PartialDecryptionRepr {
+            index: partial_decryption.index,
+            value: partial_decryption.value.to_mod_int(),
+        }
+    }
+}
+
+impl DeserializeInGroup for PartialDecryption {
+    fn deserialize_in<'de, D: Deserializer<'de>>(group: &Arc<Group>, deserializer: D) -> Result<Self, D::Error> {
+        let repr = PartialDecryptionRepr::deserialize(deserializer)?;
+
+        Ok(PartialDecryption {
+            index: repr.index,
+            value: in_group::element(group, &repr.value)?,
+        })
+    }
+}
+
+impl PartialDecryption {
+    /// Verify that this partial decryption was computed using the key share
+    /// of its trustee, i.e. the one matching the trustee's verification key.
+    ///
+    /// - parameters: The threshold parameters
+    /// - cipher_text: The cipher text which was decrypted
+    /// - proof: The proof created along with this partial decryption
+    /// - verification_key: The verification key `g^x_j` of the trustee (see `verification_key`)
+    /// - context: The context the proof was created for
+    pub fn verify(&self, parameters: &ThresholdParameters, cipher_text: &CipherText, proof: &DecryptionProof, verification_key: &GroupElement, context: &[u8]) -> bool {
+        if !same_group(&parameters.group, verification_key.group()) {
+            return false;
+        }
+
+        let public_key = PublicKey { h: verification_key.clone() };
+
+        proof.verify(public_key, cipher_text.clone(), self.value.clone(), context)
+    }
+}
+
+/// Combine the commitments of all trustees into the joint public key `h = C_10 * ... * C_n0`.
+///
+/// Returns an error if not exactly one set of commitments of each trustee is given
+/// and `Error::GroupMismatch` if a commitment belongs to another group.
+/// The commitments must have been checked using `Commitments::verify_possession`.
+///
+/// - parameters: The threshold parameters
+/// - commitments: The commitments of all trustees
+pub fn combine_public_key(parameters: &ThresholdParameters, commitments: &[Commitments]) -> Result<PublicKey, Error> {
+    check_commitments(parameters, commitments)?;
+
+    let h = commitments.iter()
+        .fold(GroupElement::identity(&parameters.group), |h, c| h * c.values[0].clone());
+
+    Ok(PublicKey { h })
+}
+
+/// Returns the verification key `g^x_j` of the trustee `j`, i.e. the public
+/// value matching its key share, computed from the commitments of all trustees.
+///
+/// - parameters: The threshold parameters
+/// - index: The index `j` of the trustee
+/// - commitments: The commitments of all trustees
+pub fn verification_key(parameters: &ThresholdParameters, index: usize, commitments: &[Commitments]) -> Result<GroupElement, Error> {
+    parameters.check_index(index)?;
+    check_commitments(parameters, commitments)?;
+
+    let group = &parameters.group;
+
+    let mut key = GroupElement::identity(group);
+    for c in commitments {
+        key = key * c.evaluate(group, index)?;
+    }
+
+    Ok(key)
+}
+
+/// Combine the partial decryptions of at least `t` trustees into `g^m`
+/// using Lagrange interpolation in the exponent.
+///
+/// Returns `Error::NotEnoughShares` if less than `t` partial decryptions are given,
+/// `Error::DuplicateShare` if a trustee contributed more than once and
+/// `Error::GroupMismatch` if the cipher text or a partial decryption belongs to another group.
+///
+/// - parameters: The threshold parameters
+/// - cipher_text: The cipher text `(G, H)` to decrypt
+/// - partial_decryptions: The partial decryptions of the cipher text
+pub fn combine_partial_decryptions(parameters: &ThresholdParameters, cipher_text: &CipherText, partial_decryptions: &[PartialDecryption]) -> Result<GroupElement, Error> {
+    if partial_decryptions.len() < parameters.threshold {
+        return Err(Error::NotEnoughShares { required: parameters.threshold, actual: partial_decryptions.len() });
+    }
+
+    let mut indices: Vec<usize> = Vec::with_capacity(partial_decryptions.len());
+    for partial_decryption in partial_decryptions {
+        parameters.check_index(partial_decryption.index)?;
+
+        if indices.contains(&partial_decryption.index) {
+            return Err(Error::DuplicateShare { index: partial_decryption.index });
+        }
+
+        indices.push(partial_decryption.index);
+    }
+
+    let group = &parameters.group;
+
+    // G^x = Π (G^x_j)^λ_j
+    let mut big_g_to_x = GroupElement::identity(group);
+    for partial_decryption in partial_decryptions {
+        let lambda = lagrange_coefficient(group, partial_decryption.index, &indices)?;
+
+        big_g_to_x = big_g_to_x.checked_mul(partial_decryption.value.clone().checked_pow(&lambda)?)?;
+    }
+
+    cipher_text.big_h.clone().checked_div(big_g_to_x)
+}
+
+/// Decrypt the given cipher text by combining the partial decryptions of at least `t` trustees
+/// and recovering the plain text from `g^m` (see `combine_partial_decryptions`).
+///
+/// Returns `Error::PlainTextNotFound` if the plain text is out of the range of the discrete log.
+///
+/// - parameters: The threshold parameters
+/// - cipher_text: The cipher text to decrypt
+/// - partial_decryptions: The partial decryptions of the cipher text
+/// - discrete_log: The precomputed table used to recover the plain text
+pub fn decrypt(parameters: &ThresholdParameters, cipher_text: &CipherText, partial_decryptions: &[PartialDecryption], discrete_log: &DiscreteLog) -> Result<Scalar, Error> {
+    let g_to_m = combine_partial_decryptions(parameters, cipher_text, partial_decryptions)?;
+
+    discrete_log.solve(&g_to_m)
+}
+
+/// Check that exactly one set of `t` commitments of each trustee is given
+/// and that all commitments belong to the group of the parameters.
+///
+/// Returns `Error::GroupMismatch` for commitments of another group,
+/// as they would move the joint public key out of the group.
+fn check_commitments(parameters: &ThresholdParameters, commitments: &[Commitments]) -> Result<(), Error> {
+    if commitments.len() != parameters.trustees {
+        return Err(Error::LengthMismatch { expected: parameters.trustees, actual: commitments.len() });
+    }
+
+    let group = &parameters.group;
+
+    let mut dealers: Vec<usize> = Vec::with_capacity(commitments.len());
+    for c in commitments {
+        parameters.check_index(c.dealer)?;
+
+        if dealers.contains(&c.dealer) {
+            return Err(Error::DuplicateShare { index: c.dealer });
+        }
+
+        if c.values.len() != parameters.threshold {
+            return Err(Error::LengthMismatch { expected: parameters.threshold, actual: c.values.len() });
+        }
+
+        if !c.values.iter().all(|value| same_group(group, value.group())) {
+            return Err(Error::GroupMismatch);
+        }
+
+        dealers.push(c.dealer);
+    }
+
+    Ok(())
+}
+
+/// Returns the Lagrange coefficient `λ_i = Π j / (j - i) mod q` over all other indices `j`,
+/// which interpolates the value at zero of a polynomial from its values at the given indices.
+fn lagrange_coefficient(group: &Arc<Group>, index: usize, indices: &[usize]) -> Result<Scalar, Error> {
+    let q = &group.q.value;
+    let i = BigInt::from(index);
+
+    let mut numerator = BigInt::one();
+    let mut denominator = BigInt::one();
+    for &other in indices.iter().filter(|&&other| other != index) {
+        let j = BigInt::from(other);
+
+        numerator = (numerator * &j).mod_floor(q);
+        denominator = (denominator * (j - &i)).mod_floor(q);
+    }
+
+    let inverse = mod_inverse::mod_inverse(denominator, q.clone()).ok_or(Error::NotInvertible)?;
+
+    Ok(Scalar::new(group, numerator * inverse))
+}
+
+#[cfg(test)]
+mod threshold_test {
+
+    use ::arithmetic::group::Group;
+    use ::arithmetic::group_element::GroupElement;
+    use ::arithmetic::in_group;
+    use ::arithmetic::scalar::Scalar;
+    use ::el_gamal::encryption::{encrypt, DiscreteLog, KeyPair, PublicKey};
+    use ::el_gamal::threshold::{combine_partial_decryptions, combine_public_key, decrypt, verification_key};
+    use ::el_gamal::threshold::{Commitments, KeyShare, PartialDecryption, SecretShare, ThresholdParameters, Trustee};
+    use ::error::Error;
+    use ::num::bigint::BigInt;
+    use ::num::traits::Pow;
+    use std::sync::Arc;
+
+    const CONTEXT: &[u8] = b"election-1";
+
+    fn parameters(threshold: usize, trustees: usize) -> ThresholdParameters {
+        let group = Arc::new(Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4)));
+
+        ThresholdParameters::new(&group, threshold, trustees).unwrap()
+    }
+
+    fn generate(parameters: &ThresholdParameters) -> (Vec<Commitments>, Vec<Vec<SecretShare>>) {
+        let trustees: Vec<Trustee> = (1..=parameters.trustees)
+            .map(|i| Trustee::new(parameters, i).unwrap())
+            .collect();
+
+        let commitments = trustees.iter().map(|t| t.commitments()).collect();
+        let shares = (1..=parameters.trustees)
+            .map(|j| trustees.iter().map(|t| t.share_for(j).unwrap()).collect())
+            .collect();
+
+        (commitments, shares)
+    }
+
+    #[test]
+    fn test_threshold_decrypt() {
+        let parameters = parameters(2, 3);
+        let (commitments, shares) = generate(&parameters);
+
+        let public_key = combine_public_key(&parameters, &commitments).unwrap();
+        assert!(public_key.validate().is_ok());
+
+        let key_shares: Vec<KeyShare> = shares.iter().enumerate()
+            .map(|(j, shares)| KeyShare::combine(&parameters, j + 1, shares, &commitments).unwrap())
+            .collect();
+
+        for key_share in key_shares.iter() {
+            let expected = verification_key(&parameters, key_share.index, &commitments).unwrap();
+            assert_eq!(expected, GroupElement::generator(&parameters.group).pow(&key_share.x));
+        }
+
+        let message = Scalar::new(&parameters.group, BigInt::from(7));
+        let (cipher_text, _) = encrypt(&public_key, message.clone());
+        let discrete_log = DiscreteLog::new(public_key.group(), 100);
+
+        let subsets: Vec<Vec<usize>> = vec![vec![0, 1], vec![0, 2], vec![1, 2], vec![2, 0, 1]];
+        for subset in subsets {
+            let partial_decryptions: Vec<_> = subset.iter()
+                .map(|&j| key_shares[j].partial_decrypt(&cipher_text))
+                .collect();
+
+            let plain_text = decrypt(&parameters, &cipher_text, &partial_decryptions, &discrete_log).unwrap();
+            assert_eq!(message, plain_text);
+        }
+    }
+
+    #[test]
+    fn test_prove_possession() {
+        let parameters = parameters(2, 3);
+
+        let trustee = Trustee::new(&parameters, 1).unwrap();
+        let other = Trustee::new(&parameters, 2).unwrap();
+
+        let commitments = trustee.commitments();
+        assert!(commitments.verify_possession(&parameters, &trustee.prove_possession(CONTEXT), CONTEXT));
+        assert!(!commitments.verify_possession(&parameters, &other.prove_possession(CONTEXT), CONTEXT));
+
+        // a rogue commitment, e.g. C_10 / C_20, comes without a proof of knowledge
+        let rogue = Commitments {
+            dealer: 1,
+            values: vec![commitments.values[0].clone() / other.commitments().values[0].clone(), commitments.values[1].clone()],
+        };
+        assert!(!rogue.verify_possession(&parameters, &trustee.prove_possession(CONTEXT), CONTEXT));
+
+        let (all_commitments, shares) = generate(&parameters);
+        let key_share = KeyShare::combine(&parameters, 3, &shares[2], &all_commitments).unwrap();
+        let key = verification_key(&parameters, 3, &all_commitments).unwrap();
+
+        assert!(key_share.prove_possession(CONTEXT).verify(PublicKey { h: key }, CONTEXT));
+    }
+
+    #[test]
+    fn test_verify_partial_decryption() {
+        let parameters = parameters(2, 3);
+        let (commitments, shares) = generate(&parameters);
+
+        let public_key = combine_public_key(&parameters, &commitments).unwrap();
+        let key_share = KeyShare::combine(&parameters, 2, &shares[1], &commitments).unwrap();
+
+        let (cipher_text, _) = encrypt(&public_key, Scalar::new(&parameters.group, BigInt::from(5)));
+        let (partial_decryption, proof) = key_share.partial_decrypt_with_proof(&cipher_text, CONTEXT);
+
+        let key_2 = verification_key(&parameters, 2, &commitments).unwrap();
+        let key_3 = verification_key(&parameters, 3, &commitments).unwrap();
+
+        assert!(partial_decryption.verify(&parameters, &cipher_text, &proof, &key_2, CONTEXT));
+        assert!(!partial_decryption.verify(&parameters, &cipher_text, &proof, &key_3, CONTEXT));
+
+        let forged = PartialDecryption {
+            index: 2,
+            value: partial_decryption.value.clone() * GroupElement::generator(&parameters.group),
+        };
+        assert!(!forged.verify(&parameters, &cipher_text, &proof, &key_2, CONTEXT));
+
+        // the partial decryption can be published along with its proof
+        let json = ::serde_json::to_string(&partial_decryption).unwrap();
+        assert_eq!(partial_decryption, in_group::from_str(&parameters.group, &json).unwrap());
+    }
+
+    #[test]
+    fn test_commitment_of_other_group_rejected() {
+        let parameters = parameters(2, 3);
+        let (mut commitments, _) = generate(&parameters);
+        let trustee = Trustee::new(&parameters, 1).unwrap();
+
+        // -g^a has order 2q and would move the joint public key out of the subgroup
+        let p = parameters.group.p.value.clone();
+        let c_0 = trustee.commitments().values[0].clone();
+        match GroupElement::new(&parameters.group, p - c_0.value()) {
+            Err(Error::ElementNotInGroup) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let other_key_pair = KeyPair::generate(&Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+        let rogue = Commitments {
+            dealer: 1,
+            values: vec![other_key_pair.public_key.h.clone(), commitments[0].values[1].clone()],
+        };
+        assert!(!rogue.verify_possession(&parameters, &trustee.prove_possession(CONTEXT), CONTEXT));
+
+        commitments[0] = rogue;
+        match combine_public_key(&parameters, &commitments) {
+            Err(Error::GroupMismatch) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match verification_key(&parameters, 1, &commitments) {
+            Err(Error::GroupMismatch) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let parameters = parameters(2, 3);
+        let (commitments, shares) = generate(&parameters);
+
+        let trustee = Trustee::new(&parameters, 1).unwrap();
+        let share = trustee.share_for(2).unwrap();
+        let key_share = KeyShare::combine(&parameters, 1, &shares[0], &commitments).unwrap();
+
+        assert!(format!("{:?}", trustee).contains("coefficients: <redacted>"));
+        assert_eq!("SecretShare { dealer: 1, recipient: 2, value: <redacted> }", format!("{:?}", share));
+        assert!(format!("{:?}", key_share).ends_with("x: <redacted> }"));
+    }
+
+    #[test]
+    fn test_serialize_shares() {
+        let parameters = parameters(2, 3);
+        let (commitments, shares) = generate(&parameters);
+        let key_share = KeyShare::combine(&parameters, 1, &shares[0], &commitments).unwrap();
+
+        let json = ::serde_json::to_string(&shares[0][1]).unwrap();
+        let share: SecretShare = in_group::from_str(&parameters.group, &json).unwrap();
+        assert!(share.verify(&parameters, &commitments[1]));
+
+        let json = ::serde_json::to_string(&commitments).unwrap();
+        assert_eq!(commitments, in_group::from_str::<Vec<Commitments>>(&parameters.group, &json).unwrap());
+
+        let json = ::serde_json::to_string(&key_share).unwrap();
+        let read_key_share: KeyShare = ::serde_json::from_str(&json).unwrap();
+        assert_eq!(key_share.index, read_key_share.index);
+        assert_eq!(key_share.x, read_key_share.x);
+
+        let json = ::serde_json::to_string(&parameters).unwrap();
+        assert_eq!(parameters, ::serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let group = Arc::new(Group::new(BigInt::from(23), BigInt::from(11), BigInt::from(4)));
+
+        assert!(ThresholdParameters::new(&group, 0, 3).is_err());
+        assert!(ThresholdParameters::new(&group, 4, 3).is_err());
+        assert!(ThresholdParameters::new(&group, 3, 11).is_err());
+        assert!(ThresholdParameters::new(&group, 3, 10).is_ok());
+
+        let parameters = ThresholdParameters::new(&group, 2, 3).unwrap();
+        assert!(Trustee::new(&parameters, 0).is_err());
+        assert!(Trustee::new(&parameters, 4).is_err());
+        assert!(Trustee::new(&parameters, 1).unwrap().share_for(4).is_err());
+    }
+
+    #[test]
+    fn test_invalid_share() {
+        let parameters = parameters(2, 3);
+        let (commitments, mut shares) = generate(&parameters);
+
+        assert!(shares[0][1].verify(&parameters, &commitments[1]));
+        assert!(!shares[0][1].verify(&parameters, &commitments[2]));
+
+        shares[0][1].value = shares[0][1].value.clone() + Scalar::new(&parameters.group, BigInt::from(1));
+        assert!(!shares[0][1].verify(&parameters, &commitments[1]));
+
+        match KeyShare::combine(&parameters, 1, &shares[0], &commitments) {
+            Err(Error::InvalidShare { dealer: 2, recipient: 1 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // shares addressed to another trustee are rejected as well
+        match KeyShare::combine(&parameters, 1, &shares[1], &commitments) {
+            Err(Error::InvalidShare { dealer: 1, recipient: 2 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_not_enough_partial_decryptions() {
+        let parameters = parameters(2, 3);
+        let (commitments, shares) = generate(&parameters);
+
+        let public_key = combine_public_key(&parameters, &commitments).unwrap();
+        let key_share = KeyShare::combine(&parameters, 1, &shares[0], &commitments).unwrap();
+
+        let (cipher_text, _) = encrypt(&public_key, Scalar::new(&parameters.group, BigInt::from(1)));
+        let mut partial_decryptions = vec![key_share.partial_decrypt(&cipher_text)];
+
+        match combine_partial_decryptions(&parameters, &cipher_text, &partial_decryptions) {
+            Err(Error::NotEnoughShares { required: 2, actual: 1 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        partial_decryptions.push(key_share.partial_decrypt(&cipher_text));
+        match combine_partial_decryptions(&parameters, &cipher_text, &partial_decryptions) {
+            Err(Error::DuplicateShare { index: 1 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use ::arithmetic::group::Group;
+use ::arithmetic::group_element::GroupElement;
+use ::arithmetic::mod_int::ModInt;
+use ::arithmetic::scalar::Scalar;
+use ::el_gamal::ciphertext::CipherText;
+use ::el_gamal::encryption::PublicKey;
+use num::bigint::{BigInt, Sign};
+use num::Integer;
+use num::Zero;
+use sha2::{Digest, Sha512};
+use std::sync::Arc;
+
+/// The domain separator absorbed by every transcript of this library.
+const DOMAIN_SEPARATOR: &[u8] = b"crypto-rs transcript v1";
+
+/// The amount of additional bits of a challenge before reducing it modulo `q`,
+/// so that the bias of the challenge is negligible.
+const CHALLENGE_SECURITY_BITS: usize = 128;
+
+/// # Fiat-Shamir Transcript
+///
+/// Derives the challenges of non-interactive proofs from all public inputs
+/// of the statement and the commitments of the prover.
+///
+/// Every input is absorbed along with a label, and both are prefixed with
+/// their length, so that different sequences of inputs can never result in
+/// the same encoding. Elements are encoded canonically, i.e. reduced
+/// modulo their modulus and as big-endian bytes.
+#[derive(Clone, Debug)]
+pub struct Transcript {
+    hasher: Sha512,
+}
+
+impl Transcript {
+    /// Create a new transcript for the given protocol.
+    ///
+    /// - protocol: The name of the protocol, which separates the challenges of different proofs
+    pub fn new(protocol: &[u8]) -> Self {
+        let mut transcript = Transcript {
+            hasher: Sha512::default(),
+        };
+
+        transcript.append_message(b"domain-separator", DOMAIN_SEPARATOR);
+        transcript.append_message(b"protocol", protocol);
+
+        transcript
+    }
+
+    /// Absorb the given bytes.
+    ///
+    /// - label: The label describing the message
+    /// - message: The message to absorb
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.hasher.input(&(label.len() as u64).to_be_bytes());
+        self.hasher.input(label);
+        self.hasher.input(&(message.len() as u64).to_be_bytes());
+        self.hasher.input(message);
+    }
+
+    /// Absorb the given number.
+    pub fn append_u64(&mut self, label: &[u8], value: u64) {
+        self.append_message(label, &value.to_be_bytes());
+    }
+
+    /// Absorb the given integer, encoded as its sign followed by its big-endian magnitude.
+    pub fn append_bigint(&mut self, label: &[u8], value: &BigInt) {
+        let (sign, magnitude) = value.to_bytes_be();
+
+        let mut message: Vec<u8> = Vec::with_capacity(magnitude.len() + 1);
+        message.push(match sign {
+            Sign::Minus => 0xff,
+            Sign::NoSign => 0x00,
+            Sign::Plus => 0x01,
+        });
+        message.extend_from_slice(&magnitude);
+
+        self.append_message(label, &message);
+    }
+
+    /// Absorb the given element, reduced modulo its modulus.
+    /// Elements without a modulus are absorbed as plain integers.
+    pub fn append_modint(&mut self, label: &[u8], value: &ModInt) {
+        if value.modulus.is_zero() {
+            self.append_bigint(label, &value.value);
+        } else {
+            self.append_bigint(label, &value.value.mod_floor(&value.modulus));
+        }
+    }
+
+    /// Absorb the given elements, prefixed by their amount.
+    pub fn append_modints(&mut self, label: &[u8], values: &[ModInt]) {
+        self.append_u64(label, values.len() as u64);
+
+        for value in values {
+            self.append_modint(label, value);
+        }
+    }
+
+    /// Absorb the given element of a group.
+    pub fn append_element(&mut self, label: &[u8], value: &GroupElement) {
+        self.append_bigint(label, value.value());
+    }
+
+    /// Absorb the given elements of a group, prefixed by their amount.
+    pub fn append_elements(&mut self, label: &[u8], values: &[GroupElement]) {
+        self.append_u64(label, values.len() as u64);
+
+        for value in values {
+            self.append_element(label, value);
+        }
+    }
+
+    /// Absorb the given scalar of a group.
+    pub fn append_scalar(&mut self, label: &[u8], value: &Scalar) {
+        self.append_bigint(label, value.value());
+    }
+
+    /// Absorb the given scalars of a group, prefixed by their amount.
+    pub fn append_scalars(&mut self, label: &[u8], values: &[Scalar]) {
+        self.append_u64(label, values.len() as u64);
+
+        for value in values {
+            self.append_scalar(label, value);
+        }
+    }
+
+    /// Absorb the parameters `(p, q, g)` of the given group.
+    pub fn append_group(&mut self, group: &Group) {
+        self.append_bigint(b"p", &group.p.value);
+        self.append_bigint(b"q", &group.q.value);
+        self.append_bigint(b"g", &group.g.value.mod_floor(&group.p.value));
+    }
+
+    /// Absorb all parameters `(p, q, g, h)` of the given public key.
+    pub fn append_public_key(&mut self, public_key: &PublicKey) {
+        self.append_group(public_key.group());
+        self.append_element(b"h", &public_key.h);
+    }
+
+    /// Absorb both elements `(G, H)` of the given cipher text.
+    pub fn append_cipher_text(&mut self, label: &[u8], cipher_text: &CipherText) {
+        self.append_message(b"cipher-text", label);
+        self.append_element(b"big-g", &cipher_text.big_g);
+        self.append_element(b"big-h", &cipher_text.big_h);
+    }
+
+    /// Derive a challenge within `Z_q` from all inputs absorbed so far.
+    /// The challenge is absorbed as well, so that subsequent challenges differ.
+    ///
+    /// - label: The label describing the challenge
+    /// - group: The group whose order `q` the challenge is reduced by
+    pub fn challenge(&mut self, label: &[u8], group: &Arc<Group>) -> Scalar {
+        self.append_message(b"challenge", label);
+
+        let q = &group.q.value;
+        let length = (q.bits() + CHALLENGE_SECURITY_BITS).div_ceil(8);
+
+        let mut bytes: Vec<u8> = Vec::with_capacity(length);
+        let mut counter: u64 = 0;
+        while bytes.len() < length {
+            let mut hasher = self.hasher.clone();
+            hasher.input(&counter.to_be_bytes());
+            bytes.extend_from_slice(&hasher.result());
+
+            counter += 1;
+        }
+        bytes.truncate(length);
+
+        let challenge = Scalar::new(group, BigInt::from_bytes_be(Sign::Plus, &bytes));
+        self.append_scalar(label, &challenge);
+
+        challenge
+    }
+}
+
+#[cfg(test)]
+mod transcript_test {
+
+    use ::arithmetic::group::Group;
+    use ::arithmetic::group_element::GroupElement;
+    use ::arithmetic::mod_int::From;
+    use ::arithmetic::mod_int::ModInt;
+    use ::arithmetic::scalar::Scalar;
+    use ::el_gamal::transcript::Transcript;
+    use ::num::bigint::BigInt;
+    use std::sync::Arc;
+
+    fn group() -> Arc<Group> {
+        Arc::new(Group::new(BigInt::from(2199023255867i64), BigInt::from(1099511627933i64), BigInt::from(4)))
+    }
+
+    #[test]
+    fn test_challenge_is_deterministic() {
+        let group = group();
+
+        let mut first = Transcript::new(b"test");
+        let mut second = Transcript::new(b"test");
+        first.append_modint(b"x", &ModInt::from_value(BigInt::from(42)));
+        second.append_modint(b"x", &ModInt::from_value(BigInt::from(42)));
+
+        let challenge = first.challenge(b"c", &group);
+        assert_eq!(challenge, second.challenge(b"c", &group));
+        assert!(*challenge.value() < group.q.value);
+
+        // subsequent challenges differ
+        assert_ne!(first.challenge(b"c", &group), challenge);
+    }
+
+    #[test]
+    fn test_encoding_is_unambiguous() {
+        let group = group();
+
+        let mut first = Transcript::new(b"test");
+        first.append_message(b"a", b"bc");
+
+        let mut second = Transcript::new(b"test");
+        second.append_message(b"ab", b"c");
+
+        let mut third = Transcript::new(b"test");
+        third.append_message(b"a", b"b");
+        third.append_message(b"", b"c");
+
+        let challenge = first.challenge(b"c", &group);
+        assert_ne!(challenge, second.challenge(b"c", &group));
+        assert_ne!(challenge, third.challenge(b"c", &group));
+
+        // the protocol separates the challenges as well
+        let mut other = Transcript::new(b"other");
+        other.append_message(b"a", b"bc");
+        assert_ne!(challenge, other.challenge(b"c", &group));
+    }
+
+    #[test]
+    fn test_elements_are_reduced() {
+        let group = group();
+
+        let mut first = Transcript::new(b"test");
+        first.append_modint(b"x", &ModInt::from_value_modulus(BigInt::from(3), BigInt::from(7)));
+
+        let mut second = Transcript::new(b"test");
+        second.append_modint(b"x", &ModInt { value: BigInt::from(-4), modulus: BigInt::from(7) });
+
+        let mut third = Transcript::new(b"test");
+        third.append_modint(b"x", &ModInt::from_value(BigInt::from(-3)));
+
+        let challenge = first.challenge(b"c", &group);
+        assert_eq!(challenge, second.challenge(b"c", &group));
+        assert_ne!(challenge, third.challenge(b"c", &group));
+    }
+
+    #[test]
+    fn test_typed_values_match_modints() {
+        let group = group();
+
+        let element = GroupElement::generator(&group);
+        let scalar = Scalar::new(&group, BigInt::from(-1));
+
+        let mut first = Transcript::new(b"test");
+        first.append_element(b"x", &element);
+        first.append_scalars(b"y", ::std::slice::from_ref(&scalar));
+
+        let mut second = Transcript::new(b"test");
+        second.append_modint(b"x", &element.to_mod_int());
+        second.append_modints(b"y", &[scalar.to_mod_int()]);
+
+        assert_eq!(first.challenge(b"c", &group), second.challenge(b"c", &group));
+    }
+}
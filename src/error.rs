@@ -0,0 +1,95 @@
+use ::arithmetic::group::ValidationError;
+use ::el_gamal::encryption::KeyError;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// # Error
+///
+/// The errors returned by the fallible `checked_*` and `try_*` operations
+/// of this library.
+#[derive(Debug)]
+pub enum Error {
+    /// The divisor is zero or congruent to zero modulo the modulus.
+    DivisionByZero,
+    /// The divisor has no inverse modulo the modulus.
+    NotInvertible,
+    /// The exponent is negative, or too large for an exponentiation without modulus.
+    ExponentOutOfRange,
+    /// The given string is not a valid hexadecimal number.
+    InvalidHexString(String),
+    /// The amount of given elements does not match the expected amount.
+    LengthMismatch { expected: usize, actual: usize },
+    /// The given index does not refer to an existing element.
+    IndexOutOfBounds { index: usize, length: usize },
+    /// The plain-text message is not an element of the given domain.
+    PlainTextNotInDomain,
+    /// No plain-text message within the supported range matches the cipher text.
+    PlainTextNotFound,
+    /// The threshold is not within `[1, trustees]` or there are too many trustees for the group.
+    InvalidThreshold { threshold: usize, trustees: usize },
+    /// The secret share of the dealer for the recipient does not match the dealer's commitments.
+    InvalidShare { dealer: usize, recipient: usize },
+    /// Less shares than required by the threshold were given.
+    NotEnoughShares { required: usize, actual: usize },
+    /// More than one share was given for the same trustee.
+    DuplicateShare { index: usize },
+    /// The byte encoding is malformed, e.g. truncated or followed by trailing bytes.
+    InvalidEncoding,
+    /// The decoded value is not reduced modulo its modulus.
+    ValueNotReduced,
+    /// The decoded value is not an element of the subgroup of order `q`.
+    ElementNotInGroup,
+    /// The operands belong to different groups.
+    GroupMismatch,
+    /// Group parameters or a public key are invalid.
+    Validation(ValidationError),
+    /// A key could not be loaded or saved.
+    Key(KeyError),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            Error::DivisionByZero => write!(f, "Division by zero is not defined"),
+            Error::NotInvertible => write!(f, "failed to compute inverse"),
+            Error::ExponentOutOfRange => write!(f, "the exponent is negative or too large"),
+            Error::InvalidHexString(ref hex_string) => write!(f, "invalid hex string: {:?}", hex_string),
+            Error::LengthMismatch { expected, actual } => write!(f, "expected {} elements, got {}", expected, actual),
+            Error::IndexOutOfBounds { index, length } => write!(f, "index {} is out of bounds for {} elements", index, length),
+            Error::PlainTextNotInDomain => write!(f, "the plain-text is not an element of the domain"),
+            Error::PlainTextNotFound => write!(f, "no plain-text within the supported range matches the cipher text"),
+            Error::InvalidThreshold { threshold, trustees } => write!(f, "invalid threshold {} for {} trustees", threshold, trustees),
+            Error::InvalidShare { dealer, recipient } => write!(f, "the share of trustee {} for trustee {} is invalid", dealer, recipient),
+            Error::NotEnoughShares { required, actual } => write!(f, "expected at least {} shares, got {}", required, actual),
+            Error::DuplicateShare { index } => write!(f, "more than one share of trustee {}", index),
+            Error::InvalidEncoding => write!(f, "the byte encoding is malformed"),
+            Error::ValueNotReduced => write!(f, "the value is not reduced modulo its modulus"),
+            Error::ElementNotInGroup => write!(f, "the value is not an element of the subgroup of order q"),
+            Error::GroupMismatch => write!(f, "the operands belong to different groups"),
+            Error::Validation(ref e) => write!(f, "validation failed: {}", e),
+            Error::Key(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            Error::Validation(ref e) => Some(e),
+            Error::Key(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl ::std::convert::From<ValidationError> for Error {
+    fn from(error: ValidationError) -> Self {
+        Error::Validation(error)
+    }
+}
+
+impl ::std::convert::From<KeyError> for Error {
+    fn from(error: KeyError) -> Self {
+        Error::Key(error)
+    }
+}
@@ -1,6 +1,7 @@
 //! This library provides common cryptographic functionality for working within
 //! the exponential ElGamal cryptosystem.
 
+extern crate base64;
 extern crate num;
 extern crate rand;
 extern crate sha2;
@@ -10,6 +11,9 @@ extern crate serde_derive;
 extern crate serde;
 extern crate serde_json;
 
+#[cfg(test)]
+extern crate proptest;
+
 /// Adds support for modular arithmetic within a cyclic field of integers.
 pub mod arithmetic;
 
@@ -21,3 +25,11 @@ pub mod cai;
 /// proofs can be generated for a ciphertext, ensuring that the encrypted plain-text message
 /// is within a particular bound.
 pub mod el_gamal;
+
+/// The error type of all fallible operations.
+pub mod error;
+
+/// Distributes work over scoped threads if the `parallel` feature is enabled.
+mod parallel;
+
+pub use error::Error;
@@ -0,0 +1,121 @@
+use std::thread;
+
+/// Returns the amount of threads to distribute work over, i.e. the amount
+/// of available cores if the `parallel` feature is enabled and one otherwise.
+pub(crate) fn threads() -> usize {
+    if cfg!(feature = "parallel") {
+        thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1)
+    } else {
+        1
+    }
+}
+
+/// Apply the given function to all items, using up to `threads` scoped threads.
+///
+/// The items are split into contiguous chunks, one per thread, and the results
+/// are returned in the order of the items, regardless of the amount of threads.
+pub(crate) fn map<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+    where T: Sync, R: Send, F: Fn(&T) -> R + Sync {
+    let chunk_size = chunk_size(items.len(), threads);
+    if chunk_size >= items.len() {
+        return items.iter().map(f).collect();
+    }
+
+    let f = &f;
+    thread::scope(|scope| {
+        let workers: Vec<_> = items.chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+
+        workers.into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    })
+}
+
+/// Combine all items using the given associative operation, using up to `threads` scoped threads.
+///
+/// Each thread combines a contiguous chunk of the items, after which the results
+/// of the chunks are combined in order. Hence, the result is the one of combining
+/// the items from left to right, regardless of the amount of threads.
+///
+/// Returns `None` if no items are given.
+pub(crate) fn reduce<T, F>(items: Vec<T>, threads: usize, f: F) -> Option<T>
+    where T: Send, F: Fn(T, T) -> T + Sync {
+    let chunk_size = chunk_size(items.len(), threads);
+    if chunk_size >= items.len() {
+        return fold(items, &f);
+    }
+
+    let mut chunks: Vec<Vec<T>> = Vec::new();
+    let mut items = items.into_iter().peekable();
+    while items.peek().is_some() {
+        chunks.push(items.by_ref().take(chunk_size).collect());
+    }
+
+    let f = &f;
+    let partials: Vec<T> = thread::scope(|scope| {
+        let workers: Vec<_> = chunks.into_iter()
+            .map(|chunk| scope.spawn(move || fold(chunk, f)))
+            .collect();
+
+        workers.into_iter()
+            .filter_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    fold(partials, f)
+}
+
+fn fold<T, F: Fn(T, T) -> T>(items: Vec<T>, f: &F) -> Option<T> {
+    let mut items = items.into_iter();
+    let first = items.next()?;
+
+    Some(items.fold(first, f))
+}
+
+/// Returns the amount of items per thread, which is at least one.
+fn chunk_size(amount: usize, threads: usize) -> usize {
+    amount.div_ceil(threads.max(1)).max(1)
+}
+
+#[cfg(test)]
+mod parallel_test {
+
+    use ::parallel::{map, reduce, threads};
+
+    #[test]
+    fn test_threads() {
+        if cfg!(feature = "parallel") {
+            assert!(threads() >= 1);
+        } else {
+            assert_eq!(1, threads());
+        }
+    }
+
+    #[test]
+    fn test_map() {
+        let items: Vec<u64> = (0..100).collect();
+        let expected: Vec<u64> = items.iter().map(|item| item * item).collect();
+
+        for threads in [0, 1, 2, 3, 7, 100, 200].iter() {
+            assert_eq!(expected, map(&items, *threads, |item| item * item));
+        }
+
+        assert!(map(&Vec::<u64>::new(), 4, |item| item * item).is_empty());
+    }
+
+    #[test]
+    fn test_reduce() {
+        let items: Vec<String> = (0..100).map(|item| item.to_string()).collect();
+
+        // concatenation is associative but not commutative, hence the order must be preserved
+        let expected = items.concat();
+
+        for threads in [0, 1, 2, 3, 7, 100, 200].iter() {
+            assert_eq!(Some(expected.clone()), reduce(items.clone(), *threads, |a, b| a + &b));
+        }
+
+        assert_eq!(None, reduce(Vec::<String>::new(), 4, |a, b| a + &b));
+    }
+}